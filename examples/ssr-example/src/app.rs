@@ -1,10 +1,10 @@
 //! Example using leptos-leaflet-hotline [HotPolyline] component
-use leptos::{component, logging::*, view, IntoView};
-use leptos::prelude::{AutoReload, ClassAttribute, Effect, ElementChild, Get, GlobalAttributes, HydrationScripts, LeptosOptions};
+use leptos::{component, logging::*, server, view, IntoView};
+use leptos::prelude::{AutoReload, ClassAttribute, Effect, ElementChild, Get, GlobalAttributes, HydrationScripts, LeptosOptions, Resource, ServerFnError};
 use leptos_meta::MetaTags;
 use leptos_leaflet::leaflet as L;
 use leptos_leaflet::prelude::{JsRwSignal, create_map_signal, MapContainer, MapEvents, Position, TileLayer};
-use leptos_leaflet_hotline::{HotPolyline, HotlinePalette, HotlinePositionVec};
+use leptos_leaflet_hotline::{fetch_packed_chunks, HotPolylineResource, HotlinePalette, PackedChunk};
 
 use leptos_meta::{provide_meta_context, Script, Stylesheet, Title};
 use leptos_router::components::*;
@@ -21,7 +21,6 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
                 <meta name="viewport" content="width=device-width, initial-scale=1"/>
                 <Stylesheet id="leaflet" href="https://unpkg.com/leaflet@1.9.3/dist/leaflet.css"/>
                 <Script src="https://unpkg.com/leaflet@1.9.3/dist/leaflet.js"/>
-                <Script src="https://unpkg.com/leaflet-hotline@0.4.0/src/leaflet.hotline.js" />
                 <Stylesheet id="leptos" href="/pkg/leptos-leaflet-hotline-ssr.css"/>
                 <AutoReload options=options.clone()/>
                 <HydrationScripts options/>
@@ -59,6 +58,38 @@ pub fn App() -> impl IntoView {
     }
 }
 
+/// number of track points sent per server function call
+#[cfg(feature = "ssr")]
+const POINTS_PER_CHUNK: usize = 5_000;
+
+/// Loads one chunk of the demo track on the server, packed in the compact binary form.
+/// Only the points of the requested chunk are generated and packed.
+#[server]
+pub async fn track_chunk(index: usize) -> Result<PackedChunk, ServerFnError> {
+    let total = DEMO_POINTS.div_ceil(POINTS_PER_CHUNK);
+    if index >= total {
+        return Err(ServerFnError::new(format!("no track chunk {index}")));
+    }
+    let start = index * POINTS_PER_CHUNK;
+    let end = (start + POINTS_PER_CHUNK).min(DEMO_POINTS);
+    let points: Vec<(f64, f64, f64)> = (start..end).map(demo_point).collect();
+    // the values are normalized over the whole track once the chunks are reassembled
+    let positions = leptos_leaflet_hotline::HotlinePositionVec::new(&points).to_packed();
+    Ok(PackedChunk::new(index, total, positions))
+}
+
+/// number of points in the demo track
+#[cfg(feature = "ssr")]
+const DEMO_POINTS: usize = 20_000;
+
+/// Point `i` of a 20 000 point loop around the map center with a value varying along
+/// the way, standing in for a track read from a file or database.
+#[cfg(feature = "ssr")]
+fn demo_point(i: usize) -> (f64, f64, f64) {
+    let t = i as f64 / DEMO_POINTS as f64 * std::f64::consts::TAU;
+    (40.2928 + 0.002 * t.sin(), -105.6170 + 0.003 * t.cos(), 50.0 + 40.0 * (5.0 * t).sin())
+}
+
 /// Renders the home page of your application.
 #[component]
 fn HomePage() -> impl IntoView {
@@ -77,12 +108,16 @@ fn HomePage() -> impl IntoView {
 
     let events = MapEvents::new().location_found(location_found);
 
+    // loaded on the server for the first render, then fetched chunk by chunk on refetch
+    let track = Resource::new(|| (), |_| fetch_packed_chunks(track_chunk));
+
     view! {
         <div class="map-parent" >
-        <MapContainer class="map-container" center=Position::new(40.2928, -105.6170) zoom=17.0 map=set_map set_view=true events>
+        <MapContainer class="map-container" center=Position::new(40.2928, -105.6170) zoom=16.0 map=set_map set_view=true events>
             <TileLayer url="https://tile.openstreetmap.org/{z}/{x}/{y}.png" attribution="&copy; <a href=\"https://www.openstreetmap.org/copyright\">OpenStreetMap</a> contributors"/>
-            <HotPolyline
-                positions=HotlinePositionVec::new(&[(40.2928, -105.6180, 56.54), (40.2928, -105.6190, 6.80), (40.2928, -105.6200, 96.52), (40.2918, -105.6210, 24.91)])
+            <HotPolylineResource
+                resource=track
+                fallback=|| view! { <div class="map-loading">"Loading track..."</div> }
                 palette=HotlinePalette::new(&[("green", 0.0), ("blue", 0.33), ("#ffff00", 0.67), ("red", 1.0)])
                 outline_color="#5a5a5a"
                 max=1.0
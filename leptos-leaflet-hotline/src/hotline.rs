@@ -1,14 +1,73 @@
 //! module for hotline wasm JS bindings, structs and functions
+#[cfg(any(feature = "fit", feature = "tcx"))]
+#[path = "./hotline_activity.rs"]
+pub mod hotline_activity;
+#[path = "./hotline_color.rs"]
+pub mod hotline_color;
+#[path = "./hotline_colormap.rs"]
+pub mod hotline_colormap;
+#[cfg(feature = "csv")]
+#[path = "./hotline_csv.rs"]
+pub mod hotline_csv;
+#[path = "./hotline_error.rs"]
+pub mod hotline_error;
+#[path = "./hotline_export.rs"]
+pub mod hotline_export;
+#[cfg(feature = "fit")]
+#[path = "./hotline_fit.rs"]
+pub mod hotline_fit;
+#[cfg(feature = "geojson")]
+#[path = "./hotline_geojson.rs"]
+pub mod hotline_geojson;
+#[path = "./hotline_geometry.rs"]
+pub mod hotline_geometry;
+#[cfg(feature = "gpx")]
+#[path = "./hotline_gpx.rs"]
+pub mod hotline_gpx;
+#[path = "./hotline_normalization.rs"]
+pub mod hotline_normalization;
+#[path = "./hotline_packed.rs"]
+pub mod hotline_packed;
 #[path = "./hotline_palette.rs"]
 pub mod hotline_palette;
+#[path = "./hotline_polyline.rs"]
+pub mod hotline_polyline;
 #[path = "./hotline_position.rs"]
 pub mod hotline_position;
+#[path = "./hotline_raster.rs"]
+pub mod hotline_raster;
+#[cfg(feature = "wasm")]
+#[path = "./hotline_renderer.rs"]
+pub mod hotline_renderer;
+#[cfg(any(feature = "gpx", feature = "fit", feature = "tcx"))]
+#[path = "./hotline_samples.rs"]
+mod hotline_samples;
+#[cfg(feature = "serde")]
+#[path = "./hotline_serde.rs"]
+mod hotline_serde;
+#[path = "./hotline_style.rs"]
+pub mod hotline_style;
+#[cfg(feature = "tcx")]
+#[path = "./hotline_tcx.rs"]
+pub mod hotline_tcx;
+#[cfg(any(feature = "gpx", feature = "tcx"))]
+#[path = "./hotline_time.rs"]
+mod hotline_time;
+#[path = "./hotline_track.rs"]
+pub mod hotline_track;
 
+#[cfg(feature = "wasm")]
 use js_sys::{Array, Object, Reflect};
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "wasm")]
 use leptos_leaflet::leaflet as L;
 
+#[cfg(feature = "wasm")]
+use hotline_error::HotlineError;
+
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 extern "C" {
 
@@ -49,13 +108,30 @@ extern "C" {
     #[wasm_bindgen(method, setter)]
     pub fn set_min(this: &HotlineOptions, min: &JsValue) -> HotlineOptions;
 
-    /// struct for binding to leaflet-hotline JS L::Hotline class
+    /// set the width of the outline drawn along both sides of the hotline
+    ///
+    /// # Returns
+    /// [`HotlineOptions`]
+    ///
+    #[wasm_bindgen(method, setter, js_name = "outlineWidth")]
+    pub fn set_outline_width(this: &HotlineOptions, width: f64) -> HotlineOptions;
+
+    /// set the map pane the hotline is drawn in
+    ///
+    /// # Returns
+    /// [`HotlineOptions`]
+    ///
+    #[wasm_bindgen(method, setter)]
+    pub fn set_pane(this: &HotlineOptions, pane: &str) -> HotlineOptions;
+
+    /// struct for binding to the JS `L.Hotline` class, defined natively by
+    /// [`hotline_renderer::register_hotline`] unless leaflet-hotline is loaded
     ///
     #[wasm_bindgen(extends = L::Polyline)]
     #[derive(Debug, Clone)]
     pub type Hotline;
 
-    /// construct a new [`Hotline`]
+    /// construct a new [`Hotline`]; `L.Hotline` must already be registered
     ///
     /// # Returns
     /// [`Hotline`]
@@ -63,13 +139,23 @@ extern "C" {
     #[wasm_bindgen(constructor, js_namespace=L)]
     pub fn new(hotline_data: &Array, opts: &JsValue) -> Hotline;
 
-    /// [`Hotline`] click tolerance
+    /// construct a new [`Hotline`], returning the exception leaflet throws, e.g. for
+    /// an invalid position or when `L.Hotline` is not registered
     ///
     /// # Returns
-    /// [`Object`]
+    /// [`Result<Hotline, JsValue>`]
+    ///
+    #[wasm_bindgen(catch, constructor, js_namespace=L, js_class = "Hotline")]
+    pub fn try_new(hotline_data: &Array, opts: &JsValue) -> Result<Hotline, JsValue>;
+
+    /// [`Hotline`] click tolerance, the pixel distance from the line that still
+    /// counts as a hit; pass it to `hit_test` on the positions
+    ///
+    /// # Returns
+    /// [`f64`]
     ///
     #[wasm_bindgen(method, js_name = "_clickTolerance")]
-    pub fn _click_tolerance(this: &Hotline) -> Object;
+    pub fn _click_tolerance(this: &Hotline) -> f64;
 
     /// get lat, lng bounds for [`Hotline`]
     ///
@@ -87,10 +173,15 @@ extern "C" {
     #[wasm_bindgen(method, js_name = "setStyle")]
     pub fn set_style(this: &Hotline, style: &Object) -> Object;
 
+    /// set style properties for [`Hotline`], returning the exception leaflet throws
+    #[wasm_bindgen(catch, method, js_name = "setStyle")]
+    fn try_set_style(this: &Hotline, style: &Object) -> Result<Object, JsValue>;
+
 }
 
 ///
 /// implement constructor and conversions of properties to [`JsValue`] for [`HotlineOptions`]
+#[cfg(feature = "wasm")]
 impl HotlineOptions {
     ///
     /// construct new [`HotlineOptions`]
@@ -102,47 +193,81 @@ impl HotlineOptions {
     #[inline]
     pub fn new(palette: &JsValue, outline_color: &JsValue, max: &JsValue, min: &JsValue) -> Self {
         let opts: Self = JsCast::unchecked_into(Object::new());
-        opts.set_palette(&palette);
-        opts.set_outline_color(&outline_color);
-        opts.set_max(&max);
-        opts.set_min(&min);
+        opts.set_palette(palette);
+        opts.set_outline_color(outline_color);
+        opts.set_max(max);
+        opts.set_min(min);
         opts
     }
 }
 
 ///
-/// implement functions to set outline color, set max breakpoint threshold,
-/// and set min breakpoint threshold for [`Hotline`]
+/// implement functions to set palette, outline color, max breakpoint threshold,
+/// min breakpoint threshold, line widths and simplification for [`Hotline`]
 ///
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 impl Hotline {
+    /// set a new palette for the hotline after it has already been created; \
+    /// expects the JS object produced for [`HotlineOptions`] (breakpoint -> color)
+    #[inline]
+    pub fn set_palette_val(&self, palette: &JsValue) -> Result<(), HotlineError> {
+        self.set_style_val("palette", palette)
+    }
+
     /// set a new outline color for the hotline after it has already been created; \
     /// creates JS object with outlineColor k,v pair and calls set_style on self
     #[inline]
-    pub fn set_outline_color_val(&self, color: &str) {
-        let obj = js_sys::Object::new();
-        Reflect::set(&obj, &"outlineColor".into(), &JsValue::from(color)).unwrap_or(true);
-
-        // Call the set_style method with the created object.
-        self.set_style(&obj);
+    pub fn set_outline_color_val(&self, color: &str) -> Result<(), HotlineError> {
+        self.set_style_val("outlineColor", &JsValue::from(color))
     }
 
     /// set the max breakpoint threshold for [`Hotline`]
     #[inline]
-    pub fn set_max_val(&self, max: f64) {
-        let obj = js_sys::Object::new();
-        Reflect::set(&obj, &"max".into(), &JsValue::from_f64(max)).unwrap_or(true);
-
-        self.set_style(&obj);
+    pub fn set_max_val(&self, max: f64) -> Result<(), HotlineError> {
+        self.set_style_val("max", &JsValue::from_f64(max))
     }
 
     /// set the min breakpoint threshold for [`Hotline`]
     #[inline]
-    pub fn set_min_val(&self, min: f64) {
+    pub fn set_min_val(&self, min: f64) -> Result<(), HotlineError> {
+        self.set_style_val("min", &JsValue::from_f64(min))
+    }
+
+    /// set the line weight (width in pixels) for [`Hotline`]
+    pub fn set_weight_val(&self, weight: f64) -> Result<(), HotlineError> {
+        self.set_style_val("weight", &JsValue::from_f64(weight))
+    }
+
+    /// set the outline width (in pixels, on each side of the line) for [`Hotline`]
+    pub fn set_outline_width_val(&self, width: f64) -> Result<(), HotlineError> {
+        self.set_style_val("outlineWidth", &JsValue::from_f64(width))
+    }
+
+    /// set the polyline simplification factor for [`Hotline`] and redraw it
+    pub fn set_smooth_factor_val(&self, smooth_factor: f64) -> Result<(), HotlineError> {
+        self.set_style_val("smoothFactor", &JsValue::from_f64(smooth_factor))?;
+        self.redraw();
+        Ok(())
+    }
+
+    /// enable or disable polyline clipping for [`Hotline`] and redraw it
+    pub fn set_no_clip_val(&self, no_clip: bool) -> Result<(), HotlineError> {
+        self.set_style_val("noClip", &JsValue::from_bool(no_clip))?;
+        self.redraw();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl Hotline {
+    /// creates a JS object with a single style k,v pair and calls setStyle on self
+    fn set_style_val(&self, key: &str, value: &JsValue) -> Result<(), HotlineError> {
         let obj = js_sys::Object::new();
-        Reflect::set(&obj, &"min".into(), &JsValue::from_f64(min)).unwrap_or(true);
+        Reflect::set(&obj, &key.into(), value)?;
 
-        self.set_style(&obj);
+        self.try_set_style(&obj)?;
+        Ok(())
     }
 }
 
@@ -150,6 +275,7 @@ impl Hotline {
 /// similar to the impl used for `From<Polyline>` for Layer in leptos-leaflet
 /// see: <https://github.com/headless-studio/leptos-leaflet/>
 /// specifically: <https://github.com/headless-studio/leptos-leaflet/blob/main/leaflet/src/shapes/polyline.rs/>
+#[cfg(feature = "wasm")]
 impl From<Hotline> for L::Layer {
     #[inline]
     fn from(value: Hotline) -> Self {
@@ -0,0 +1,185 @@
+//! module for building hotline tracks from the records of activity files (FIT, TCX)
+use super::hotline_normalization::Normalization;
+use super::hotline_position::{FlatPosition, HotlinePositionVec};
+use super::hotline_samples::{between_points, fill_gaps};
+use super::hotline_track::HotlineTrack;
+
+///
+/// value read from each activity record to color the hotline
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ActivityMetric {
+    /// speed in m/s, as recorded or computed from time and distance
+    #[default]
+    Speed,
+    /// power in watts
+    Power,
+    /// heart rate in beats per minute
+    HeartRate,
+    /// cadence in revolutions (or steps) per minute
+    Cadence,
+    /// altitude in meters
+    Altitude,
+    /// temperature in degrees Celsius
+    Temperature,
+}
+
+/// a positioned activity record with the optional data the metrics are read from
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct ActivityRecord {
+    pub(crate) position: FlatPosition,
+    pub(crate) time: Option<f64>,
+    pub(crate) speed: Option<f64>,
+    pub(crate) power: Option<f64>,
+    pub(crate) heart_rate: Option<f64>,
+    pub(crate) cadence: Option<f64>,
+    pub(crate) altitude: Option<f64>,
+    pub(crate) temperature: Option<f64>,
+}
+
+///
+/// build one hotline track per group of activity records (TCX track, FIT session),
+/// keeping the record times as timestamps
+///
+/// Records missing the metric take the value of the nearest earlier record (or the
+/// first later one).  `normalization` is resolved over the values of the whole
+/// activity, so the same value has the same color in every track.  Records without
+/// a time get a NaN timestamp.
+///
+/// # Returns
+/// [`Option<Vec<HotlineTrack>>`], `None` if a group has no record carrying the data
+/// needed for `metric`
+///
+pub(crate) fn activity_tracks(
+    groups: &[Vec<ActivityRecord>],
+    metric: ActivityMetric,
+    normalization: Normalization,
+) -> Option<Vec<HotlineTrack>> {
+    let values = groups
+        .iter()
+        .map(|records| fill_gaps(metric_values(records, metric)))
+        .collect::<Option<Vec<_>>>()?;
+    let all_values: Vec<f64> = values.iter().flatten().copied().collect();
+    let normalization = normalization.resolve(&all_values);
+
+    groups
+        .iter()
+        .zip(values)
+        .map(|(records, values)| activity_track(records, values, normalization))
+        .collect()
+}
+
+/// build the hotline track of one group of records from its metric values
+fn activity_track(
+    records: &[ActivityRecord],
+    values: Vec<f64>,
+    normalization: Normalization,
+) -> Option<HotlineTrack> {
+    let tuples: Vec<(f64, f64, f64)> = records
+        .iter()
+        .zip(values)
+        .map(|(record, value)| (record.position.lat, record.position.lng, value))
+        .collect();
+    let track = HotlineTrack::from(&HotlinePositionVec::with_normalization(
+        &tuples,
+        normalization,
+    ));
+
+    if records.iter().all(|record| record.time.is_none()) {
+        return Some(track);
+    }
+    let timestamps = records
+        .iter()
+        .map(|record| record.time.unwrap_or(f64::NAN))
+        .collect();
+    track.with_timestamps(timestamps).ok()
+}
+
+/// value of `metric` for each record, `None` where the record lacks it
+fn metric_values(records: &[ActivityRecord], metric: ActivityMetric) -> Vec<Option<f64>> {
+    let field = |read: fn(&ActivityRecord) -> Option<f64>| records.iter().map(read).collect();
+    match metric {
+        ActivityMetric::Speed if records.iter().any(|record| record.speed.is_some()) => {
+            field(|record| record.speed)
+        }
+        ActivityMetric::Speed => computed_speed(records),
+        ActivityMetric::Power => field(|record| record.power),
+        ActivityMetric::HeartRate => field(|record| record.heart_rate),
+        ActivityMetric::Cadence => field(|record| record.cadence),
+        ActivityMetric::Altitude => field(|record| record.altitude),
+        ActivityMetric::Temperature => field(|record| record.temperature),
+    }
+}
+
+/// speed from each record to the next in m/s, from record times and great-circle distance
+fn computed_speed(records: &[ActivityRecord]) -> Vec<Option<f64>> {
+    between_points(
+        records,
+        |record| record.position,
+        |from, to, distance| {
+            let seconds = to.time? - from.time?;
+            (seconds > 0.0).then(|| distance / seconds)
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(lng: f64, time: Option<f64>, power: Option<f64>) -> ActivityRecord {
+        ActivityRecord {
+            position: FlatPosition::new(0.0, lng),
+            time,
+            power,
+            ..ActivityRecord::default()
+        }
+    }
+
+    /// tracks of a single group of records, normalized as in [`HotlinePositionVec::new`]
+    fn single(records: &[ActivityRecord], metric: ActivityMetric) -> Option<HotlineTrack> {
+        activity_tracks(&[records.to_vec()], metric, Normalization::default())
+            .map(|mut tracks| tracks.remove(0))
+    }
+
+    #[test]
+    fn test_activity_track() {
+        let records = [
+            record(0.0, Some(100.0), None),
+            record(0.001, Some(110.0), Some(200.0)),
+            record(0.002, None, Some(250.0)),
+        ];
+        let track = single(&records, ActivityMetric::Power).unwrap();
+        assert_eq!(track.raw_values(), &[200.0, 200.0, 250.0]);
+        let timestamps = track.timestamps().unwrap();
+        assert_eq!(timestamps[..2], [100.0, 110.0]);
+        assert!(timestamps[2].is_nan());
+
+        let speed = single(&records[..2], ActivityMetric::Speed).unwrap();
+        assert!((speed.raw_values()[1] - 11.12).abs() < 0.01);
+
+        assert!(single(&records, ActivityMetric::Temperature).is_none());
+    }
+
+    #[test]
+    fn test_normalized_over_activity() {
+        let laps = [
+            vec![
+                record(0.0, None, Some(100.0)),
+                record(0.001, None, Some(200.0)),
+            ],
+            vec![
+                record(0.002, None, Some(300.0)),
+                record(0.003, None, Some(400.0)),
+            ],
+        ];
+        let tracks = activity_tracks(&laps, ActivityMetric::Power, Normalization::MinMax).unwrap();
+        assert_eq!(tracks[0].values(), &[0.0, 1.0 / 3.0]);
+        assert_eq!(tracks[1].values()[1], 1.0);
+        assert_eq!(tracks[0].raw_value_for(1.0), 400.0);
+
+        let missing = [laps[0].clone(), vec![record(0.004, None, None)]];
+        assert!(activity_tracks(&missing, ActivityMetric::Power, Normalization::MinMax).is_none());
+    }
+}
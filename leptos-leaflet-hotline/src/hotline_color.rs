@@ -0,0 +1,735 @@
+//! module for parsing and validating CSS colors used by hotline palettes and outlines
+use std::fmt;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// matches `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa` hex colors
+static HEX_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^#([0-9a-f]{3,4}|[0-9a-f]{6}|[0-9a-f]{8})$").expect("valid hex color regex")
+});
+
+/// matches `rgb()`, `rgba()`, `hsl()` and `hsla()` color functions, capturing name and arguments
+static FUNCTION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(rgba?|hsla?)\(\s*([^()]*?)\s*\)$").expect("valid color function regex")
+});
+
+/// CSS named colors (CSS Color Module Level 4), excluding `transparent`
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("aliceblue", [0xf0, 0xf8, 0xff]),
+    ("antiquewhite", [0xfa, 0xeb, 0xd7]),
+    ("aqua", [0x00, 0xff, 0xff]),
+    ("aquamarine", [0x7f, 0xff, 0xd4]),
+    ("azure", [0xf0, 0xff, 0xff]),
+    ("beige", [0xf5, 0xf5, 0xdc]),
+    ("bisque", [0xff, 0xe4, 0xc4]),
+    ("black", [0x00, 0x00, 0x00]),
+    ("blanchedalmond", [0xff, 0xeb, 0xcd]),
+    ("blue", [0x00, 0x00, 0xff]),
+    ("blueviolet", [0x8a, 0x2b, 0xe2]),
+    ("brown", [0xa5, 0x2a, 0x2a]),
+    ("burlywood", [0xde, 0xb8, 0x87]),
+    ("cadetblue", [0x5f, 0x9e, 0xa0]),
+    ("chartreuse", [0x7f, 0xff, 0x00]),
+    ("chocolate", [0xd2, 0x69, 0x1e]),
+    ("coral", [0xff, 0x7f, 0x50]),
+    ("cornflowerblue", [0x64, 0x95, 0xed]),
+    ("cornsilk", [0xff, 0xf8, 0xdc]),
+    ("crimson", [0xdc, 0x14, 0x3c]),
+    ("cyan", [0x00, 0xff, 0xff]),
+    ("darkblue", [0x00, 0x00, 0x8b]),
+    ("darkcyan", [0x00, 0x8b, 0x8b]),
+    ("darkgoldenrod", [0xb8, 0x86, 0x0b]),
+    ("darkgray", [0xa9, 0xa9, 0xa9]),
+    ("darkgreen", [0x00, 0x64, 0x00]),
+    ("darkgrey", [0xa9, 0xa9, 0xa9]),
+    ("darkkhaki", [0xbd, 0xb7, 0x6b]),
+    ("darkmagenta", [0x8b, 0x00, 0x8b]),
+    ("darkolivegreen", [0x55, 0x6b, 0x2f]),
+    ("darkorange", [0xff, 0x8c, 0x00]),
+    ("darkorchid", [0x99, 0x32, 0xcc]),
+    ("darkred", [0x8b, 0x00, 0x00]),
+    ("darksalmon", [0xe9, 0x96, 0x7a]),
+    ("darkseagreen", [0x8f, 0xbc, 0x8f]),
+    ("darkslateblue", [0x48, 0x3d, 0x8b]),
+    ("darkslategray", [0x2f, 0x4f, 0x4f]),
+    ("darkslategrey", [0x2f, 0x4f, 0x4f]),
+    ("darkturquoise", [0x00, 0xce, 0xd1]),
+    ("darkviolet", [0x94, 0x00, 0xd3]),
+    ("deeppink", [0xff, 0x14, 0x93]),
+    ("deepskyblue", [0x00, 0xbf, 0xff]),
+    ("dimgray", [0x69, 0x69, 0x69]),
+    ("dimgrey", [0x69, 0x69, 0x69]),
+    ("dodgerblue", [0x1e, 0x90, 0xff]),
+    ("firebrick", [0xb2, 0x22, 0x22]),
+    ("floralwhite", [0xff, 0xfa, 0xf0]),
+    ("forestgreen", [0x22, 0x8b, 0x22]),
+    ("fuchsia", [0xff, 0x00, 0xff]),
+    ("gainsboro", [0xdc, 0xdc, 0xdc]),
+    ("ghostwhite", [0xf8, 0xf8, 0xff]),
+    ("gold", [0xff, 0xd7, 0x00]),
+    ("goldenrod", [0xda, 0xa5, 0x20]),
+    ("gray", [0x80, 0x80, 0x80]),
+    ("green", [0x00, 0x80, 0x00]),
+    ("greenyellow", [0xad, 0xff, 0x2f]),
+    ("grey", [0x80, 0x80, 0x80]),
+    ("honeydew", [0xf0, 0xff, 0xf0]),
+    ("hotpink", [0xff, 0x69, 0xb4]),
+    ("indianred", [0xcd, 0x5c, 0x5c]),
+    ("indigo", [0x4b, 0x00, 0x82]),
+    ("ivory", [0xff, 0xff, 0xf0]),
+    ("khaki", [0xf0, 0xe6, 0x8c]),
+    ("lavender", [0xe6, 0xe6, 0xfa]),
+    ("lavenderblush", [0xff, 0xf0, 0xf5]),
+    ("lawngreen", [0x7c, 0xfc, 0x00]),
+    ("lemonchiffon", [0xff, 0xfa, 0xcd]),
+    ("lightblue", [0xad, 0xd8, 0xe6]),
+    ("lightcoral", [0xf0, 0x80, 0x80]),
+    ("lightcyan", [0xe0, 0xff, 0xff]),
+    ("lightgoldenrodyellow", [0xfa, 0xfa, 0xd2]),
+    ("lightgray", [0xd3, 0xd3, 0xd3]),
+    ("lightgreen", [0x90, 0xee, 0x90]),
+    ("lightgrey", [0xd3, 0xd3, 0xd3]),
+    ("lightpink", [0xff, 0xb6, 0xc1]),
+    ("lightsalmon", [0xff, 0xa0, 0x7a]),
+    ("lightseagreen", [0x20, 0xb2, 0xaa]),
+    ("lightskyblue", [0x87, 0xce, 0xfa]),
+    ("lightslategray", [0x77, 0x88, 0x99]),
+    ("lightslategrey", [0x77, 0x88, 0x99]),
+    ("lightsteelblue", [0xb0, 0xc4, 0xde]),
+    ("lightyellow", [0xff, 0xff, 0xe0]),
+    ("lime", [0x00, 0xff, 0x00]),
+    ("limegreen", [0x32, 0xcd, 0x32]),
+    ("linen", [0xfa, 0xf0, 0xe6]),
+    ("magenta", [0xff, 0x00, 0xff]),
+    ("maroon", [0x80, 0x00, 0x00]),
+    ("mediumaquamarine", [0x66, 0xcd, 0xaa]),
+    ("mediumblue", [0x00, 0x00, 0xcd]),
+    ("mediumorchid", [0xba, 0x55, 0xd3]),
+    ("mediumpurple", [0x93, 0x70, 0xdb]),
+    ("mediumseagreen", [0x3c, 0xb3, 0x71]),
+    ("mediumslateblue", [0x7b, 0x68, 0xee]),
+    ("mediumspringgreen", [0x00, 0xfa, 0x9a]),
+    ("mediumturquoise", [0x48, 0xd1, 0xcc]),
+    ("mediumvioletred", [0xc7, 0x15, 0x85]),
+    ("midnightblue", [0x19, 0x19, 0x70]),
+    ("mintcream", [0xf5, 0xff, 0xfa]),
+    ("mistyrose", [0xff, 0xe4, 0xe1]),
+    ("moccasin", [0xff, 0xe4, 0xb5]),
+    ("navajowhite", [0xff, 0xde, 0xad]),
+    ("navy", [0x00, 0x00, 0x80]),
+    ("oldlace", [0xfd, 0xf5, 0xe6]),
+    ("olive", [0x80, 0x80, 0x00]),
+    ("olivedrab", [0x6b, 0x8e, 0x23]),
+    ("orange", [0xff, 0xa5, 0x00]),
+    ("orangered", [0xff, 0x45, 0x00]),
+    ("orchid", [0xda, 0x70, 0xd6]),
+    ("palegoldenrod", [0xee, 0xe8, 0xaa]),
+    ("palegreen", [0x98, 0xfb, 0x98]),
+    ("paleturquoise", [0xaf, 0xee, 0xee]),
+    ("palevioletred", [0xdb, 0x70, 0x93]),
+    ("papayawhip", [0xff, 0xef, 0xd5]),
+    ("peachpuff", [0xff, 0xda, 0xb9]),
+    ("peru", [0xcd, 0x85, 0x3f]),
+    ("pink", [0xff, 0xc0, 0xcb]),
+    ("plum", [0xdd, 0xa0, 0xdd]),
+    ("powderblue", [0xb0, 0xe0, 0xe6]),
+    ("purple", [0x80, 0x00, 0x80]),
+    ("rebeccapurple", [0x66, 0x33, 0x99]),
+    ("red", [0xff, 0x00, 0x00]),
+    ("rosybrown", [0xbc, 0x8f, 0x8f]),
+    ("royalblue", [0x41, 0x69, 0xe1]),
+    ("saddlebrown", [0x8b, 0x45, 0x13]),
+    ("salmon", [0xfa, 0x80, 0x72]),
+    ("sandybrown", [0xf4, 0xa4, 0x60]),
+    ("seagreen", [0x2e, 0x8b, 0x57]),
+    ("seashell", [0xff, 0xf5, 0xee]),
+    ("sienna", [0xa0, 0x52, 0x2d]),
+    ("silver", [0xc0, 0xc0, 0xc0]),
+    ("skyblue", [0x87, 0xce, 0xeb]),
+    ("slateblue", [0x6a, 0x5a, 0xcd]),
+    ("slategray", [0x70, 0x80, 0x90]),
+    ("slategrey", [0x70, 0x80, 0x90]),
+    ("snow", [0xff, 0xfa, 0xfa]),
+    ("springgreen", [0x00, 0xff, 0x7f]),
+    ("steelblue", [0x46, 0x82, 0xb4]),
+    ("tan", [0xd2, 0xb4, 0x8c]),
+    ("teal", [0x00, 0x80, 0x80]),
+    ("thistle", [0xd8, 0xbf, 0xd8]),
+    ("tomato", [0xff, 0x63, 0x47]),
+    ("turquoise", [0x40, 0xe0, 0xd0]),
+    ("violet", [0xee, 0x82, 0xee]),
+    ("wheat", [0xf5, 0xde, 0xb3]),
+    ("white", [0xff, 0xff, 0xff]),
+    ("whitesmoke", [0xf5, 0xf5, 0xf5]),
+    ("yellow", [0xff, 0xff, 0x00]),
+    ("yellowgreen", [0x9a, 0xcd, 0x32]),
+];
+
+///
+/// struct data type for an RGBA color
+///
+/// # Fields
+/// * `r`, `g`, `b` [`u8`] red, green and blue channels
+/// * `a` [`u8`] alpha channel, 255 is opaque
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+///
+/// color space used to interpolate between two colors
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Interpolation {
+    /// gamma-encoded sRGB channels, as used by canvas gradients and leaflet-hotline
+    #[default]
+    Srgb,
+    /// physically linear RGB light intensities
+    LinearRgb,
+    /// hue, saturation and lightness, taking the shorter way around the hue circle
+    Hsl,
+    /// the perceptually uniform OKLab space
+    Oklab,
+}
+
+///
+/// error returned when a string is not a valid CSS color
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ColorError {
+    /// the color string is empty
+    Empty,
+    /// not a known CSS color name
+    UnknownName(String),
+    /// starts with `#` but is not a 3, 4, 6 or 8 digit hex color
+    InvalidHex(String),
+    /// an `rgb()`/`rgba()`/`hsl()`/`hsla()` function with invalid arguments
+    InvalidFunction(String),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "color is empty"),
+            Self::UnknownName(name) => write!(f, "'{name}' is not a recognized CSS color name"),
+            Self::InvalidHex(hex) => write!(
+                f,
+                "'{hex}' is not a valid hex color; expected #rgb, #rgba, #rrggbb or #rrggbbaa"
+            ),
+            Self::InvalidFunction(func) => write!(
+                f,
+                "'{func}' is not a valid rgb(), rgba(), hsl() or hsla() color"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ColorError {}
+
+/// implement constructors, parsing and formatting for [`Color`]
+impl Color {
+    /// opaque black, the default outline color
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+
+    /// opaque white
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+
+    ///
+    /// construct an opaque [`Color`]
+    ///
+    /// # Returns
+    /// [`Color`]
+    ///
+    #[must_use]
+    #[inline]
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    ///
+    /// construct a [`Color`] with an alpha channel
+    ///
+    /// # Returns
+    /// [`Color`]
+    ///
+    #[must_use]
+    #[inline]
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    ///
+    /// parse a CSS color: a named color, `transparent`, `#rgb`, `#rgba`, `#rrggbb`,
+    /// `#rrggbbaa`, `rgb()`/`rgba()` or `hsl()`/`hsla()` (comma or space separated)
+    ///
+    /// # Returns
+    /// [`Color`], or a [`ColorError`] describing why the string is not a color
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{Color, ColorError};
+    ///
+    /// assert_eq!(Color::parse("green"), Ok(Color::rgb(0, 128, 0)));
+    /// assert_eq!(Color::parse("#ff000080"), Ok(Color::rgba(255, 0, 0, 128)));
+    /// assert_eq!(Color::parse("hsl(120, 100%, 25%)"), Ok(Color::rgb(0, 128, 0)));
+    /// assert!(matches!(Color::parse("gren"), Err(ColorError::UnknownName(_))));
+    /// ```
+    ///
+    pub fn parse(color: &str) -> Result<Self, ColorError> {
+        let input = color.trim().to_ascii_lowercase();
+
+        if input.is_empty() {
+            return Err(ColorError::Empty);
+        }
+        if input.starts_with('#') {
+            return parse_hex(&input).ok_or_else(|| ColorError::InvalidHex(color.to_owned()));
+        }
+        if let Some(captures) = FUNCTION_RE.captures(&input) {
+            let parsed = match &captures[1] {
+                "rgb" | "rgba" => parse_rgb_args(&captures[2]),
+                _ => parse_hsl_args(&captures[2]),
+            };
+            return parsed.ok_or_else(|| ColorError::InvalidFunction(color.to_owned()));
+        }
+        if input.contains('(') {
+            return Err(ColorError::InvalidFunction(color.to_owned()));
+        }
+        if input == "transparent" {
+            return Ok(Self::rgba(0, 0, 0, 0));
+        }
+
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == input)
+            .map(|(_, [r, g, b])| Self::rgb(*r, *g, *b))
+            .ok_or_else(|| ColorError::UnknownName(color.to_owned()))
+    }
+
+    ///
+    /// blend towards `other` in sRGB, `t = 0` gives `self` and `t = 1` gives `other`
+    ///
+    /// # Returns
+    /// [`Color`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn mix(&self, other: &Self, t: f64) -> Self {
+        self.mix_in(other, t, Interpolation::Srgb)
+    }
+
+    ///
+    /// blend towards `other` in the given color space, `t = 0` gives `self` and
+    /// `t = 1` gives `other`; alpha is always blended linearly
+    ///
+    /// # Returns
+    /// [`Color`]
+    ///
+    #[must_use]
+    pub fn mix_in(&self, other: &Self, t: f64, space: Interpolation) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        let alpha = to_channel(lerp(f64::from(self.a), f64::from(other.a)));
+
+        let (r, g, b) = match space {
+            Interpolation::Srgb => {
+                let [r1, g1, b1] = self.unit_rgb();
+                let [r2, g2, b2] = other.unit_rgb();
+                (lerp(r1, r2), lerp(g1, g2), lerp(b1, b2))
+            }
+            Interpolation::LinearRgb => {
+                let [r1, g1, b1] = self.unit_rgb().map(srgb_to_linear);
+                let [r2, g2, b2] = other.unit_rgb().map(srgb_to_linear);
+                (
+                    linear_to_srgb(lerp(r1, r2)),
+                    linear_to_srgb(lerp(g1, g2)),
+                    linear_to_srgb(lerp(b1, b2)),
+                )
+            }
+            Interpolation::Hsl => {
+                let (h1, s1, l1) = rgb_to_hsl(self.unit_rgb());
+                let (h2, s2, l2) = rgb_to_hsl(other.unit_rgb());
+                // take the shorter way around the hue circle
+                let mut delta = h2 - h1;
+                if delta > 180.0 {
+                    delta -= 360.0;
+                } else if delta < -180.0 {
+                    delta += 360.0;
+                }
+                let hue = (h1 + delta * t).rem_euclid(360.0);
+                hsl_to_rgb(hue, lerp(s1, s2), lerp(l1, l2))
+            }
+            Interpolation::Oklab => {
+                let [l1, a1, b1] = linear_rgb_to_oklab(self.unit_rgb().map(srgb_to_linear));
+                let [l2, a2, b2] = linear_rgb_to_oklab(other.unit_rgb().map(srgb_to_linear));
+                let [r, g, b] = oklab_to_linear_rgb([lerp(l1, l2), lerp(a1, a2), lerp(b1, b2)])
+                    .map(linear_to_srgb);
+                (r, g, b)
+            }
+        };
+
+        Self::rgba(
+            to_channel(r * 255.0),
+            to_channel(g * 255.0),
+            to_channel(b * 255.0),
+            alpha,
+        )
+    }
+
+    /// red, green and blue channels scaled to 0..=1
+    #[inline]
+    fn unit_rgb(&self) -> [f64; 3] {
+        [self.r, self.g, self.b].map(|c| f64::from(c) / 255.0)
+    }
+
+    ///
+    /// CSS representation of the color, `#rrggbb` when opaque and `#rrggbbaa` otherwise
+    ///
+    /// # Returns
+    /// [`String`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn to_css(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a < 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<&str> for Color {
+    type Error = ColorError;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::parse(value)
+    }
+}
+
+/// parse a lowercase `#` hex color
+fn parse_hex(input: &str) -> Option<Color> {
+    if !HEX_RE.is_match(input) {
+        return None;
+    }
+    let digits = &input[1..];
+    let channel = |i: usize, width: usize| -> Option<u8> {
+        let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).ok()?;
+        // expand short form digits, e.g. `f` -> `ff`
+        Some(if width == 1 { value * 17 } else { value })
+    };
+
+    match digits.len() {
+        3 | 4 => Some(Color::rgba(
+            channel(0, 1)?,
+            channel(1, 1)?,
+            channel(2, 1)?,
+            if digits.len() == 4 {
+                channel(3, 1)?
+            } else {
+                255
+            },
+        )),
+        _ => Some(Color::rgba(
+            channel(0, 2)?,
+            channel(1, 2)?,
+            channel(2, 2)?,
+            if digits.len() == 8 {
+                channel(3, 2)?
+            } else {
+                255
+            },
+        )),
+    }
+}
+
+/// split color function arguments given either as `a, b, c[, alpha]` or `a b c[ / alpha]`
+fn split_args(args: &str) -> Option<(Vec<&str>, Option<&str>)> {
+    let mut parts: Vec<&str> = if args.contains(',') {
+        args.split(',').map(str::trim).collect()
+    } else {
+        let (channels, alpha) = match args.split_once('/') {
+            Some((channels, alpha)) => (channels, Some(alpha.trim())),
+            None => (args, None),
+        };
+        let mut parts: Vec<&str> = channels.split_whitespace().collect();
+        parts.extend(alpha);
+        parts
+    };
+
+    match parts.len() {
+        3 => Some((parts, None)),
+        4 => {
+            let alpha = parts.pop();
+            Some((parts, alpha))
+        }
+        _ => None,
+    }
+}
+
+/// parse a number or percentage, mapping 100% to `scale`
+fn parse_number_or_percent(arg: &str, scale: f64) -> Option<f64> {
+    let value = match arg.strip_suffix('%') {
+        Some(pct) => pct.trim().parse::<f64>().ok()? * scale / 100.0,
+        None => arg.parse::<f64>().ok()?,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// parse an alpha value given as 0..=1 or a percentage
+fn parse_alpha(arg: Option<&str>) -> Option<u8> {
+    match arg {
+        Some(arg) => Some(to_channel(parse_number_or_percent(arg, 1.0)? * 255.0)),
+        None => Some(255),
+    }
+}
+
+/// round and clamp a value to a color channel
+#[inline]
+fn to_channel(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+/// parse the arguments of `rgb()`/`rgba()`
+fn parse_rgb_args(args: &str) -> Option<Color> {
+    let (channels, alpha) = split_args(args)?;
+    let channel = |arg: &str| parse_number_or_percent(arg, 255.0).map(to_channel);
+
+    Some(Color::rgba(
+        channel(channels[0])?,
+        channel(channels[1])?,
+        channel(channels[2])?,
+        parse_alpha(alpha)?,
+    ))
+}
+
+/// parse the arguments of `hsl()`/`hsla()`
+fn parse_hsl_args(args: &str) -> Option<Color> {
+    let (channels, alpha) = split_args(args)?;
+    let hue = parse_hue(channels[0])?;
+    let saturation = parse_number_or_percent(channels[1], 100.0)?.clamp(0.0, 100.0) / 100.0;
+    let lightness = parse_number_or_percent(channels[2], 100.0)?.clamp(0.0, 100.0) / 100.0;
+
+    let (r, g, b) = hsl_to_rgb(hue, saturation, lightness);
+    Some(Color::rgba(
+        to_channel(r * 255.0),
+        to_channel(g * 255.0),
+        to_channel(b * 255.0),
+        parse_alpha(alpha)?,
+    ))
+}
+
+/// parse a hue angle in degrees, accepting `deg`, `grad`, `rad` and `turn` units
+fn parse_hue(arg: &str) -> Option<f64> {
+    let (value, to_degrees) = if let Some(v) = arg.strip_suffix("deg") {
+        (v, 1.0)
+    } else if let Some(v) = arg.strip_suffix("grad") {
+        (v, 0.9)
+    } else if let Some(v) = arg.strip_suffix("rad") {
+        (v, 180.0 / std::f64::consts::PI)
+    } else if let Some(v) = arg.strip_suffix("turn") {
+        (v, 360.0)
+    } else {
+        (arg, 1.0)
+    };
+    let degrees = value.trim().parse::<f64>().ok()? * to_degrees;
+    degrees.is_finite().then(|| degrees.rem_euclid(360.0))
+}
+
+/// convert hue (degrees), saturation and lightness (0..=1) to r, g, b in 0..=1
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (f64, f64, f64) {
+    let channel = |n: f64| {
+        let k = (n + hue / 30.0) % 12.0;
+        let a = saturation * lightness.min(1.0 - lightness);
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    (channel(0.0), channel(8.0), channel(4.0))
+}
+
+/// convert r, g, b in 0..=1 to hue (degrees), saturation and lightness (0..=1)
+fn rgb_to_hsl([r, g, b]: [f64; 3]) -> (f64, f64, f64) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let chroma = max - min;
+
+    if chroma == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+
+    let saturation = chroma / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+/// decode a gamma-encoded sRGB channel (0..=1) to linear light
+#[inline]
+fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// encode a linear light channel (0..=1) as sRGB
+#[inline]
+fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// convert linear RGB to OKLab, see <https://bottosson.github.io/posts/oklab/>
+fn linear_rgb_to_oklab([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+    let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+    let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+    [
+        0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+    ]
+}
+
+/// convert OKLab to linear RGB, see <https://bottosson.github.io/posts/oklab/>
+fn oklab_to_linear_rgb([l, a, b]: [f64; 3]) -> [f64; 3] {
+    let l_ = (l + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m_ = (l - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s_ = (l - 0.089_484_177_5 * a - 1.291_485_548_0 * b).powi(3);
+    [
+        4.076_741_662_1 * l_ - 3.307_711_591_3 * m_ + 0.230_969_929_2 * s_,
+        -1.268_438_004_6 * l_ + 2.609_757_401_1 * m_ - 0.341_319_396_5 * s_,
+        -0.004_196_086_3 * l_ - 0.703_418_614_7 * m_ + 1.707_614_701_0 * s_,
+    ]
+    .map(|c| c.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_colors() {
+        assert_eq!(Color::parse("Red"), Ok(Color::rgb(255, 0, 0)));
+        assert_eq!(
+            Color::parse(" rebeccapurple "),
+            Ok(Color::rgb(102, 51, 153))
+        );
+        assert_eq!(Color::parse("transparent"), Ok(Color::rgba(0, 0, 0, 0)));
+        assert_eq!(
+            Color::parse("gren"),
+            Err(ColorError::UnknownName("gren".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_hex_colors() {
+        assert_eq!(Color::parse("#ffff00"), Ok(Color::rgb(255, 255, 0)));
+        assert_eq!(Color::parse("#0F0"), Ok(Color::rgb(0, 255, 0)));
+        assert_eq!(Color::parse("#0f08"), Ok(Color::rgba(0, 255, 0, 136)));
+        assert_eq!(
+            Color::parse("#12345"),
+            Err(ColorError::InvalidHex("#12345".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_rgb_functions() {
+        assert_eq!(
+            Color::parse("rgb(255, 128, 0)"),
+            Ok(Color::rgb(255, 128, 0))
+        );
+        assert_eq!(
+            Color::parse("rgba(255, 0, 0, 0.5)"),
+            Ok(Color::rgba(255, 0, 0, 128))
+        );
+        assert_eq!(
+            Color::parse("rgb(100% 0% 0% / 25%)"),
+            Ok(Color::rgba(255, 0, 0, 64))
+        );
+        assert!(matches!(
+            Color::parse("rgb(255, 0)"),
+            Err(ColorError::InvalidFunction(_))
+        ));
+    }
+
+    #[test]
+    fn test_hsl_functions() {
+        assert_eq!(Color::parse("hsl(0, 100%, 50%)"), Ok(Color::rgb(255, 0, 0)));
+        assert_eq!(
+            Color::parse("hsl(240deg 100% 50%)"),
+            Ok(Color::rgb(0, 0, 255))
+        );
+        assert_eq!(
+            Color::parse("hsla(0.5turn, 100%, 50%, 0)"),
+            Ok(Color::rgba(0, 255, 255, 0))
+        );
+    }
+
+    #[test]
+    fn test_mix_in_spaces() {
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+        assert_eq!(
+            red.mix_in(&blue, 0.5, Interpolation::Srgb),
+            Color::rgb(128, 0, 128)
+        );
+        assert_eq!(
+            red.mix_in(&blue, 0.5, Interpolation::LinearRgb),
+            Color::rgb(188, 0, 188)
+        );
+        assert_eq!(
+            red.mix_in(&blue, 0.5, Interpolation::Hsl),
+            Color::rgb(255, 0, 255)
+        );
+        for space in [
+            Interpolation::Srgb,
+            Interpolation::LinearRgb,
+            Interpolation::Hsl,
+            Interpolation::Oklab,
+        ] {
+            assert_eq!(red.mix_in(&blue, 0.0, space), red, "{space:?}");
+            assert_eq!(red.mix_in(&blue, 1.0, space), blue, "{space:?}");
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for css in ["#5a5a5a", "#ff000080"] {
+            assert_eq!(Color::parse(css).unwrap().to_string(), css);
+        }
+    }
+}
@@ -0,0 +1,457 @@
+//! module for built-in scientific and ColorBrewer colormaps for hotline palettes
+use std::fmt;
+
+use super::hotline_color::Color;
+use super::hotline_palette::{ColorStop, HotlinePalette};
+
+/// viridis, evenly spaced samples from dark purple to yellow
+const VIRIDIS: &[u32] = &[
+    0x440154, 0x482878, 0x3e4a89, 0x31688e, 0x26828e, 0x1f9e89, 0x35b779, 0x6dcd59, 0xb4de2c,
+    0xfde725,
+];
+
+/// magma, evenly spaced samples from black to light yellow
+const MAGMA: &[u32] = &[
+    0x000004, 0x180f3e, 0x451077, 0x721f81, 0x9f2f7f, 0xcd4071, 0xf1605d, 0xfd9567, 0xfec98d,
+    0xfcfdbf,
+];
+
+/// inferno, evenly spaced samples from black to pale yellow
+const INFERNO: &[u32] = &[
+    0x000004, 0x1b0c42, 0x4b0c6b, 0x781c6d, 0xa52c60, 0xcf4446, 0xed6925, 0xfb9a06, 0xf7d03c,
+    0xfcffa4,
+];
+
+/// plasma, evenly spaced samples from blue to yellow
+const PLASMA: &[u32] = &[
+    0x0d0887, 0x47039f, 0x7301a8, 0x9c179e, 0xbd3786, 0xd8576b, 0xed7953, 0xfa9e3b, 0xfdc926,
+    0xf0f921,
+];
+
+/// cividis, evenly spaced samples from navy to yellow, designed for color vision deficiency
+const CIVIDIS: &[u32] = &[
+    0x00204d, 0x00336f, 0x39486b, 0x575c6d, 0x707173, 0x8a8779, 0xa69d75, 0xc4b56c, 0xe4cf5b,
+    0xffea46,
+];
+
+/// turbo, evenly spaced samples of the improved rainbow colormap
+const TURBO: &[u32] = &[
+    0x30123b, 0x4662d7, 0x36aaf9, 0x1ae4b6, 0x72fe5e, 0xc7ef34, 0xfaba39, 0xf66b19, 0xcb2a04,
+    0x7a0403,
+];
+
+/// ColorBrewer RdYlGn, 11 classes
+const RD_YL_GN: &[u32] = &[
+    0xa50026, 0xd73027, 0xf46d43, 0xfdae61, 0xfee08b, 0xffffbf, 0xd9ef8b, 0xa6d96a, 0x66bd63,
+    0x1a9850, 0x006837,
+];
+
+/// ColorBrewer Spectral, 11 classes
+const SPECTRAL: &[u32] = &[
+    0x9e0142, 0xd53e4f, 0xf46d43, 0xfdae61, 0xfee08b, 0xffffbf, 0xe6f598, 0xabdda4, 0x66c2a5,
+    0x3288bd, 0x5e4fa2,
+];
+
+/// ColorBrewer RdBu, 11 classes
+const RD_BU: &[u32] = &[
+    0x67001f, 0xb2182b, 0xd6604d, 0xf4a582, 0xfddbc7, 0xf7f7f7, 0xd1e5f0, 0x92c5de, 0x4393c3,
+    0x2166ac, 0x053061,
+];
+
+/// ColorBrewer RdYlBu, 11 classes
+const RD_YL_BU: &[u32] = &[
+    0xa50026, 0xd73027, 0xf46d43, 0xfdae61, 0xfee090, 0xffffbf, 0xe0f3f8, 0xabd9e9, 0x74add1,
+    0x4575b4, 0x313695,
+];
+
+/// ColorBrewer PuOr, 11 classes
+const PU_OR: &[u32] = &[
+    0x7f3b08, 0xb35806, 0xe08214, 0xfdb863, 0xfee0b6, 0xf7f7f7, 0xd8daeb, 0xb2abd2, 0x8073ac,
+    0x542788, 0x2d004b,
+];
+
+/// ColorBrewer BrBG, 11 classes
+const BR_BG: &[u32] = &[
+    0x543005, 0x8c510a, 0xbf812d, 0xdfc27d, 0xf6e8c3, 0xf5f5f5, 0xc7eae5, 0x80cdc1, 0x35978f,
+    0x01665e, 0x003c30,
+];
+
+/// ColorBrewer Blues, 9 classes
+const BLUES: &[u32] = &[
+    0xf7fbff, 0xdeebf7, 0xc6dbef, 0x9ecae1, 0x6baed6, 0x4292c6, 0x2171b5, 0x08519c, 0x08306b,
+];
+
+/// ColorBrewer Greens, 9 classes
+const GREENS: &[u32] = &[
+    0xf7fcf5, 0xe5f5e0, 0xc7e9c0, 0xa1d99b, 0x74c476, 0x41ab5d, 0x238b45, 0x006d2c, 0x00441b,
+];
+
+/// ColorBrewer Reds, 9 classes
+const REDS: &[u32] = &[
+    0xfff5f0, 0xfee0d2, 0xfcbba1, 0xfc9272, 0xfb6a4a, 0xef3b2c, 0xcb181d, 0xa50f15, 0x67000d,
+];
+
+/// ColorBrewer Oranges, 9 classes
+const ORANGES: &[u32] = &[
+    0xfff5eb, 0xfee6ce, 0xfdd0a2, 0xfdae6b, 0xfd8d3c, 0xf16913, 0xd94801, 0xa63603, 0x7f2704,
+];
+
+/// ColorBrewer Purples, 9 classes
+const PURPLES: &[u32] = &[
+    0xfcfbfd, 0xefedf5, 0xdadaeb, 0xbcbddc, 0x9e9ac8, 0x807dba, 0x6a51a3, 0x54278f, 0x3f007d,
+];
+
+/// ColorBrewer Greys, 9 classes
+const GREYS: &[u32] = &[
+    0xffffff, 0xf0f0f0, 0xd9d9d9, 0xbdbdbd, 0x969696, 0x737373, 0x525252, 0x252525, 0x000000,
+];
+
+/// ColorBrewer YlOrRd, 9 classes
+const YL_OR_RD: &[u32] = &[
+    0xffffcc, 0xffeda0, 0xfed976, 0xfeb24c, 0xfd8d3c, 0xfc4e2a, 0xe31a1c, 0xbd0026, 0x800026,
+];
+
+/// ColorBrewer YlGnBu, 9 classes
+const YL_GN_BU: &[u32] = &[
+    0xffffd9, 0xedf8b1, 0xc7e9b4, 0x7fcdbb, 0x41b6c4, 0x1d91c0, 0x225ea8, 0x253494, 0x081d58,
+];
+
+///
+/// whether a colormap runs from low to high or diverges from a neutral midpoint
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColormapKind {
+    /// perceptually ordered from low to high values
+    Sequential,
+    /// two sequential ramps meeting at a neutral midpoint
+    Diverging,
+}
+
+///
+/// built-in colormaps that can be turned into a [`HotlinePalette`]
+///
+/// The scientific colormaps (viridis, magma, inferno, plasma, cividis) are
+/// perceptually uniform; cividis is additionally optimized for color vision
+/// deficiency.  The ColorBrewer schemes are by Cynthia Brewer, see
+/// <https://colorbrewer2.org>.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Colormap {
+    /// perceptually uniform, dark purple to yellow
+    Viridis,
+    /// perceptually uniform, black to light yellow
+    Magma,
+    /// perceptually uniform, black to pale yellow
+    Inferno,
+    /// perceptually uniform, blue to yellow
+    Plasma,
+    /// perceptually uniform and color-blind safe, navy to yellow
+    Cividis,
+    /// improved rainbow, dark blue to dark red
+    Turbo,
+    /// diverging red, yellow, green
+    RdYlGn,
+    /// diverging red, yellow, blue through orange and green
+    Spectral,
+    /// diverging red, white, blue
+    RdBu,
+    /// diverging red, yellow, blue
+    RdYlBu,
+    /// diverging orange, white, purple
+    PuOr,
+    /// diverging brown, white, blue-green
+    BrBG,
+    /// sequential white to dark blue
+    Blues,
+    /// sequential white to dark green
+    Greens,
+    /// sequential white to dark red
+    Reds,
+    /// sequential white to dark orange
+    Oranges,
+    /// sequential white to dark purple
+    Purples,
+    /// sequential white to black
+    Greys,
+    /// sequential yellow, orange, red
+    YlOrRd,
+    /// sequential yellow, green, blue
+    YlGnBu,
+}
+
+/// implement lookups for [`Colormap`]
+impl Colormap {
+    /// every built-in colormap
+    pub const ALL: &'static [Self] = &[
+        Self::Viridis,
+        Self::Magma,
+        Self::Inferno,
+        Self::Plasma,
+        Self::Cividis,
+        Self::Turbo,
+        Self::RdYlGn,
+        Self::Spectral,
+        Self::RdBu,
+        Self::RdYlBu,
+        Self::PuOr,
+        Self::BrBG,
+        Self::Blues,
+        Self::Greens,
+        Self::Reds,
+        Self::Oranges,
+        Self::Purples,
+        Self::Greys,
+        Self::YlOrRd,
+        Self::YlGnBu,
+    ];
+
+    /// conventional name of the colormap, e.g. `"viridis"` or `"RdYlGn"`
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Viridis => "viridis",
+            Self::Magma => "magma",
+            Self::Inferno => "inferno",
+            Self::Plasma => "plasma",
+            Self::Cividis => "cividis",
+            Self::Turbo => "turbo",
+            Self::RdYlGn => "RdYlGn",
+            Self::Spectral => "Spectral",
+            Self::RdBu => "RdBu",
+            Self::RdYlBu => "RdYlBu",
+            Self::PuOr => "PuOr",
+            Self::BrBG => "BrBG",
+            Self::Blues => "Blues",
+            Self::Greens => "Greens",
+            Self::Reds => "Reds",
+            Self::Oranges => "Oranges",
+            Self::Purples => "Purples",
+            Self::Greys => "Greys",
+            Self::YlOrRd => "YlOrRd",
+            Self::YlGnBu => "YlGnBu",
+        }
+    }
+
+    /// whether the colormap is sequential or diverging
+    #[must_use]
+    pub const fn kind(self) -> ColormapKind {
+        match self {
+            Self::RdYlGn | Self::Spectral | Self::RdBu | Self::RdYlBu | Self::PuOr | Self::BrBG => {
+                ColormapKind::Diverging
+            }
+            _ => ColormapKind::Sequential,
+        }
+    }
+
+    /// evenly spaced anchor colors the palette is sampled from
+    #[must_use]
+    const fn anchors(self) -> &'static [u32] {
+        match self {
+            Self::Viridis => VIRIDIS,
+            Self::Magma => MAGMA,
+            Self::Inferno => INFERNO,
+            Self::Plasma => PLASMA,
+            Self::Cividis => CIVIDIS,
+            Self::Turbo => TURBO,
+            Self::RdYlGn => RD_YL_GN,
+            Self::Spectral => SPECTRAL,
+            Self::RdBu => RD_BU,
+            Self::RdYlBu => RD_YL_BU,
+            Self::PuOr => PU_OR,
+            Self::BrBG => BR_BG,
+            Self::Blues => BLUES,
+            Self::Greens => GREENS,
+            Self::Reds => REDS,
+            Self::Oranges => ORANGES,
+            Self::Purples => PURPLES,
+            Self::Greys => GREYS,
+            Self::YlOrRd => YL_OR_RD,
+            Self::YlGnBu => YL_GN_BU,
+        }
+    }
+
+    /// number of anchor colors, used as the default number of palette stops
+    #[must_use]
+    pub const fn default_stops(self) -> usize {
+        self.anchors().len()
+    }
+
+    ///
+    /// color of the colormap at `t` in 0..=1, interpolating between anchors
+    ///
+    /// # Returns
+    /// [`Color`]
+    ///
+    #[must_use]
+    pub fn sample(self, t: f64) -> Color {
+        let anchors = self.anchors();
+        let pos = t.clamp(0.0, 1.0) * (anchors.len() - 1) as f64;
+        let below = pos.floor() as usize;
+        let above = pos.ceil() as usize;
+        hex_to_color(anchors[below]).mix(&hex_to_color(anchors[above]), pos - below as f64)
+    }
+
+    ///
+    /// sample the colormap into a [`HotlinePalette`] with evenly spaced breakpoints
+    ///
+    /// # Returns
+    /// [`HotlinePalette`] with `stops` color stops (at least 2)
+    ///
+    #[must_use]
+    pub fn palette(self, stops: usize) -> HotlinePalette {
+        let stops = stops.max(2);
+        let last = (stops - 1) as f64;
+        let color_stops = (0..stops).map(|i| {
+            let t = i as f64 / last;
+            ColorStop::new(self.sample(t), t)
+        });
+
+        HotlinePalette::from_stops(color_stops).expect("evenly spaced breakpoints in 0..=1")
+    }
+}
+
+impl fmt::Display for Colormap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// convert a `0xrrggbb` constant to an opaque [`Color`]
+#[inline]
+const fn hex_to_color(hex: u32) -> Color {
+    Color::rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+}
+
+/// implement built-in colormap constructors for [`HotlinePalette`]
+impl HotlinePalette {
+    ///
+    /// construct a [`HotlinePalette`] from a built-in colormap
+    ///
+    /// # Returns
+    /// [`HotlinePalette`] with `stops` evenly spaced color stops (at least 2)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{Colormap, HotlinePalette};
+    ///
+    /// let palette = HotlinePalette::colormap(Colormap::RdYlGn, 5).reversed();
+    /// assert_eq!(palette.stops().len(), 5);
+    /// ```
+    ///
+    #[must_use]
+    #[inline]
+    pub fn colormap(colormap: Colormap, stops: usize) -> Self {
+        colormap.palette(stops)
+    }
+
+    /// viridis: perceptually uniform, dark purple to yellow
+    #[must_use]
+    #[inline]
+    pub fn viridis() -> Self {
+        Self::colormap(Colormap::Viridis, Colormap::Viridis.default_stops())
+    }
+
+    /// magma: perceptually uniform, black to light yellow
+    #[must_use]
+    #[inline]
+    pub fn magma() -> Self {
+        Self::colormap(Colormap::Magma, Colormap::Magma.default_stops())
+    }
+
+    /// inferno: perceptually uniform, black to pale yellow
+    #[must_use]
+    #[inline]
+    pub fn inferno() -> Self {
+        Self::colormap(Colormap::Inferno, Colormap::Inferno.default_stops())
+    }
+
+    /// plasma: perceptually uniform, blue to yellow
+    #[must_use]
+    #[inline]
+    pub fn plasma() -> Self {
+        Self::colormap(Colormap::Plasma, Colormap::Plasma.default_stops())
+    }
+
+    /// cividis: perceptually uniform and color-blind safe, navy to yellow
+    #[must_use]
+    #[inline]
+    pub fn cividis() -> Self {
+        Self::colormap(Colormap::Cividis, Colormap::Cividis.default_stops())
+    }
+
+    /// turbo: improved rainbow, dark blue to dark red
+    #[must_use]
+    #[inline]
+    pub fn turbo() -> Self {
+        Self::colormap(Colormap::Turbo, Colormap::Turbo.default_stops())
+    }
+
+    ///
+    /// the same palette running in the opposite direction
+    ///
+    /// Breakpoints that round to the same `1 - b` (e.g. 0 and 1e-17) keep only
+    /// the stop that was nearest the start of the original palette.
+    ///
+    /// # Returns
+    /// [`HotlinePalette`] with each breakpoint `b` moved to `1 - b`
+    ///
+    #[must_use]
+    pub fn reversed(&self) -> Self {
+        let mut stops: Vec<ColorStop> = Vec::with_capacity(self.stops().len());
+        for stop in self.stops().iter().rev() {
+            let mirrored = ColorStop::new(stop.color, 1.0 - stop.breakpoint);
+            match stops.last_mut() {
+                Some(last) if last.breakpoint == mirrored.breakpoint => *last = mirrored,
+                _ => stops.push(mirrored),
+            }
+        }
+
+        // mirrored breakpoints stay in 0..=1 and are deduplicated above
+        Self::from_stops(stops).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colormap_endpoints() {
+        for colormap in Colormap::ALL {
+            let palette = HotlinePalette::colormap(*colormap, 7);
+            let stops = palette.stops();
+            assert_eq!(stops.len(), 7, "{colormap}");
+            assert_eq!(stops[0].color, hex_to_color(colormap.anchors()[0]));
+            assert_eq!(
+                stops[6].color,
+                hex_to_color(*colormap.anchors().last().unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn test_reversed() {
+        let palette = HotlinePalette::viridis();
+        let reversed = palette.reversed();
+        assert_eq!(reversed.stops()[0].color, Color::rgb(0xfd, 0xe7, 0x25));
+        let colors = |p: &HotlinePalette| p.stops().iter().map(|s| s.color).collect::<Vec<_>>();
+        assert_eq!(colors(&reversed.reversed()), colors(&palette));
+    }
+
+    #[test]
+    fn test_reversed_collapsed_breakpoints() {
+        let palette = HotlinePalette::from_stops([
+            ColorStop::new(Color::rgb(255, 0, 0), 0.0),
+            ColorStop::new(Color::rgb(0, 255, 0), 1e-17),
+            ColorStop::new(Color::rgb(0, 0, 255), 1.0),
+        ])
+        .unwrap();
+        let reversed = palette.reversed();
+        assert_eq!(reversed.stops().len(), 2);
+        assert_eq!(reversed.stops()[0].color, Color::rgb(0, 0, 255));
+        assert_eq!(reversed.stops()[1].color, Color::rgb(255, 0, 0));
+        assert_eq!(reversed.stops()[1].breakpoint, 1.0);
+    }
+}
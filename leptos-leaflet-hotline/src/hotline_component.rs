@@ -0,0 +1,1097 @@
+//! module for the hot polyline functional component
+use js_sys::{Array, JsString, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use leptos::children::Children;
+use leptos::logging::log;
+use leptos::prelude::*;
+use leptos::*;
+use leptos_leaflet::leaflet as L;
+use leptos_leaflet::prelude::*;
+
+use crate::hotline::hotline_track::to_hotline_lat_lng_array;
+use crate::hotline_events::{HotlineListeners, HotlineVertex};
+use crate::{
+    register_hotline, Color, ColorError, FlatPosition, Hotline, HotlineError, HotlineEvents,
+    HotlineOptions, HotlinePalette, HotlinePositionVec, HotlineTrack, DEFAULT_OUTLINE_WIDTH,
+    DEFAULT_WEIGHT,
+};
+
+pub struct HotlinePositions(HotlineTrack);
+
+impl HotlinePositions {
+    pub fn hotline_lat_lngs(&self) -> Array {
+        to_hotline_lat_lng_array(&self.0)
+    }
+}
+
+/// allows passing a [`HotlinePositionVec`] directly as the `positions` prop of [`HotPolyline`]
+impl From<HotlinePositionVec> for Signal<HotlineTrack> {
+    #[inline]
+    fn from(value: HotlinePositionVec) -> Self {
+        Signal::stored(value.into())
+    }
+}
+
+pub struct HotlineOutlineColor(Color);
+
+impl HotlineOutlineColor {
+    pub fn outline_color(&self) -> JsValue {
+        Self::outline_color_to_js(&self.0)
+    }
+
+    pub fn color(&self) -> Color {
+        self.0
+    }
+
+    ///
+    /// Converts hotline outline color to [`JsValue`] type
+    ///
+    /// # Returns
+    /// [`JsValue`] containing hotline outline color information
+    ///
+    #[must_use]
+    #[inline]
+    fn outline_color_to_js(outline_color: &Color) -> JsValue {
+        JsCast::unchecked_into(JsString::from(outline_color.to_css()))
+    }
+}
+
+/// parses a CSS outline color; an empty string gives the default black outline
+impl TryFrom<&str> for HotlineOutlineColor {
+    type Error = ColorError;
+
+    #[inline]
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "" => Ok(Self(Color::BLACK)),
+            color => Color::parse(color).map(Self),
+        }
+    }
+}
+
+pub struct HotlineMax(f64);
+
+impl HotlineMax {
+    pub fn hotline_max(&self) -> JsValue {
+        Self::max_to_js(&self.0)
+    }
+
+    ///
+    /// Converts hotline max breakpoint threshold to [`JsValue`] type
+    ///
+    /// # Returns
+    /// [`JsValue`] containing hotline max breakpoint threshold information
+    ///
+    #[must_use]
+    #[inline]
+    fn max_to_js(val: &f64) -> JsValue {
+        JsValue::from_f64(*val)
+    }
+}
+
+pub struct HotlineMin(f64);
+
+impl HotlineMin {
+    pub fn hotline_min(&self) -> JsValue {
+        Self::min_to_js(&self.0)
+    }
+
+    ///
+    /// Converts hotline min breakpoint threshold to [`JsValue`] type
+    ///
+    /// # Returns
+    /// [`JsValue`] containing hotline min breakpoint threshold information
+    ///
+    #[must_use]
+    #[inline]
+    fn min_to_js(val: &f64) -> JsValue {
+        JsValue::from_f64(*val)
+    }
+}
+
+/// adds hotline instance to a leptos-leaflet map
+/// # Arguments
+/// * `map` the map from the leptos-leaflet context
+/// * `hotline` instance
+/// * `overlay` map overlay
+///
+/// # Returns
+/// [`Result<(), HotlineError>`], [`HotlineError::JsException`] if adding the layer throws
+///
+#[inline]
+fn add_hotline_to_map(
+    map: &L::Map,
+    hotline: Hotline,
+    overlay: StoredValue<Option<Hotline>, LocalStorage>,
+) -> Result<(), HotlineError> {
+    let add_to: js_sys::Function = Reflect::get(&hotline, &"addTo".into())?.unchecked_into();
+    add_to.call1(&hotline, map)?;
+    update_overlay_context(&hotline);
+    overlay.set_value(Some(hotline));
+    Ok(())
+}
+
+/// removes the hotline instance held by the overlay from its map, if there is one,
+/// then detaches and drops its event listeners
+/// # Arguments
+/// * `overlay` map overlay
+/// * `listeners` event listeners attached to the overlay's hotline
+///
+#[inline]
+fn remove_hotline_from_map(
+    overlay: StoredValue<Option<Hotline>, LocalStorage>,
+    listeners: StoredValue<Option<HotlineListeners>, LocalStorage>,
+) {
+    let hotline_listeners = listeners.try_update_value(Option::take).flatten();
+    if let Some(hotline) = overlay.try_update_value(Option::take).flatten() {
+        hotline.remove();
+        if let Some(hotline_listeners) = hotline_listeners {
+            hotline_listeners.detach(&hotline);
+        }
+    }
+}
+
+pub struct HotlinePaletteStruct(HotlinePalette);
+
+impl HotlinePaletteStruct {
+    pub fn hotline_palette(&self) -> Result<JsValue, HotlineError> {
+        if !self.0.is_empty() {
+            Self::palette_to_js(&self.0)
+        } else {
+            Self::palette_to_js(&HotlinePalette::default())
+        }
+    }
+    ///
+    /// convert [`HotlinePalette`] to [`JsValue`] type
+    ///
+    /// # Returns
+    /// [`JsValue`] containing hotline palette information (maps breakpoint -> color for JS binding),
+    /// or [`HotlineError::JsException`] if a stop cannot be set
+    ///
+    #[inline]
+    fn palette_to_js(palette: &HotlinePalette) -> Result<JsValue, HotlineError> {
+        let palette_opts = Object::new();
+
+        for stop in palette.stops() {
+            Reflect::set(
+                &palette_opts,
+                &JsValue::from_f64(stop.breakpoint),
+                &stop.color.to_css().into(),
+            )?;
+        }
+
+        Ok(JsCast::unchecked_into(palette_opts))
+    }
+}
+
+///
+/// Creates hot polyline functional component added to a leptos leaflet map container
+///
+/// All props except `children` accept either plain values or reactive signals.
+/// The hotline layer is created once; later changes to a signal update the existing
+/// layer in place (`setLatLngs` / `setStyle`) rather than constructing a new one.
+/// The layer is removed from the map when the component unmounts, and moved when the
+/// map in the leptos-leaflet context changes.
+///
+/// `weight`, `outline_width`, `smooth_factor` and `no_clip` are updated in place as
+/// well.  `interactive`, `pane` and `class_name` only take effect when a leaflet layer
+/// is created, so changing one of them replaces the layer.  `renderer` is read once.
+/// Unset options keep the leaflet-hotline defaults.
+///
+/// # Arguments
+///
+/// * `positions` - [`HotlineTrack`] (or [`HotlinePositionVec`]) holding path and value information
+/// * `palette` - palette of colors and breakpoints
+/// * `outline_color` - string representing the polyline outline color
+/// * `max` - float representing max breakpoint to use for palette
+/// * `min` - float representing min breakpoint to use for palette
+/// * `weight` - line width in pixels
+/// * `outline_width` - outline width in pixels on each side of the line, 0 for no outline
+/// * `smooth_factor` - how much to simplify the polyline on each zoom level
+/// * `no_clip` - disable polyline clipping
+/// * `interactive` - whether the hotline emits mouse events
+/// * `pane` - name of the map pane the hotline is drawn in
+/// * `class_name` - custom class name set on the hotline element
+/// * `renderer` - [`HotlineRenderer`] used to draw the hotline, e.g. one shared by several
+///   hotlines; by default each hotline gets its own
+/// * `events` - [`HotlineEvents`] handlers; pointer events carry the nearest track point
+/// * `on_error` - called with a [`HotlineError`] when the layer cannot be created or
+///   updated, e.g. outside a `MapContainer`; errors are logged if unset
+/// * `children` - child elements; leptos-leaflet `Tooltip` and `Popup` children are bound
+///   to the hotline layer, and [`HotlineTooltip`] shows the value under the cursor
+///
+/// # Returns
+///
+/// `impl` [`leptos::IntoView`]
+///
+/// # Examples
+///
+/// Basic usage:
+/// ```ignore
+/// use leptos::prelude::*;
+/// use leptos_leaflet::prelude::{MapContainer, Position};
+/// use leptos_leaflet_hotline::{HotPolyline, HotlinePalette, HotlinePositionVec, HotlineTrack};
+///
+/// #[component]
+/// fn MyMap() -> impl IntoView {
+///     let (positions, set_positions) = signal(HotlineTrack::from(HotlinePositionVec::new(&[
+///         (90.000, 135.000, 0.0),
+///         (90.010, 135.010, 100.0),
+///     ])));
+///
+///     view! {
+///         <MapContainer style="height: 100vh" center=Position::new(90.000, 135.000) zoom=17.0 set_view=true>
+///             <HotPolyline
+///                 positions=positions
+///                 palette=HotlinePalette::new(&[("green", 0.0), ("red", 1.0)])
+///                 outline_color="white"
+///                 max=1.0
+///                 min=0.0
+///             />
+///         </MapContainer>
+///     }
+/// }
+/// ```
+///
+#[component]
+pub fn HotPolyline(
+    #[prop(into)] positions: Signal<HotlineTrack>,
+    #[prop(into)] palette: Signal<HotlinePalette>,
+    #[prop(optional, into)] outline_color: Signal<Option<String>>,
+    #[prop(optional, into)] max: Signal<Option<f64>>,
+    #[prop(optional, into)] min: Signal<Option<f64>>,
+    #[prop(optional, into)] weight: Signal<Option<f64>>,
+    #[prop(optional, into)] outline_width: Signal<Option<f64>>,
+    #[prop(optional, into)] smooth_factor: Signal<Option<f64>>,
+    #[prop(optional, into)] no_clip: Signal<Option<bool>>,
+    #[prop(optional, into)] interactive: Signal<Option<bool>>,
+    #[prop(optional, into)] pane: Signal<Option<String>>,
+    #[prop(optional, into)] class_name: Signal<Option<String>>,
+    #[prop(optional)] renderer: Option<JsValue>,
+    #[prop(optional)] events: HotlineEvents,
+    #[prop(optional, into)] on_error: Option<Callback<(HotlineError,)>>,
+    #[prop(optional)] children: Option<Children>,
+) -> impl IntoView {
+    extend_context_with_overlay();
+    let hotline_context = HotlineContext {
+        positions,
+        palette,
+        max,
+        min,
+    };
+    provide_context(hotline_context);
+    let overlay = StoredValue::new_with_storage(None::<Hotline>);
+    let listeners = StoredValue::new_with_storage(None::<HotlineListeners>);
+
+    let create_hotline = move || -> Result<(), HotlineError> {
+        // drop the layer from a previous run, e.g. when the map context changed
+        remove_hotline_from_map(overlay, listeners);
+
+        let map_context =
+            use_context::<LeafletMapContext>().ok_or(HotlineError::MissingMapContext)?;
+        // the map is created after its children mount; this runs again once it is
+        let Some(map) = map_context.map() else {
+            return Ok(());
+        };
+        if let Err(err) = positions.with_untracked(HotlineTrack::check_positions) {
+            // without a layer the positions watcher has nothing to update, so this
+            // runs again once the positions change
+            positions.track();
+            return Err(err);
+        }
+        report_error(on_error, palette.with_untracked(palette_error));
+
+        let opts = HotlineOptions::new(
+            &HotlinePaletteStruct(palette.get_untracked()).hotline_palette()?,
+            &outline_color_or_default(outline_color.get_untracked(), on_error).outline_color(),
+            &HotlineMax(max.get_untracked().unwrap_or(DEFAULT_MAX)).hotline_max(),
+            &HotlineMin(min.get_untracked().unwrap_or(DEFAULT_MIN)).hotline_min(),
+        );
+        if let Some(weight) = weight.get_untracked() {
+            opts.set_weight(weight);
+        }
+        if let Some(outline_width) = outline_width.get_untracked() {
+            opts.set_outline_width(outline_width);
+        }
+        if let Some(smooth_factor) = smooth_factor.get_untracked() {
+            opts.set_smooth_factor(smooth_factor);
+        }
+        if let Some(no_clip) = no_clip.get_untracked() {
+            opts.set_no_clip(no_clip);
+        }
+        // construction-only options are tracked, so changing them recreates the layer
+        if let Some(interactive) = interactive.get() {
+            opts.set_interactive(interactive);
+        }
+        if let Some(pane) = pane.get() {
+            opts.set_pane(&pane);
+        }
+        if let Some(class_name) = class_name.get() {
+            opts.set_class_name(class_name);
+        }
+        // `undefined` is what `HotPolylineResource` forwards when no renderer is set
+        if let Some(renderer) = renderer
+            .as_ref()
+            .filter(|renderer| !renderer.is_undefined())
+        {
+            opts.set_renderer(renderer.clone());
+        }
+
+        register_hotline()?;
+        let lat_lngs = positions.with_untracked(to_hotline_lat_lng_array);
+        let hotline = Hotline::try_new(&lat_lngs, &opts)?;
+        listeners.set_value(Some(events.setup(&hotline, move |position| {
+            hotline_context.nearest_vertex(position)
+        })));
+        add_hotline_to_map(&map, hotline, overlay)
+    };
+    Effect::new(move |_| report_error(on_error, create_hotline()));
+
+    Effect::watch(
+        move || positions.get(),
+        move |positions, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                let result = positions.check_positions().map(|()| {
+                    hotline.set_lat_lngs(&to_hotline_lat_lng_array(positions));
+                });
+                report_error(on_error, result);
+            }
+        },
+        false,
+    );
+
+    Effect::watch(
+        move || palette.get(),
+        move |palette, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                report_error(on_error, palette_error(palette));
+                let result = HotlinePaletteStruct(palette.clone())
+                    .hotline_palette()
+                    .and_then(|js_palette| hotline.set_palette_val(&js_palette));
+                report_error(on_error, result);
+            }
+        },
+        false,
+    );
+
+    Effect::watch(
+        move || outline_color.get(),
+        move |color, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                let color = outline_color_or_default(color.clone(), on_error).color();
+                report_error(on_error, hotline.set_outline_color_val(&color.to_css()));
+            }
+        },
+        false,
+    );
+
+    Effect::watch(
+        move || max.get(),
+        move |max, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                report_error(on_error, hotline.set_max_val(max.unwrap_or(DEFAULT_MAX)));
+            }
+        },
+        false,
+    );
+
+    Effect::watch(
+        move || min.get(),
+        move |min, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                report_error(on_error, hotline.set_min_val(min.unwrap_or(DEFAULT_MIN)));
+            }
+        },
+        false,
+    );
+
+    Effect::watch(
+        move || weight.get(),
+        move |weight, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                report_error(
+                    on_error,
+                    hotline.set_weight_val(weight.unwrap_or(DEFAULT_WEIGHT)),
+                );
+            }
+        },
+        false,
+    );
+
+    Effect::watch(
+        move || outline_width.get(),
+        move |outline_width, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                let width = outline_width.unwrap_or(DEFAULT_OUTLINE_WIDTH);
+                report_error(on_error, hotline.set_outline_width_val(width));
+            }
+        },
+        false,
+    );
+
+    Effect::watch(
+        move || smooth_factor.get(),
+        move |smooth_factor, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                let factor = smooth_factor.unwrap_or(DEFAULT_SMOOTH_FACTOR);
+                report_error(on_error, hotline.set_smooth_factor_val(factor));
+            }
+        },
+        false,
+    );
+
+    Effect::watch(
+        move || no_clip.get(),
+        move |no_clip, _, _| {
+            if let Some(hotline) = overlay.get_value().as_ref() {
+                report_error(
+                    on_error,
+                    hotline.set_no_clip_val(no_clip.unwrap_or(DEFAULT_NO_CLIP)),
+                );
+            }
+        },
+        false,
+    );
+
+    on_cleanup(move || remove_hotline_from_map(overlay, listeners));
+
+    children.map(move |child| child())
+}
+
+/// default max breakpoint threshold used when the `max` prop is not set
+pub(crate) const DEFAULT_MAX: f64 = 1.0;
+
+/// default min breakpoint threshold used when the `min` prop is not set
+pub(crate) const DEFAULT_MIN: f64 = 0.0;
+
+/// leaflet's default polyline simplification, used when the `smooth_factor` prop is not set
+const DEFAULT_SMOOTH_FACTOR: f64 = 1.0;
+
+/// leaflet's default polyline clipping, used when the `no_clip` prop is not set
+const DEFAULT_NO_CLIP: bool = false;
+
+/// data of the enclosing [`HotPolyline`], provided to its children such as [`HotlineTooltip`]
+#[derive(Clone, Copy)]
+pub(crate) struct HotlineContext {
+    pub(crate) positions: Signal<HotlineTrack>,
+    pub(crate) palette: Signal<HotlinePalette>,
+    pub(crate) max: Signal<Option<f64>>,
+    pub(crate) min: Signal<Option<f64>>,
+}
+
+impl HotlineContext {
+    /// color the hotline draws for a value, using the current palette, min and max
+    pub(crate) fn color_for_value(&self, value: f64) -> Color {
+        let max = self.max.get_untracked().unwrap_or(DEFAULT_MAX);
+        let min = self.min.get_untracked().unwrap_or(DEFAULT_MIN);
+        self.palette.with_untracked(|palette| {
+            if palette.is_empty() {
+                HotlinePalette::default().color_for_value(value, min, max)
+            } else {
+                palette.color_for_value(value, min, max)
+            }
+        })
+    }
+
+    /// finds the track point nearest to a pointer position, with its raw value and drawn color
+    fn nearest_vertex(&self, position: FlatPosition) -> Option<HotlineVertex> {
+        let (index, value, raw_value) = self.positions.with_untracked(|track| {
+            track
+                .nearest_index(&position)
+                .map(|index| (index, track.values()[index], track.raw_values()[index]))
+        })?;
+        Some(HotlineVertex {
+            index,
+            value: raw_value,
+            color: self.color_for_value(value),
+        })
+    }
+}
+
+/// parses the outline color, falling back to black when none is set or it is invalid
+#[inline]
+fn outline_color_or_default(
+    outline_color: Option<String>,
+    on_error: Option<Callback<(HotlineError,)>>,
+) -> HotlineOutlineColor {
+    let color = outline_color.unwrap_or_default();
+    HotlineOutlineColor::try_from(color.as_str()).unwrap_or_else(|err| {
+        report_error(on_error, Err(err.into()));
+        HotlineOutlineColor(Color::BLACK)
+    })
+}
+
+/// error of a palette built by [`HotlinePalette::new`] from invalid stops
+fn palette_error(palette: &HotlinePalette) -> Result<(), HotlineError> {
+    palette
+        .error()
+        .map_or(Ok(()), |err| Err(err.clone().into()))
+}
+
+/// passes the error of a failed hotline update to `on_error`, or logs it when there is none
+pub(crate) fn report_error(
+    on_error: Option<Callback<(HotlineError,)>>,
+    result: Result<(), HotlineError>,
+) {
+    if let Err(err) = result {
+        match on_error {
+            Some(on_error) => on_error.run((err,)),
+            None => log!("{err}"),
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{ColorStop, HotlineHover, HotlineTooltip};
+    use leptos::task::{tick, Executor};
+    use std::cell::Cell;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::*;
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const LEAFLET_JS: &str = "https://unpkg.com/leaflet@1.9.3/dist/leaflet.js";
+
+    /// appends a script tag to the test page and waits for it to load
+    async fn load_script(src: &str) {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let script: web_sys::HtmlScriptElement =
+            document.create_element("script").unwrap().unchecked_into();
+        script.set_src(src);
+
+        let loaded = js_sys::Promise::new(&mut |resolve, reject| {
+            script.set_onload(Some(&resolve));
+            script.set_onerror(Some(&reject));
+        });
+        document.head().unwrap().append_child(&script).unwrap();
+        JsFuture::from(loaded).await.unwrap();
+    }
+
+    /// loads leaflet once per test page and registers the native hotline layer
+    async fn load_leaflet() {
+        let _ = Executor::init_wasm_bindgen();
+        if !Reflect::has(&js_sys::global(), &"L".into()).unwrap() {
+            load_script(LEAFLET_JS).await;
+        }
+        register_hotline().unwrap();
+    }
+
+    /// creates a leaflet map in a new div with its view already set
+    fn test_map() -> L::Map {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let div: web_sys::HtmlElement = document.create_element("div").unwrap().unchecked_into();
+        div.style().set_property("height", "200px").unwrap();
+        document.body().unwrap().append_child(&div).unwrap();
+
+        let map = L::Map::new_with_element(&div, &L::MapOptions::new());
+        map.set_view(&L::LatLng::new(40.2928, -105.6180), 15.0);
+        map
+    }
+
+    /// counts the hotline layers on a map (renderers and other layers are skipped)
+    fn hotline_count(map: &L::Map) -> usize {
+        let count = Cell::new(0);
+        map.each_layer(&|layer| {
+            if Reflect::has(&layer, &"getRGBForValue".into()).unwrap_or(false) {
+                count.set(count.get() + 1);
+            }
+        });
+        count.get()
+    }
+
+    /// mounts a [`HotPolyline`] under a new owner with the given map context
+    fn mount_hot_polyline(context: LeafletMapContext) -> Owner {
+        let owner = Owner::new();
+        owner.with(|| {
+            provide_context(context);
+            let _view = view! {
+                <HotPolyline
+                    positions=HotlinePositionVec::new(&[(40.2928, -105.6180, 1.0), (40.2928, -105.6190, 2.0)])
+                    palette=HotlinePalette::default()
+                />
+            };
+        });
+        owner
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_hotline_removed_on_unmount() {
+        load_leaflet().await;
+        let map = test_map();
+        let context = LeafletMapContext::new();
+        context.set_map(&map);
+
+        let owner = mount_hot_polyline(context);
+        tick().await;
+        assert_eq!(hotline_count(&map), 1, "expected the hotline to be added");
+
+        owner.cleanup();
+        tick().await;
+        assert_eq!(hotline_count(&map), 0, "expected the hotline to be removed");
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_hotline_moved_on_map_change() {
+        load_leaflet().await;
+        let first_map = test_map();
+        let second_map = test_map();
+        let context = LeafletMapContext::new();
+        context.set_map(&first_map);
+
+        let _owner = mount_hot_polyline(context);
+        tick().await;
+        assert_eq!(hotline_count(&first_map), 1);
+
+        context.set_map(&second_map);
+        tick().await;
+        assert_eq!(hotline_count(&first_map), 0, "expected no orphaned hotline");
+        assert_eq!(
+            hotline_count(&second_map),
+            1,
+            "expected exactly one hotline"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_hotline_not_duplicated_on_prop_change() {
+        load_leaflet().await;
+        let map = test_map();
+        let context = LeafletMapContext::new();
+        context.set_map(&map);
+
+        let owner = Owner::new();
+        let max = RwSignal::new(Some(1.0));
+        owner.with(|| {
+            provide_context(context);
+            let _view = view! {
+                <HotPolyline
+                    positions=HotlinePositionVec::new(&[(40.2928, -105.6180, 1.0), (40.2928, -105.6190, 2.0)])
+                    palette=HotlinePalette::default()
+                    max=max
+                />
+            };
+        });
+        tick().await;
+
+        max.set(Some(0.5));
+        tick().await;
+        assert_eq!(
+            hotline_count(&map),
+            1,
+            "expected the existing hotline to be updated"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_hotline_errors_reported() {
+        load_leaflet().await;
+        let map = test_map();
+        let context = LeafletMapContext::new();
+        context.set_map(&map);
+
+        let owner = Owner::new();
+        let errors = RwSignal::new(Vec::<HotlineError>::new());
+        let positions = RwSignal::new(HotlineTrack::from(HotlinePositionVec::new(&[
+            (40.2928, -105.6180, 1.0),
+            (40.2928, -105.6190, 2.0),
+        ])));
+        owner.with(|| {
+            provide_context(context);
+            let _view = view! {
+                <HotPolyline
+                    positions=positions
+                    palette=HotlinePalette::new(&[("gren", 0.0), ("red", 1.0)])
+                    outline_color="not a color"
+                    on_error=move |err: HotlineError| errors.update(|errors| errors.push(err))
+                />
+            };
+        });
+        tick().await;
+        assert_eq!(hotline_count(&map), 1, "expected defaults instead");
+        assert!(matches!(
+            errors.get_untracked().as_slice(),
+            [
+                HotlineError::InvalidPalette(_),
+                HotlineError::InvalidColor(_)
+            ]
+        ));
+
+        positions.update(|track| track.push(FlatPosition::new(f64::NAN, -105.62), 3.0));
+        tick().await;
+        assert!(matches!(
+            errors.get_untracked().last(),
+            Some(HotlineError::InvalidPosition { index: 2, .. })
+        ));
+
+        let errors = RwSignal::new(Vec::<HotlineError>::new());
+        let unmapped = Owner::new();
+        unmapped.with(|| {
+            let _view = view! {
+                <HotPolyline
+                    positions=HotlinePositionVec::new(&[(40.2928, -105.6180, 1.0)])
+                    palette=HotlinePalette::default()
+                    on_error=move |err: HotlineError| errors.update(|errors| errors.push(err))
+                />
+            };
+        });
+        tick().await;
+        assert_eq!(
+            errors.get_untracked(),
+            vec![HotlineError::MissingMapContext]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_hotline_created_once_positions_valid() {
+        load_leaflet().await;
+        let map = test_map();
+        let context = LeafletMapContext::new();
+        context.set_map(&map);
+
+        let owner = Owner::new();
+        let errors = RwSignal::new(Vec::<HotlineError>::new());
+        let positions = RwSignal::new(HotlineTrack::from(HotlinePositionVec::new(&[
+            (40.2928, -105.6180, 1.0),
+            (f64::NAN, -105.6190, 2.0),
+        ])));
+        owner.with(|| {
+            provide_context(context);
+            let _view = view! {
+                <HotPolyline
+                    positions=positions
+                    palette=HotlinePalette::default()
+                    on_error=move |err: HotlineError| errors.update(|errors| errors.push(err))
+                />
+            };
+        });
+        tick().await;
+        assert_eq!(hotline_count(&map), 0);
+        assert!(matches!(
+            errors.get_untracked().as_slice(),
+            [HotlineError::InvalidPosition { index: 1, .. }]
+        ));
+
+        positions.set(HotlineTrack::from(HotlinePositionVec::new(&[
+            (40.2928, -105.6180, 1.0),
+            (40.2928, -105.6190, 2.0),
+        ])));
+        tick().await;
+        assert_eq!(
+            hotline_count(&map),
+            1,
+            "expected the hotline once its positions are valid"
+        );
+        assert_eq!(errors.get_untracked().len(), 1);
+    }
+
+    /// leaflet options of the single hotline layer on the map
+    fn hotline_options(map: &L::Map) -> Object {
+        let options = std::cell::RefCell::new(None);
+        map.each_layer(&|layer| {
+            if Reflect::has(&layer, &"getRGBForValue".into()).unwrap_or(false) {
+                let opts = Reflect::get(&layer, &"options".into()).unwrap();
+                *options.borrow_mut() = Some(opts.unchecked_into());
+            }
+        });
+        options.into_inner().expect("expected a hotline on the map")
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_hotline_layer_options() {
+        load_leaflet().await;
+        let map = test_map();
+        let context = LeafletMapContext::new();
+        context.set_map(&map);
+
+        let owner = Owner::new();
+        let weight = RwSignal::new(Some(8.0));
+        let outline_width = RwSignal::new(Some(2.0));
+        let smooth_factor = RwSignal::new(Some(3.0));
+        let no_clip = RwSignal::new(Some(true));
+        let pane = RwSignal::new(Some("shadowPane".to_string()));
+        owner.with(|| {
+            provide_context(context);
+            let _view = view! {
+                <HotPolyline
+                    positions=HotlinePositionVec::new(&[(40.2928, -105.6180, 1.0), (40.2928, -105.6190, 2.0)])
+                    palette=HotlinePalette::default()
+                    weight=weight
+                    outline_width=outline_width
+                    smooth_factor=smooth_factor
+                    no_clip=no_clip
+                    pane=pane
+                />
+            };
+        });
+        tick().await;
+
+        let option = |key: &str| Reflect::get(&hotline_options(&map), &key.into()).unwrap();
+        assert_eq!(option("weight").as_f64(), Some(8.0));
+        assert_eq!(option("outlineWidth").as_f64(), Some(2.0));
+        assert_eq!(option("pane").as_string().as_deref(), Some("shadowPane"));
+
+        weight.set(Some(12.0));
+        pane.set(Some("markerPane".to_string()));
+        tick().await;
+        assert_eq!(
+            hotline_count(&map),
+            1,
+            "expected the hotline to be replaced"
+        );
+        assert_eq!(option("weight").as_f64(), Some(12.0));
+        assert_eq!(option("pane").as_string().as_deref(), Some("markerPane"));
+
+        weight.set(None);
+        outline_width.set(None);
+        smooth_factor.set(None);
+        no_clip.set(None);
+        tick().await;
+        assert_eq!(option("weight").as_f64(), Some(DEFAULT_WEIGHT));
+        assert_eq!(option("outlineWidth").as_f64(), Some(DEFAULT_OUTLINE_WIDTH));
+        assert_eq!(option("smoothFactor").as_f64(), Some(DEFAULT_SMOOTH_FACTOR));
+        assert_eq!(option("noClip").as_bool(), Some(DEFAULT_NO_CLIP));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_hotline_click_event() {
+        load_leaflet().await;
+        let map = test_map();
+        let context = LeafletMapContext::new();
+        context.set_map(&map);
+
+        let clicked = std::rc::Rc::new(Cell::new(None));
+        let events = HotlineEvents::new().on_click({
+            let clicked = clicked.clone();
+            move |event| clicked.set(Some((event.index, event.value, event.color)))
+        });
+        let owner = Owner::new();
+        owner.with(|| {
+            provide_context(context);
+            let _view = view! {
+                <HotPolyline
+                    positions=HotlinePositionVec::new(&[(40.2928, -105.6180, 10.0), (40.2928, -105.6190, 20.0)])
+                    palette=HotlinePalette::new(&[("green", 0.0), ("red", 1.0)])
+                    events=events
+                />
+            };
+        });
+        tick().await;
+
+        let data = Object::new();
+        Reflect::set(
+            &data,
+            &"latlng".into(),
+            &L::LatLng::new(40.2929, -105.6189).into(),
+        )
+        .unwrap();
+        let layer = std::cell::RefCell::new(None);
+        map.each_layer(&|l| {
+            if Reflect::has(&l, &"getRGBForValue".into()).unwrap_or(false) {
+                *layer.borrow_mut() = Some(l);
+            }
+        });
+        let layer = layer.into_inner().expect("expected a hotline on the map");
+        layer.fire("click", &data, None);
+
+        let (index, value, color) = clicked.get().expect("expected the click handler to run");
+        assert_eq!(index, 1);
+        assert_eq!(value, 20.0);
+        assert_eq!(
+            color,
+            HotlinePalette::new(&[("green", 0.0), ("red", 1.0)]).color_for_value(1.0, 0.0, 1.0)
+        );
+
+        owner.cleanup();
+        tick().await;
+        assert!(
+            !layer.listens("click", None),
+            "expected the click handler to be detached"
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_hotline_tooltip_bound_to_layer() {
+        load_leaflet().await;
+        let map = test_map();
+        let context = LeafletMapContext::new();
+        context.set_map(&map);
+
+        let owner = Owner::new();
+        let direction = RwSignal::new("top".to_string());
+        owner.with(|| {
+            provide_context(context);
+            let _view = view! {
+                <HotPolyline
+                    positions=HotlinePositionVec::new(&[(40.2928, -105.6180, 10.0), (40.2928, -105.6190, 20.0)])
+                    palette=HotlinePalette::default()
+                >
+                    <HotlineTooltip
+                        format=|hover: HotlineHover| format!("{:.1}", hover.value)
+                        direction=direction
+                    />
+                </HotPolyline>
+            };
+        });
+        tick().await;
+
+        let bound_direction = || {
+            let bound = std::cell::RefCell::new(JsValue::NULL);
+            map.each_layer(&|layer| {
+                if Reflect::has(&layer, &"getRGBForValue".into()).unwrap_or(false) {
+                    let get_tooltip: js_sys::Function = Reflect::get(&layer, &"getTooltip".into())
+                        .unwrap()
+                        .unchecked_into();
+                    *bound.borrow_mut() = get_tooltip.call0(&layer).unwrap();
+                }
+            });
+            let bound = bound.into_inner();
+            assert!(bound.is_object(), "expected a tooltip bound to the hotline");
+            let options = Reflect::get(&bound, &"options".into()).unwrap();
+            Reflect::get(&options, &"direction".into())
+                .unwrap()
+                .as_string()
+        };
+        assert_eq!(bound_direction().as_deref(), Some("top"));
+
+        direction.set("bottom".to_string());
+        tick().await;
+        assert_eq!(bound_direction().as_deref(), Some("bottom"));
+
+        owner.cleanup();
+        tick().await;
+        assert_eq!(hotline_count(&map), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_hotline_palette_type() {
+        let palette_struct = HotlinePaletteStruct(HotlinePalette::new(&[("#FF0000", 0.0)]));
+
+        let js_value = palette_struct.hotline_palette().unwrap();
+
+        assert!(js_value.is_object());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_hotline_palette_colors() {
+        let palette_struct = HotlinePaletteStruct(HotlinePalette::new(&[
+            ("#FF0000", 0.0),
+            ("#00FF00", 0.5),
+            ("#0000FF", 0.99),
+        ]));
+
+        let js_value = palette_struct.hotline_palette().unwrap();
+
+        let js_obj: Object = js_value.unchecked_into();
+
+        for stop in palette_struct.0.stops() {
+            assert_eq!(
+                Reflect::get(&js_obj, &JsValue::from_f64(stop.breakpoint))
+                    .unwrap()
+                    .as_string()
+                    .unwrap(),
+                stop.color.to_css()
+            )
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_default_palette() {
+        let palette_struct = HotlinePaletteStruct(HotlinePalette::new(&[]));
+
+        let js_value = palette_struct.hotline_palette().unwrap();
+
+        let js_obj: Object = js_value.unchecked_into();
+
+        let default_palette = HotlinePalette::default();
+
+        let mut keys: Vec<f64> = Object::keys(&js_obj)
+            .iter()
+            .filter_map(|key| key.as_string().and_then(|k| k.parse::<f64>().ok()))
+            .collect();
+
+        keys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let expected_keys: Vec<f64> = default_palette
+            .stops()
+            .iter()
+            .map(|stop| stop.breakpoint)
+            .collect();
+
+        assert_eq!(
+            keys, expected_keys,
+            "mismatch in breakpoints: expected {:?} got {:?}",
+            expected_keys, keys
+        );
+
+        for ColorStop {
+            color,
+            breakpoint: breakpt,
+            ..
+        } in default_palette.stops()
+        {
+            let js_color = Reflect::get(&js_obj, &JsValue::from_f64(*breakpt))
+                .unwrap_or_else(|_| panic!("Missing breakpoint {}", breakpt))
+                .as_string()
+                .unwrap_or_else(|| panic!("Expected string color for {}", breakpt));
+
+            assert_eq!(
+                js_color,
+                color.to_css(),
+                "Mismatch for breakpt {}: expected: {} got {}",
+                breakpt,
+                color,
+                js_color
+            );
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_outline_color() {
+        let outline_color = HotlineOutlineColor::try_from("red").unwrap();
+        let js_value = outline_color.outline_color();
+
+        let js_string: String = js_value.as_string().unwrap();
+
+        assert_eq!(
+            js_string, "#ff0000",
+            "expected outline color of '#ff0000' but got {}",
+            js_string
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_default_outline_color() {
+        let outline_color = HotlineOutlineColor::try_from("").unwrap();
+        let js_value = outline_color.outline_color();
+
+        let js_string: String = js_value.as_string().unwrap();
+
+        assert_eq!(
+            js_string, "#000000",
+            "expected outline color of '#000000' but got {}",
+            js_string
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_hotline_max_with_value() {
+        let hotline_max = HotlineMax(0.75);
+        let js_value = hotline_max.hotline_max();
+
+        let js_f64: f64 = js_value.as_f64().unwrap();
+
+        assert_eq!(js_f64, 0.75, "Expected 0.75, but got {}", js_f64);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_hotline_min_with_value() {
+        let hotline_min = HotlineMin(0.25);
+        let js_value = hotline_min.hotline_min();
+
+        let js_f64: f64 = js_value.as_f64().unwrap();
+
+        assert_eq!(js_f64, 0.25, "Expected 0.25, but got {}", js_f64);
+    }
+}
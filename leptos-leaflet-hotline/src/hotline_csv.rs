@@ -0,0 +1,503 @@
+//! module for importing delimited text (CSV, TSV, ...) as hotline positions
+use std::fmt;
+
+use csv::{ReaderBuilder, StringRecord};
+
+use super::hotline_normalization::Normalization;
+use super::hotline_position::HotlinePositionVec;
+
+///
+/// column of a delimited text file, selected by header name or zero-based index
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// column with this header, compared after trimming whitespace
+    Name(String),
+    /// zero-based column index
+    Index(usize),
+}
+
+impl From<&str> for CsvColumn {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<String> for CsvColumn {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl From<usize> for CsvColumn {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl fmt::Display for CsvColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{name:?}"),
+            Self::Index(index) => write!(f, "#{index}"),
+        }
+    }
+}
+
+///
+/// column mapping and dialect for [`HotlinePositionVec::from_csv`]
+///
+/// Defaults to a comma delimiter, a header row and a single track.
+///
+/// # Examples
+///
+/// ```
+/// use leptos_leaflet_hotline::CsvColumns;
+///
+/// let columns = CsvColumns::new("latitude", "longitude", "pm25")
+///     .with_delimiter(b';')
+///     .with_group_by("sensor_id");
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CsvColumns {
+    /// latitude column
+    pub lat: CsvColumn,
+    /// longitude column
+    pub lng: CsvColumn,
+    /// column with the values to color the hotline by
+    pub value: CsvColumn,
+    /// optional column whose value splits the rows into separate tracks
+    pub group_by: Option<CsvColumn>,
+    /// field delimiter byte, `b','` by default
+    pub delimiter: u8,
+    /// whether the first row holds column names, `true` by default
+    pub has_headers: bool,
+}
+
+/// implement builder methods for [`CsvColumns`]
+impl CsvColumns {
+    ///
+    /// map the latitude, longitude and value columns by header name or index
+    ///
+    /// # Returns
+    /// [`CsvColumns`]
+    ///
+    #[must_use]
+    pub fn new(
+        lat: impl Into<CsvColumn>,
+        lng: impl Into<CsvColumn>,
+        value: impl Into<CsvColumn>,
+    ) -> Self {
+        Self {
+            lat: lat.into(),
+            lng: lng.into(),
+            value: value.into(),
+            group_by: None,
+            delimiter: b',',
+            has_headers: true,
+        }
+    }
+
+    ///
+    /// set the field delimiter, e.g. `b';'` or `b'\t'`
+    ///
+    /// # Returns
+    /// [`CsvColumns`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    ///
+    /// split the rows into one track per distinct value of `column`
+    ///
+    /// # Returns
+    /// [`CsvColumns`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn with_group_by(mut self, column: impl Into<CsvColumn>) -> Self {
+        self.group_by = Some(column.into());
+        self
+    }
+
+    ///
+    /// read the first row as data; columns must then be selected by index
+    ///
+    /// # Returns
+    /// [`CsvColumns`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn without_headers(mut self) -> Self {
+        self.has_headers = false;
+        self
+    }
+}
+
+///
+/// track read from delimited text
+///
+/// # Fields
+/// * `id` [`Option<String>`] value of the group-by column, `None` without grouping
+/// * `positions` [`HotlinePositionVec`] positions of the track, in file order
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct CsvTrack {
+    /// value of the group-by column
+    pub id: Option<String>,
+    /// positions of the track, in file order
+    pub positions: HotlinePositionVec,
+}
+
+///
+/// tracks read from delimited text, along with the rows that were skipped
+///
+/// # Fields
+/// * `tracks` [`Vec<CsvTrack>`] one track, or one per group in order of first appearance
+/// * `skipped` [`Vec<CsvError>`] malformed rows, with their line numbers
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct CsvImport {
+    /// tracks in order of first appearance
+    pub tracks: Vec<CsvTrack>,
+    /// malformed rows that were skipped
+    pub skipped: Vec<CsvError>,
+}
+
+///
+/// error returned when delimited text cannot be imported, or describing a skipped row
+///
+/// Line numbers are one-based and count the header row.
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum CsvError {
+    /// the text could not be read as delimited records
+    Csv {
+        /// line of the error, if known
+        line: Option<u64>,
+        /// description of the error
+        message: String,
+    },
+    /// a mapped column is not in the header row, or a column is selected by name without headers
+    MissingColumn(CsvColumn),
+    /// a row has no field for a mapped column
+    MissingField {
+        /// line of the row
+        line: u64,
+        /// column that is missing
+        column: CsvColumn,
+    },
+    /// a field is not a number, or a latitude/longitude is out of range
+    InvalidValue {
+        /// line of the row
+        line: u64,
+        /// column of the field
+        column: CsvColumn,
+        /// text of the field
+        text: String,
+    },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Csv {
+                line: Some(line),
+                message,
+            } => write!(f, "invalid CSV at line {line}: {message}"),
+            Self::Csv {
+                line: None,
+                message,
+            } => write!(f, "invalid CSV: {message}"),
+            Self::MissingColumn(column) => write!(f, "CSV column {column} not found"),
+            Self::MissingField { line, column } => {
+                write!(f, "CSV line {line} has no field for column {column}")
+            }
+            Self::InvalidValue { line, column, text } => {
+                write!(
+                    f,
+                    "CSV line {line} has invalid value {text:?} in column {column}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+/// rows of one track, keyed by the value of the group-by column
+type CsvGroup = (Option<String>, Vec<(f64, f64, f64)>);
+
+/// implement delimited text import for [`HotlinePositionVec`]
+impl HotlinePositionVec {
+    ///
+    /// import positions from delimited text, e.g. a sensor log exported as CSV
+    ///
+    /// Rows with a missing or invalid latitude, longitude or value are skipped and
+    /// reported in [`CsvImport::skipped`].  Values are normalized as in
+    /// [`HotlinePositionVec::new`], over all rows so that every track shares one color
+    /// scale; see [`HotlinePositionVec::from_csv_with_normalization`] to pick another
+    /// [`Normalization`].
+    ///
+    /// # Returns
+    /// [`CsvImport`], or [`CsvError`] if the text cannot be read or a mapped column
+    /// is not in the header row
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{CsvColumns, HotlinePositionVec};
+    ///
+    /// let csv = "sensor,lat,lng,pm25\n\
+    ///            a,40.2928,-105.6180,12\n\
+    ///            b,40.3000,-105.6000,30\n\
+    ///            a,40.2928,-105.6190,n/a\n\
+    ///            a,40.2928,-105.6200,18\n";
+    /// let columns = CsvColumns::new("lat", "lng", "pm25").with_group_by("sensor");
+    /// let import = HotlinePositionVec::from_csv(csv, &columns).unwrap();
+    ///
+    /// assert_eq!(import.tracks[0].id.as_deref(), Some("a"));
+    /// assert_eq!(import.tracks[0].positions.raw_values, vec![12.0, 18.0]);
+    /// assert_eq!(import.skipped.len(), 1);
+    /// ```
+    ///
+    #[inline]
+    pub fn from_csv(text: &str, columns: &CsvColumns) -> Result<CsvImport, CsvError> {
+        Self::from_csv_with_normalization(text, columns, Normalization::default())
+    }
+
+    ///
+    /// import positions from delimited text, normalizing the values of all rows
+    /// with the given strategy
+    ///
+    /// # Returns
+    /// [`CsvImport`], or [`CsvError`] as for [`HotlinePositionVec::from_csv`]
+    ///
+    pub fn from_csv_with_normalization(
+        text: &str,
+        columns: &CsvColumns,
+        normalization: Normalization,
+    ) -> Result<CsvImport, CsvError> {
+        let mut reader = ReaderBuilder::new()
+            .delimiter(columns.delimiter)
+            .has_headers(columns.has_headers)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+
+        let headers = if columns.has_headers {
+            Some(reader.headers().map_err(csv_error)?.clone())
+        } else {
+            None
+        };
+        let index_of = |column: &CsvColumn| column_index(column, headers.as_ref());
+        let (lat, lng, value) = (
+            index_of(&columns.lat)?,
+            index_of(&columns.lng)?,
+            index_of(&columns.value)?,
+        );
+        let group_by = columns.group_by.as_ref().map(index_of).transpose()?;
+
+        let mut groups: Vec<CsvGroup> = Vec::new();
+        let mut skipped = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(csv_error)?;
+            let line = record.position().map_or(0, csv::Position::line);
+            let field = |index: usize, column: &CsvColumn, range: f64| {
+                read_number(&record, line, index, column, range)
+            };
+
+            let row = field(lat, &columns.lat, 90.0).and_then(|lat| {
+                Ok((
+                    lat,
+                    field(lng, &columns.lng, 180.0)?,
+                    field(value, &columns.value, f64::INFINITY)?,
+                ))
+            });
+            let row = match row {
+                Ok(row) => row,
+                Err(err) => {
+                    skipped.push(err);
+                    continue;
+                }
+            };
+
+            let id = group_by.map(|index| record.get(index).unwrap_or_default().to_string());
+            match groups.iter_mut().find(|(group, _)| *group == id) {
+                Some((_, rows)) => rows.push(row),
+                None => groups.push((id, vec![row])),
+            }
+        }
+
+        let all_values: Vec<f64> = groups
+            .iter()
+            .flat_map(|(_, rows)| rows.iter().map(|row| row.2))
+            .collect();
+        let normalization = normalization.resolve(&all_values);
+
+        Ok(CsvImport {
+            tracks: groups
+                .into_iter()
+                .map(|(id, rows)| CsvTrack {
+                    id,
+                    positions: Self::with_normalization(&rows, normalization),
+                })
+                .collect(),
+            skipped,
+        })
+    }
+}
+
+/// resolve a column to its index in the records
+fn column_index(column: &CsvColumn, headers: Option<&StringRecord>) -> Result<usize, CsvError> {
+    let missing = || CsvError::MissingColumn(column.clone());
+    match (column, headers) {
+        (CsvColumn::Name(name), Some(headers)) => headers
+            .iter()
+            .position(|header| header == name.trim())
+            .ok_or_else(missing),
+        (CsvColumn::Name(_), None) => Err(missing()),
+        (CsvColumn::Index(index), Some(headers)) if *index >= headers.len() => Err(missing()),
+        (CsvColumn::Index(index), _) => Ok(*index),
+    }
+}
+
+/// read a finite number no larger than `range` in magnitude from a record field
+fn read_number(
+    record: &StringRecord,
+    line: u64,
+    index: usize,
+    column: &CsvColumn,
+    range: f64,
+) -> Result<f64, CsvError> {
+    let text = record.get(index).ok_or_else(|| CsvError::MissingField {
+        line,
+        column: column.clone(),
+    })?;
+    text.parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite() && number.abs() <= range)
+        .ok_or_else(|| CsvError::InvalidValue {
+            line,
+            column: column.clone(),
+            text: text.to_string(),
+        })
+}
+
+/// convert a csv crate error, keeping its line number
+fn csv_error(err: csv::Error) -> CsvError {
+    CsvError::Csv {
+        line: err.position().map(csv::Position::line),
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+time;sensor;lat;lng;pm25
+0;north;40.30;-105.60;12.5
+0;south;40.10;-105.60;30
+1;north;40.31;-105.61;14
+1;south;40.11;-105.61;
+2;north;95.00;-105.62;11
+2;south;40.12;-105.62;34
+";
+
+    #[test]
+    fn test_group_by_and_delimiter() {
+        let columns = CsvColumns::new("lat", "lng", "pm25")
+            .with_delimiter(b';')
+            .with_group_by("sensor");
+        let import = HotlinePositionVec::from_csv(LOG, &columns).unwrap();
+
+        let ids: Vec<_> = import.tracks.iter().map(|track| track.id.clone()).collect();
+        assert_eq!(
+            ids,
+            vec![Some("north".to_string()), Some("south".to_string())]
+        );
+        assert_eq!(import.tracks[0].positions.raw_values, vec![12.5, 14.0]);
+        assert_eq!(import.tracks[1].positions.raw_values, vec![30.0, 34.0]);
+        assert_eq!(import.tracks[1].positions.positions[1].latlng.lat, 40.12);
+
+        let import =
+            HotlinePositionVec::from_csv_with_normalization(LOG, &columns, Normalization::MinMax)
+                .unwrap();
+        // one color scale for all sensors, from 12.5 in the north to 34 in the south
+        assert_eq!(import.tracks[0].positions.positions[0].alt, 0.0);
+        assert_eq!(
+            import.tracks[1].positions.positions[0].alt,
+            (30.0 - 12.5) / 21.5
+        );
+        assert_eq!(import.tracks[1].positions.positions[1].alt, 1.0);
+    }
+
+    #[test]
+    fn test_skipped_rows_report_line_numbers() {
+        let columns = CsvColumns::new("lat", "lng", "pm25").with_delimiter(b';');
+        let import = HotlinePositionVec::from_csv(LOG, &columns).unwrap();
+
+        assert_eq!(import.tracks.len(), 1);
+        assert_eq!(import.tracks[0].id, None);
+        assert_eq!(import.tracks[0].positions.positions.len(), 4);
+        assert_eq!(
+            import.skipped,
+            vec![
+                CsvError::InvalidValue {
+                    line: 5,
+                    column: "pm25".into(),
+                    text: String::new(),
+                },
+                CsvError::InvalidValue {
+                    line: 6,
+                    column: "lat".into(),
+                    text: "95.00".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_columns_by_index_without_headers() {
+        let csv = "40.0\t-105.0\t1\n40.1\t-105.1\n40.2\t-105.2\t3\n";
+        let columns = CsvColumns::new(0, 1, 2)
+            .with_delimiter(b'\t')
+            .without_headers();
+        let import = HotlinePositionVec::from_csv(csv, &columns).unwrap();
+
+        assert_eq!(import.tracks[0].positions.raw_values, vec![1.0, 3.0]);
+        assert_eq!(
+            import.skipped,
+            vec![CsvError::MissingField {
+                line: 2,
+                column: CsvColumn::Index(2),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_missing_column() {
+        let columns = CsvColumns::new("lat", "lon", "pm25").with_delimiter(b';');
+        assert_eq!(
+            HotlinePositionVec::from_csv(LOG, &columns),
+            Err(CsvError::MissingColumn("lon".into()))
+        );
+
+        let columns = CsvColumns::new("lat", 1, 2).without_headers();
+        assert_eq!(
+            HotlinePositionVec::from_csv("40,-105,1", &columns),
+            Err(CsvError::MissingColumn("lat".into()))
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! module for errors reported while creating and updating hotline layers
+use std::fmt;
+
+use super::hotline_color::ColorError;
+use super::hotline_packed::PackedError;
+use super::hotline_palette::PaletteError;
+use super::hotline_position::FlatPosition;
+
+///
+/// error raised while creating or updating a hotline layer, passed to the `on_error`
+/// callback of `HotPolyline`
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum HotlineError {
+    /// the component is not inside a leptos-leaflet `MapContainer`
+    MissingMapContext,
+    /// a palette could not be built or converted
+    InvalidPalette(PaletteError),
+    /// the outline color is not a valid CSS color; black is drawn instead
+    InvalidColor(ColorError),
+    /// a track position has a non-finite latitude or longitude
+    InvalidPosition {
+        /// index of the position in the track
+        index: usize,
+        /// the position given
+        position: FlatPosition,
+    },
+    /// a JS global the hotline layer needs, such as leaflet's `L`, is not defined
+    MissingGlobal(&'static str),
+    /// leaflet or the hotline layer threw a JS exception
+    JsException(String),
+    /// packed positions loaded by a `HotPolylineResource` could not be unpacked
+    InvalidPacked(PackedError),
+    /// the resource of a `HotPolylineResource` resolved to an error, with its message
+    ResourceFailed(String),
+}
+
+impl fmt::Display for HotlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingMapContext => write!(
+                f,
+                "hotline has no map context; place it inside a MapContainer"
+            ),
+            Self::InvalidPalette(err) => write!(f, "invalid hotline palette: {err}"),
+            Self::InvalidColor(err) => write!(f, "invalid hotline outline color: {err}"),
+            Self::InvalidPosition { index, position } => write!(
+                f,
+                "hotline position {index} ({}, {}) is not a finite latitude and longitude",
+                position.lat, position.lng
+            ),
+            Self::MissingGlobal(name) => write!(
+                f,
+                "JS global `{name}` is not defined; load leaflet before adding hotlines"
+            ),
+            Self::JsException(message) => write!(f, "JS exception: {message}"),
+            Self::InvalidPacked(err) => write!(f, "invalid packed hotline positions: {err}"),
+            Self::ResourceFailed(message) => write!(f, "hotline resource failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for HotlineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidPalette(err) => Some(err),
+            Self::InvalidColor(err) => Some(err),
+            Self::InvalidPacked(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<PaletteError> for HotlineError {
+    #[inline]
+    fn from(value: PaletteError) -> Self {
+        Self::InvalidPalette(value)
+    }
+}
+
+impl From<ColorError> for HotlineError {
+    #[inline]
+    fn from(value: ColorError) -> Self {
+        Self::InvalidColor(value)
+    }
+}
+
+impl From<PackedError> for HotlineError {
+    #[inline]
+    fn from(value: PackedError) -> Self {
+        Self::InvalidPacked(value)
+    }
+}
+
+/// wraps a thrown JS value, keeping the message of JS `Error` objects
+#[cfg(feature = "wasm")]
+impl From<wasm_bindgen::JsValue> for HotlineError {
+    fn from(value: wasm_bindgen::JsValue) -> Self {
+        use wasm_bindgen::JsCast;
+
+        let message = match value.dyn_ref::<js_sys::Error>() {
+            Some(error) => String::from(error.message()),
+            None => value.as_string().unwrap_or_else(|| format!("{value:?}")),
+        };
+        Self::JsException(message)
+    }
+}
+
+/// throws a JS `Error` with the message of a [`HotlineError`] from exported functions
+#[cfg(feature = "wasm")]
+impl From<HotlineError> for wasm_bindgen::JsValue {
+    #[inline]
+    fn from(value: HotlineError) -> Self {
+        js_sys::Error::new(&value.to_string()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_messages() {
+        let err = HotlineError::from(ColorError::Empty);
+        assert_eq!(
+            err.to_string(),
+            "invalid hotline outline color: color is empty"
+        );
+        assert!(std::error::Error::source(&err).is_some());
+        assert_eq!(
+            HotlineError::MissingGlobal("L").to_string(),
+            "JS global `L` is not defined; load leaflet before adding hotlines"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! module for hotline event handlers
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use leptos_leaflet::leaflet as L;
+use wasm_bindgen::prelude::*;
+
+use crate::{Color, FlatPosition, Hotline};
+
+///
+/// pointer event on a hotline, enriched with the track point nearest to the pointer
+///
+/// # Fields
+/// * `event` [`L::MouseEvent`] the leaflet event
+/// * `index` [`usize`] index of the nearest track point
+/// * `value` [`f64`] raw (pre-normalization) value of that point
+/// * `color` [`Color`] color the hotline draws at that point
+///
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct HotlineMouseEvent {
+    /// the leaflet mouse event
+    pub event: L::MouseEvent,
+    /// index of the track point nearest to the pointer
+    pub index: usize,
+    /// raw value of the nearest track point
+    pub value: f64,
+    /// color of the hotline at the nearest track point
+    pub color: Color,
+}
+
+/// track point found for a pointer position, used to build [`HotlineMouseEvent`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct HotlineVertex {
+    pub(crate) index: usize,
+    pub(crate) value: f64,
+    pub(crate) color: Color,
+}
+
+type MouseCallback = Rc<dyn Fn(HotlineMouseEvent)>;
+type LayerCallback = Rc<dyn Fn(L::Event)>;
+type MouseListener = (&'static str, Closure<dyn Fn(L::MouseEvent)>);
+type LayerListener = (&'static str, Closure<dyn Fn(L::Event)>);
+
+///
+/// closures attached to a hotline layer by [`HotlineEvents::setup`], kept next to the
+/// layer so they can be detached and dropped when it is removed
+///
+#[derive(Default)]
+pub(crate) struct HotlineListeners {
+    mouse: Vec<MouseListener>,
+    layer: Vec<LayerListener>,
+}
+
+impl HotlineListeners {
+    /// detach the closures from the layer they were attached to; they are dropped with `self`
+    pub(crate) fn detach(self, hotline: &Hotline) {
+        let evented = hotline.unchecked_ref::<L::Evented>();
+        for (kind, closure) in &self.mouse {
+            evented.off(kind, closure.as_ref());
+        }
+        for (kind, closure) in &self.layer {
+            evented.off(kind, closure.as_ref());
+        }
+    }
+}
+
+///
+/// event handlers for a [`crate::HotPolyline`], mirroring `leptos_leaflet::MapEvents`
+///
+/// Unlike the leptos-leaflet event builders, handlers are kept after setup, so they
+/// stay attached when the hotline layer is recreated (e.g. when the map changes).
+///
+/// # Examples
+///
+/// ```ignore
+/// let events = HotlineEvents::new()
+///     .on_click(|event| log!("clicked point {} with value {}", event.index, event.value));
+///
+/// view! { <HotPolyline positions=positions palette=palette events=events/> }
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct HotlineEvents {
+    inner: Rc<RefCell<InnerHotlineEvents>>,
+}
+
+#[derive(Default)]
+struct InnerHotlineEvents {
+    on_click: Option<MouseCallback>,
+    on_mouse_over: Option<MouseCallback>,
+    on_mouse_out: Option<MouseCallback>,
+    on_mouse_move: Option<MouseCallback>,
+    on_context_menu: Option<MouseCallback>,
+    on_add: Option<LayerCallback>,
+    on_remove: Option<LayerCallback>,
+}
+
+/// defines a builder method registering a hotline event handler
+macro_rules! hotline_event {
+    ($e:ident, $t:ty) => {
+        impl HotlineEvents {
+            #[doc = concat!("set the `", stringify!($e), "` handler")]
+            #[must_use]
+            pub fn $e(self, callback: impl Fn($t) + 'static) -> Self {
+                self.inner.borrow_mut().$e = Some(Rc::new(callback));
+                self
+            }
+        }
+    };
+}
+
+hotline_event!(on_click, HotlineMouseEvent);
+hotline_event!(on_mouse_over, HotlineMouseEvent);
+hotline_event!(on_mouse_out, HotlineMouseEvent);
+hotline_event!(on_mouse_move, HotlineMouseEvent);
+hotline_event!(on_context_menu, HotlineMouseEvent);
+hotline_event!(on_add, L::Event);
+hotline_event!(on_remove, L::Event);
+
+impl HotlineEvents {
+    /// construct [`HotlineEvents`] without any handlers
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// attach the handlers to a hotline layer
+    ///
+    /// # Arguments
+    /// * `hotline` - the layer to attach to
+    /// * `lookup` - finds the track point nearest to a pointer position
+    ///
+    /// # Returns
+    /// [`HotlineListeners`] to detach from the layer when it is removed
+    ///
+    pub(crate) fn setup(
+        &self,
+        hotline: &Hotline,
+        lookup: impl Fn(FlatPosition) -> Option<HotlineVertex> + 'static,
+    ) -> HotlineListeners {
+        let inner = self.inner.borrow();
+        let evented = hotline.unchecked_ref::<L::Evented>();
+        let lookup = Rc::new(lookup);
+
+        let enrich = |callback: &MouseCallback| -> Box<dyn Fn(L::MouseEvent)> {
+            let callback = Rc::clone(callback);
+            let lookup = Rc::clone(&lookup);
+            Box::new(move |event: L::MouseEvent| {
+                let lat_lng = event.lat_lng();
+                if let Some(vertex) = lookup(FlatPosition::new(lat_lng.lat(), lat_lng.lng())) {
+                    callback(HotlineMouseEvent {
+                        event,
+                        index: vertex.index,
+                        value: vertex.value,
+                        color: vertex.color,
+                    });
+                }
+            })
+        };
+
+        let mut listeners = HotlineListeners::default();
+        let mouse_events = [
+            ("click", &inner.on_click),
+            ("mouseover", &inner.on_mouse_over),
+            ("mouseout", &inner.on_mouse_out),
+            ("mousemove", &inner.on_mouse_move),
+            ("contextmenu", &inner.on_context_menu),
+        ];
+        for (kind, callback) in mouse_events {
+            if let Some(callback) = callback {
+                let closure = Closure::wrap(enrich(callback));
+                evented.on(kind, closure.as_ref());
+                listeners.mouse.push((kind, closure));
+            }
+        }
+        for (kind, callback) in [("add", &inner.on_add), ("remove", &inner.on_remove)] {
+            if let Some(callback) = callback {
+                let callback = Rc::clone(callback);
+                let closure = Closure::<dyn Fn(L::Event)>::new(move |event| callback(event));
+                evented.on(kind, closure.as_ref());
+                listeners.layer.push((kind, closure));
+            }
+        }
+        listeners
+    }
+}
@@ -0,0 +1,937 @@
+//! module for exporting hotline tracks as SVG and PNG images
+use std::fmt::{self, Write as _};
+
+use super::hotline_color::Color;
+use super::hotline_palette::HotlinePalette;
+use super::hotline_position::{FlatPosition, HotlinePositionVec};
+use super::hotline_raster::{HotlineRaster, MercatorBounds};
+use super::hotline_style::HotlineStyle;
+use super::hotline_track::HotlineTrack;
+
+/// CSS pixels per inch, the DPI of an export at scale 1
+pub const CSS_DPI: f64 = 96.0;
+
+/// color of the legend ticks and labels, as in `HotlineLegend`
+const LEGEND_TEXT: Color = Color::rgb(0x33, 0x33, 0x33);
+
+/// space above the legend bar, in CSS pixels
+const LEGEND_GAP: f64 = 6.0;
+
+/// height of the legend gradient bar, in CSS pixels
+const LEGEND_BAR: f64 = 8.0;
+
+/// length of the legend ticks below the bar, in CSS pixels
+const LEGEND_TICK: f64 = 3.0;
+
+/// height of the legend labels, in CSS pixels
+const LEGEND_FONT: f64 = 10.0;
+
+/// height of the legend unit line, in CSS pixels
+const LEGEND_UNIT_LINE: f64 = 14.0;
+
+///
+/// error returned when a track cannot be exported
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ExportError {
+    /// the track has no positions
+    EmptyTrack,
+    /// the image is too small for its padding and legend
+    NoRoom,
+    /// the image could not be encoded
+    Encoding(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyTrack => write!(f, "cannot export a track without positions"),
+            Self::NoRoom => write!(f, "image is too small for its padding and legend"),
+            Self::Encoding(message) => write!(f, "failed to encode image: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+///
+/// legend drawn below an exported track: a gradient bar with a value label at each
+/// palette breakpoint, like the `HotlineLegend` component
+///
+/// # Fields
+/// * `min`, `max` [`f64`] values labelled at breakpoints 0 and 1
+/// * `unit` [`String`] unit shown below the labels, e.g. `"km/h"`; raster and PNG legends
+///   draw letters as capitals and leave characters outside `a-z 0-9 . - / % °` blank
+/// * `decimals` [`Option<usize>`] decimals in the labels; trailing zeros are trimmed if unset
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ExportLegend {
+    /// value labelled at breakpoint 0
+    pub min: f64,
+    /// value labelled at breakpoint 1
+    pub max: f64,
+    /// unit shown below the labels
+    pub unit: String,
+    /// decimals in the labels
+    pub decimals: Option<usize>,
+}
+
+/// implement constructors for [`ExportLegend`]
+impl ExportLegend {
+    ///
+    /// construct a new [`ExportLegend`] labelling breakpoints linearly from `min` to `max`
+    ///
+    /// # Returns
+    /// [`ExportLegend`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn new(min: f64, max: f64) -> Self {
+        Self {
+            min,
+            max,
+            unit: String::new(),
+            decimals: None,
+        }
+    }
+
+    ///
+    /// construct a new [`ExportLegend`] labelling the raw values of `positions` that
+    /// their normalization maps to 0 and 1, the default `min` and `max` of
+    /// [`HotlineStyle`], or `0..=1` if there are no positions
+    ///
+    /// With another `min` / `max`, build the legend with [`ExportLegend::new`] from
+    /// the raw values they stand for.
+    ///
+    /// # Returns
+    /// [`ExportLegend`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn for_positions(positions: &HotlinePositionVec) -> Self {
+        if positions.raw_values.is_empty() {
+            return Self::new(0.0, 1.0);
+        }
+        let raw_value = |normalized| {
+            positions
+                .normalization
+                .invert(&positions.raw_values, normalized)
+        };
+        Self::new(raw_value(0.0), raw_value(1.0))
+    }
+
+    /// set the unit shown below the labels
+    #[must_use]
+    #[inline]
+    pub fn with_unit(self, unit: impl Into<String>) -> Self {
+        Self {
+            unit: unit.into(),
+            ..self
+        }
+    }
+
+    /// set the number of decimals in the labels
+    #[must_use]
+    #[inline]
+    pub fn with_decimals(self, decimals: usize) -> Self {
+        Self {
+            decimals: Some(decimals),
+            ..self
+        }
+    }
+
+    /// breakpoints of the legend and their labels
+    fn ticks(&self, palette: &HotlinePalette) -> Vec<(f64, String)> {
+        palette
+            .stops()
+            .iter()
+            .map(|stop| {
+                let value = self.min + stop.breakpoint * (self.max - self.min);
+                (stop.breakpoint, format_tick(value, self.decimals))
+            })
+            .collect()
+    }
+}
+
+///
+/// size and content of an exported track image, independent of any map view
+///
+/// The track is framed with [`MercatorBounds::fit`] inside the padding, above the
+/// legend if there is one.  Sizes are in CSS pixels; PNG exports are rendered at
+/// `scale` device pixels per CSS pixel, while SVG exports are vector images of the
+/// CSS pixel size.
+///
+/// # Fields
+/// * `width`, `height` [`u32`] image size in CSS pixels, including the legend
+/// * `scale` [`f64`] device pixels per CSS pixel of PNG exports, e.g. 2 for 192 DPI
+/// * `padding` [`f64`] free space around the track in CSS pixels
+/// * `style` [`HotlineStyle`] palette, value range, line and outline widths
+/// * `background` [`Option<Color>`] background color, transparent if `None`
+/// * `legend` [`Option<ExportLegend>`] legend drawn below the track
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ExportOptions {
+    /// image width in CSS pixels
+    pub width: u32,
+    /// image height in CSS pixels, including the legend
+    pub height: u32,
+    /// device pixels per CSS pixel of PNG exports
+    pub scale: f64,
+    /// free space around the track in CSS pixels
+    pub padding: f64,
+    /// how the track is drawn
+    pub style: HotlineStyle,
+    /// background color, transparent if `None`
+    pub background: Option<Color>,
+    /// legend drawn below the track
+    pub legend: Option<ExportLegend>,
+}
+
+/// implement builder methods for [`ExportOptions`]
+impl ExportOptions {
+    ///
+    /// construct new [`ExportOptions`] for a `width` x `height` CSS pixel image with a
+    /// 16 pixel padding, the default [`HotlineStyle`], no background and no legend
+    ///
+    /// # Returns
+    /// [`ExportOptions`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            scale: 1.0,
+            padding: 16.0,
+            style: HotlineStyle::default(),
+            background: None,
+            legend: None,
+        }
+    }
+
+    /// set how the track is drawn
+    #[must_use]
+    #[inline]
+    pub fn with_style(self, style: HotlineStyle) -> Self {
+        Self { style, ..self }
+    }
+
+    /// set the device pixels per CSS pixel of PNG exports
+    #[must_use]
+    #[inline]
+    pub fn with_scale(self, scale: f64) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// set the resolution of PNG exports in dots per inch; 96 DPI is scale 1
+    #[must_use]
+    #[inline]
+    pub fn with_dpi(self, dpi: f64) -> Self {
+        self.with_scale(dpi / CSS_DPI)
+    }
+
+    /// set the free space around the track in CSS pixels
+    #[must_use]
+    #[inline]
+    pub fn with_padding(self, padding: f64) -> Self {
+        Self { padding, ..self }
+    }
+
+    /// set the background color
+    #[must_use]
+    #[inline]
+    pub fn with_background(self, background: Color) -> Self {
+        Self {
+            background: Some(background),
+            ..self
+        }
+    }
+
+    /// draw a legend below the track
+    #[must_use]
+    #[inline]
+    pub fn with_legend(self, legend: ExportLegend) -> Self {
+        Self {
+            legend: Some(legend),
+            ..self
+        }
+    }
+
+    /// the palette drawn; an empty palette is drawn as the default one
+    fn palette(&self) -> HotlinePalette {
+        if self.style.palette.is_empty() {
+            HotlinePalette::default()
+        } else {
+            self.style.palette.clone()
+        }
+    }
+
+    /// height of the legend in CSS pixels, 0 without one
+    fn legend_height(&self) -> f64 {
+        match &self.legend {
+            None => 0.0,
+            Some(legend) => {
+                let unit = if legend.unit.is_empty() {
+                    0.0
+                } else {
+                    LEGEND_UNIT_LINE
+                };
+                LEGEND_GAP + LEGEND_BAR + LEGEND_TICK + LEGEND_FONT + LEGEND_GAP + unit
+            }
+        }
+    }
+
+    /// bounds framing the track in the area above the legend, `map_height` high, at
+    /// `scale` pixels per CSS pixel
+    fn bounds(
+        &self,
+        positions: &[FlatPosition],
+        map_height: f64,
+        scale: f64,
+    ) -> Result<MercatorBounds, ExportError> {
+        if positions.is_empty() {
+            return Err(ExportError::EmptyTrack);
+        }
+        let (width, height) = (
+            (f64::from(self.width) * scale).round() as u32,
+            (map_height * scale).round() as u32,
+        );
+        MercatorBounds::fit(positions, width, height, self.padding * scale)
+            .ok_or(ExportError::NoRoom)
+    }
+
+    ///
+    /// render a track at `scale` device pixels per CSS pixel, with its legend
+    ///
+    /// # Returns
+    /// [`HotlineRaster`], or [`ExportError`] if the track is empty or the image too small
+    ///
+    pub fn to_raster(&self, track: &HotlineTrack) -> Result<HotlineRaster, ExportError> {
+        let scale = self.scale;
+        let map_height = f64::from(self.height) - self.legend_height();
+        let bounds = self.bounds(track.positions(), map_height, scale)?;
+
+        let background = self.background.unwrap_or(Color::rgba(0, 0, 0, 0));
+        let width = (f64::from(self.width) * scale).round() as u32;
+        let height = (f64::from(self.height) * scale).round() as u32;
+        let map_rows = ((map_height * scale).round() as u32).min(height);
+
+        let style = HotlineStyle {
+            palette: self.palette(),
+            weight: self.style.weight * scale,
+            outline_width: self.style.outline_width * scale,
+            ..self.style.clone()
+        };
+        let mut map = HotlineRaster::filled(width, map_rows, background);
+        map.draw(track.iter(), &bounds, &style);
+
+        let mut legend = HotlineRaster::filled(width, height - map_rows, background);
+        if let Some(options) = &self.legend {
+            draw_legend(
+                &mut legend,
+                options,
+                &style.palette,
+                self.padding * scale,
+                scale,
+            );
+        }
+
+        let pixels = [map.into_pixels(), legend.into_pixels()].concat();
+        HotlineRaster::from_pixels(width, height, pixels).ok_or(ExportError::NoRoom)
+    }
+
+    ///
+    /// render a track as an SVG document, each segment stroked with its own
+    /// `<linearGradient>`
+    ///
+    /// # Returns
+    /// [`String`], or [`ExportError`] if the track is empty or the image too small
+    ///
+    pub fn to_svg(&self, track: &HotlineTrack) -> Result<String, ExportError> {
+        let (width, height) = (self.width, self.height);
+        let map_height = f64::from(height) - self.legend_height();
+        let bounds = self.bounds(track.positions(), map_height, 1.0)?;
+        let map_rows = map_height.round() as u32;
+        let palette = self.palette();
+        let style = &self.style;
+
+        let points: Vec<(f64, f64, f64)> = track
+            .iter()
+            .map(|(position, value)| {
+                let (x, y) = bounds.to_pixel(&position, width, map_rows);
+                (x, y, value)
+            })
+            .filter(|(x, y, _)| x.is_finite() && y.is_finite())
+            .collect();
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        );
+        if let Some(background) = self.background {
+            let _ = write!(
+                svg,
+                r#"<rect width="100%" height="100%" {}/>"#,
+                paint("fill", background)
+            );
+        }
+
+        svg.push_str("<defs>");
+        for (index, segment) in points.windows(2).enumerate() {
+            let ((x0, y0, from), (x1, y1, to)) = (segment[0], segment[1]);
+            let _ = write!(
+                svg,
+                r#"<linearGradient id="hotline-segment-{index}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}"><stop offset="0" {}/><stop offset="1" {}/></linearGradient>"#,
+                coord(x0),
+                coord(y0),
+                coord(x1),
+                coord(y1),
+                paint("stop-color", style.color_for_value(from)),
+                paint("stop-color", style.color_for_value(to)),
+            );
+        }
+        if self.legend.is_some() {
+            svg.push_str(r#"<linearGradient id="hotline-legend">"#);
+            for stop in palette.stops() {
+                let _ = write!(
+                    svg,
+                    r#"<stop offset="{}" {}/>"#,
+                    stop.breakpoint,
+                    paint("stop-color", stop.color)
+                );
+            }
+            svg.push_str("</linearGradient>");
+        }
+        svg.push_str("</defs>");
+
+        if style.outline_width > 0.0 && points.len() > 1 {
+            let path: Vec<String> = points
+                .iter()
+                .map(|(x, y, _)| format!("{},{}", coord(*x), coord(*y)))
+                .collect();
+            let _ = write!(
+                svg,
+                r#"<polyline points="{}" fill="none" {} stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                path.join(" "),
+                paint("stroke", style.outline_color),
+                style.outer_width()
+            );
+        }
+
+        let _ = write!(
+            svg,
+            r#"<g fill="none" stroke-width="{}" stroke-linecap="round">"#,
+            style.weight
+        );
+        for (index, segment) in points.windows(2).enumerate() {
+            let ((x0, y0, _), (x1, y1, _)) = (segment[0], segment[1]);
+            let _ = write!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="url(#hotline-segment-{index})"/>"#,
+                coord(x0),
+                coord(y0),
+                coord(x1),
+                coord(y1)
+            );
+        }
+        svg.push_str("</g>");
+
+        if let Some(legend) = &self.legend {
+            let (left, right) = legend_span(f64::from(width), self.padding);
+            let bar_top = f64::from(map_rows) + LEGEND_GAP;
+            let tick_bottom = bar_top + LEGEND_BAR + LEGEND_TICK;
+            let _ = write!(
+                svg,
+                r#"<g font-family="sans-serif" font-size="{LEGEND_FONT}" {}><rect x="{}" y="{}" width="{}" height="{LEGEND_BAR}" fill="url(#hotline-legend)"/>"#,
+                paint("fill", LEGEND_TEXT),
+                coord(left),
+                coord(bar_top),
+                coord(right - left),
+            );
+            for (breakpoint, label) in legend.ticks(&palette) {
+                let x = left + breakpoint * (right - left);
+                let anchor = match breakpoint {
+                    b if b <= 0.0 => "start",
+                    b if b >= 1.0 => "end",
+                    _ => "middle",
+                };
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="1" height="{LEGEND_TICK}"/><text x="{}" y="{}" text-anchor="{anchor}">{}</text>"#,
+                    coord(x - 0.5),
+                    coord(tick_bottom - LEGEND_TICK),
+                    coord(x),
+                    coord(tick_bottom + LEGEND_FONT),
+                    escape(&label)
+                );
+            }
+            if !legend.unit.is_empty() {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+                    coord(right),
+                    coord(tick_bottom + LEGEND_FONT + LEGEND_UNIT_LINE),
+                    escape(&legend.unit)
+                );
+            }
+            svg.push_str("</g>");
+        }
+
+        svg.push_str("</svg>");
+        Ok(svg)
+    }
+
+    ///
+    /// render a track as a PNG image at `scale` device pixels per CSS pixel, recording
+    /// the matching DPI
+    ///
+    /// # Returns
+    /// PNG bytes, or [`ExportError`] if the track is empty or the image too small
+    ///
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, track: &HotlineTrack) -> Result<Vec<u8>, ExportError> {
+        self.to_raster(track)?.to_png(self.scale * CSS_DPI)
+    }
+}
+
+/// implement image export for [`HotlineTrack`]
+impl HotlineTrack {
+    ///
+    /// export the track as an SVG document
+    ///
+    /// # Returns
+    /// [`String`], or [`ExportError`] if the track is empty or the image too small
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{ExportLegend, ExportOptions, HotlinePositionVec, HotlineTrack};
+    ///
+    /// let positions = HotlinePositionVec::new(&[(40.0, -105.0, 10.0), (40.01, -105.01, 20.0)]);
+    /// let options = ExportOptions::new(320, 240).with_legend(ExportLegend::for_positions(&positions).with_unit("km/h"));
+    /// let svg = HotlineTrack::from(positions).to_svg(&options).unwrap();
+    /// assert!(svg.contains("<linearGradient id=\"hotline-segment-0\""));
+    /// // values are divided by their maximum, so breakpoint 0 stands for 0, not 10
+    /// assert!(svg.contains(r#"text-anchor="start">0</text>"#));
+    /// assert!(svg.contains(r#"text-anchor="end">20</text>"#));
+    /// assert!(svg.contains(">km/h</text>"));
+    /// ```
+    ///
+    #[inline]
+    pub fn to_svg(&self, options: &ExportOptions) -> Result<String, ExportError> {
+        options.to_svg(self)
+    }
+
+    ///
+    /// export the track as a PNG image
+    ///
+    /// # Returns
+    /// PNG bytes, or [`ExportError`] if the track is empty or the image too small
+    ///
+    #[cfg(feature = "png")]
+    #[inline]
+    pub fn to_png(&self, options: &ExportOptions) -> Result<Vec<u8>, ExportError> {
+        options.to_png(self)
+    }
+}
+
+/// implement image export for [`HotlinePositionVec`]
+impl HotlinePositionVec {
+    ///
+    /// export the positions as an SVG document, drawing the normalized values
+    ///
+    /// # Returns
+    /// [`String`], or [`ExportError`] if there are no positions or the image is too small
+    ///
+    #[inline]
+    pub fn to_svg(&self, options: &ExportOptions) -> Result<String, ExportError> {
+        options.to_svg(&self.into())
+    }
+
+    ///
+    /// export the positions as a PNG image, drawing the normalized values
+    ///
+    /// # Returns
+    /// PNG bytes, or [`ExportError`] if there are no positions or the image is too small
+    ///
+    #[cfg(feature = "png")]
+    #[inline]
+    pub fn to_png(&self, options: &ExportOptions) -> Result<Vec<u8>, ExportError> {
+        options.to_png(&self.into())
+    }
+}
+
+/// implement canvas capture for [`HotlineRaster`]
+#[cfg(feature = "wasm")]
+impl HotlineRaster {
+    ///
+    /// copy the pixels of a canvas `ImageData`, e.g. from `getImageData` on the canvas
+    /// of a [`crate::HotlineRenderer`], to export the hotline as drawn on the map
+    ///
+    /// # Returns
+    /// [`Option<HotlineRaster>`], `None` if the data does not match its size
+    ///
+    #[must_use]
+    pub fn from_image_data(image: &web_sys::ImageData) -> Option<Self> {
+        Self::from_pixels(image.width(), image.height(), image.data().0)
+    }
+}
+
+/// implement PNG encoding for [`HotlineRaster`]
+#[cfg(feature = "png")]
+impl HotlineRaster {
+    ///
+    /// encode the raster as an RGBA PNG image, recording `dpi` as its resolution
+    ///
+    /// Also works for canvas pixels wrapped with [`HotlineRaster::from_pixels`], such
+    /// as the `ImageData` of the map canvas.
+    ///
+    /// # Returns
+    /// PNG bytes, or [`ExportError::Encoding`]
+    ///
+    pub fn to_png(&self, dpi: f64) -> Result<Vec<u8>, ExportError> {
+        let encoding = |err: png::EncodingError| ExportError::Encoding(err.to_string());
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width(), self.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let per_meter = (dpi / 0.0254).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: per_meter,
+            yppu: per_meter,
+            unit: png::Unit::Meter,
+        }));
+        let mut writer = encoder.write_header().map_err(encoding)?;
+        writer.write_image_data(self.pixels()).map_err(encoding)?;
+        writer.finish().map_err(encoding)?;
+        Ok(bytes)
+    }
+}
+
+/// format a tick label with the given decimals, or up to 2 decimals with trailing zeros trimmed
+pub(crate) fn format_tick(value: f64, decimals: Option<usize>) -> String {
+    match decimals {
+        Some(decimals) => format!("{value:.decimals$}"),
+        None => {
+            let label = format!("{value:.2}");
+            let label = label.trim_end_matches('0').trim_end_matches('.');
+            match label {
+                "-0" => "0".to_string(),
+                _ => label.to_string(),
+            }
+        }
+    }
+}
+
+/// horizontal extent of the legend bar, inside the padding
+fn legend_span(width: f64, padding: f64) -> (f64, f64) {
+    let inset = padding.min(width / 4.0).max(0.0);
+    (inset, width - inset)
+}
+
+/// draw the legend bar, ticks and labels into the rows below the track
+fn draw_legend(
+    raster: &mut HotlineRaster,
+    legend: &ExportLegend,
+    palette: &HotlinePalette,
+    padding: f64,
+    scale: f64,
+) {
+    let (left, right) = legend_span(f64::from(raster.width()), padding);
+    let bar_top = LEGEND_GAP * scale;
+    let bar_bottom = bar_top + LEGEND_BAR * scale;
+    let tick_bottom = bar_bottom + LEGEND_TICK * scale;
+
+    for x in (left.round() as u32)..(right.round() as u32) {
+        let color = palette.color_at((f64::from(x) + 0.5 - left) / (right - left));
+        fill_rect(
+            raster,
+            f64::from(x),
+            bar_top,
+            1.0,
+            bar_bottom - bar_top,
+            color,
+        );
+    }
+
+    // the digit font is 5 dots high, scaled to the label height
+    let dot = (LEGEND_FONT * scale / 5.0).floor().max(1.0);
+    for (breakpoint, label) in legend.ticks(palette) {
+        let x = left + breakpoint * (right - left);
+        let tick_width = scale.round().max(1.0);
+        fill_rect(
+            raster,
+            (x - tick_width / 2.0).round(),
+            bar_bottom,
+            tick_width,
+            tick_bottom - bar_bottom,
+            LEGEND_TEXT,
+        );
+
+        let text_width = label.chars().count() as f64 * 4.0 * dot - dot;
+        let text_left = match breakpoint {
+            b if b <= 0.0 => x,
+            b if b >= 1.0 => x - text_width,
+            _ => x - text_width / 2.0,
+        }
+        .clamp(0.0, (f64::from(raster.width()) - text_width).max(0.0))
+        .round();
+        draw_text(raster, &label, text_left, tick_bottom.round(), dot);
+    }
+
+    if !legend.unit.is_empty() {
+        let text_width = legend.unit.chars().count() as f64 * 4.0 * dot - dot;
+        let text_left = (right - text_width).max(0.0).round();
+        let top = (tick_bottom + LEGEND_UNIT_LINE * scale).round();
+        draw_text(raster, &legend.unit, text_left, top, dot);
+    }
+}
+
+/// draw text with a 3 x 5 dot font, letters as capitals; characters without a glyph
+/// are left blank
+fn draw_text(raster: &mut HotlineRaster, text: &str, left: f64, top: f64, dot: f64) {
+    for (index, character) in text.chars().enumerate() {
+        let Some(rows) = glyph(character) else {
+            continue;
+        };
+        let glyph_left = left + index as f64 * 4.0 * dot;
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    fill_rect(
+                        raster,
+                        glyph_left + f64::from(column) * dot,
+                        top + row as f64 * dot,
+                        dot,
+                        dot,
+                        LEGEND_TEXT,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// rows of a 3 x 5 dot glyph, most significant bit on the left
+fn glyph(character: char) -> Option<[u8; 5]> {
+    Some(match character.to_ascii_lowercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '°' => [0b010, 0b101, 0b010, 0b000, 0b000],
+        'a' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'b' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'c' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'd' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'e' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'f' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'g' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'h' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'i' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'j' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'k' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'l' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'm' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'n' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'o' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'p' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'r' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        's' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        't' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'u' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'v' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'w' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'x' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        _ => return None,
+    })
+}
+
+/// paint a pixel-aligned rectangle with an opaque color, clipped to the raster
+fn fill_rect(raster: &mut HotlineRaster, x: f64, y: f64, width: f64, height: f64, color: Color) {
+    let (left, top) = (x.max(0.0) as u32, y.max(0.0) as u32);
+    let right = ((x + width).max(0.0) as u32).min(raster.width());
+    let bottom = ((y + height).max(0.0) as u32).min(raster.height());
+    for row in top..bottom {
+        for column in left..right {
+            raster.set_pixel(column, row, color);
+        }
+    }
+}
+
+/// an SVG paint attribute, with a separate opacity for translucent colors
+fn paint(attribute: &str, color: Color) -> String {
+    let hex = format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b);
+    let opacity = if attribute == "stop-color" {
+        "stop-opacity"
+    } else if attribute == "fill" {
+        "fill-opacity"
+    } else {
+        "stroke-opacity"
+    };
+    match color.a {
+        255 => format!(r#"{attribute}="{hex}""#),
+        alpha => format!(
+            r#"{attribute}="{hex}" {opacity}="{}""#,
+            coord(f64::from(alpha) / 255.0)
+        ),
+    }
+}
+
+/// an SVG coordinate rounded to two decimals, without trailing zeros
+fn coord(value: f64) -> String {
+    format_tick(value, None)
+}
+
+/// escape text for SVG element content
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Normalization;
+
+    fn track() -> HotlineTrack {
+        HotlineTrack::new(
+            vec![
+                FlatPosition::new(40.000, -105.000),
+                FlatPosition::new(40.004, -104.994),
+                FlatPosition::new(40.001, -104.988),
+            ],
+            vec![0.0, 0.5, 1.0],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_format_tick() {
+        assert_eq!(format_tick(12.5, None), "12.5");
+        assert_eq!(format_tick(40.0, None), "40");
+        assert_eq!(format_tick(-0.001, None), "0");
+        assert_eq!(format_tick(12.3456, Some(3)), "12.346");
+    }
+
+    #[test]
+    fn test_svg_export() {
+        let options = ExportOptions::new(200, 120)
+            .with_style(HotlineStyle::default().with_outline(Color::rgba(0, 0, 0, 128), 2.0))
+            .with_background(Color::WHITE)
+            .with_legend(ExportLegend::new(10.0, 30.0).with_unit("<m>"));
+        let svg = track().to_svg(&options).unwrap();
+
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="120""#)
+        );
+        assert_eq!(
+            svg.matches("<linearGradient id=\"hotline-segment-").count(),
+            2
+        );
+        assert_eq!(svg.matches("<line ").count(), 2);
+        assert!(
+            svg.contains(r##"stroke="#000000" stroke-opacity="0.5""##),
+            "{svg}"
+        );
+        assert!(svg.contains(r#"stroke-width="9""#));
+        assert!(svg.contains(r#"text-anchor="start">10</text>"#));
+        assert!(svg.contains(r#"text-anchor="end">30</text>"#));
+        assert!(svg.contains(">&lt;m&gt;</text>"));
+        assert!(svg.ends_with("</svg>"));
+
+        let empty = HotlineTrack::default();
+        assert_eq!(empty.to_svg(&options), Err(ExportError::EmptyTrack));
+        let cramped = ExportOptions::new(40, 40).with_legend(ExportLegend::new(0.0, 1.0));
+        assert_eq!(track().to_svg(&cramped), Err(ExportError::NoRoom));
+    }
+
+    #[test]
+    fn test_legend_for_positions() {
+        let points = [(40.0, -105.0, 10.0), (40.1, -105.1, 20.0)];
+        let divided = HotlinePositionVec::new(&points);
+        assert_eq!(
+            ExportLegend::for_positions(&divided),
+            ExportLegend::new(0.0, 20.0)
+        );
+        let min_max = HotlinePositionVec::with_normalization(&points, Normalization::MinMax);
+        assert_eq!(
+            ExportLegend::for_positions(&min_max),
+            ExportLegend::new(10.0, 20.0)
+        );
+        assert_eq!(
+            ExportLegend::for_positions(&HotlinePositionVec::new(&[])),
+            ExportLegend::new(0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_raster_export_scale_and_legend() {
+        let options = ExportOptions::new(100, 80)
+            .with_dpi(192.0)
+            .with_padding(10.0)
+            .with_background(Color::WHITE)
+            .with_legend(ExportLegend::new(0.0, 100.0));
+        let raster = options.to_raster(&track()).unwrap();
+        assert_eq!((raster.width(), raster.height()), (200, 160));
+
+        let legend_top = (160.0 - options.legend_height() * 2.0) as u32;
+        let bar_row = legend_top + (LEGEND_GAP * 2.0) as u32 + 2;
+        let palette = options.palette();
+        assert_eq!(
+            raster.pixel(20, bar_row),
+            Some(palette.color_at(0.5 / 160.0))
+        );
+        assert_eq!(
+            raster.pixel(179, bar_row),
+            Some(palette.color_at(159.5 / 160.0))
+        );
+        assert_eq!(raster.pixel(5, bar_row), Some(Color::WHITE));
+
+        let label_rows = legend_top + ((LEGEND_GAP + LEGEND_BAR + LEGEND_TICK) * 2.0) as u32;
+        let dark = (label_rows..raster.height())
+            .filter(|row| raster.pixel(21, *row) == Some(LEGEND_TEXT))
+            .count();
+        assert!(dark > 0, "expected the first label below the bar");
+
+        let with_unit = options.with_legend(ExportLegend::new(0.0, 100.0).with_unit("km/h"));
+        let raster = with_unit.to_raster(&track()).unwrap();
+        let unit_top = (160.0 - with_unit.legend_height() * 2.0) as u32
+            + ((LEGEND_GAP + LEGEND_BAR + LEGEND_TICK + LEGEND_UNIT_LINE) * 2.0) as u32;
+        let unit_dots = (unit_top..raster.height())
+            .flat_map(|row| (100..180).map(move |column| (column, row)))
+            .filter(|&(column, row)| raster.pixel(column, row) == Some(LEGEND_TEXT))
+            .count();
+        assert!(unit_dots > 0, "expected the unit below the labels");
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png_export() {
+        let options = ExportOptions::new(64, 48).with_scale(1.5);
+        let png = track().to_png(&options).unwrap();
+        let decoder = png::Decoder::new(std::io::Cursor::new(png));
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (96, 72));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(info.pixel_dims.map(|dims| dims.xppu), Some(5669));
+    }
+}
@@ -0,0 +1,488 @@
+//! module for importing Garmin FIT activity files as hotline tracks
+use std::fmt;
+
+use super::hotline_activity::{activity_tracks, ActivityMetric, ActivityRecord};
+use super::hotline_normalization::Normalization;
+use super::hotline_position::FlatPosition;
+use super::hotline_track::HotlineTrack;
+
+/// global message number of `record` messages
+const RECORD_MESSAGE: u16 = 20;
+/// global message number of `session` messages, which close a track
+const SESSION_MESSAGE: u16 = 18;
+/// seconds from the Unix epoch to the FIT epoch, 1989-12-31T00:00:00Z
+const FIT_EPOCH: f64 = 631_065_600.0;
+/// degrees per semicircle, the FIT unit for latitude and longitude
+const SEMICIRCLE: f64 = 180.0 / 2_147_483_648.0;
+
+///
+/// error returned when a FIT file cannot be imported
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum FitError {
+    /// the file header is missing, too short or lacks the `.FIT` signature
+    InvalidHeader,
+    /// the file ends in the middle of a record
+    Truncated {
+        /// byte offset of the incomplete record
+        offset: usize,
+    },
+    /// a data message uses a local message type with no preceding definition
+    UndefinedMessage {
+        /// byte offset of the data message
+        offset: usize,
+        /// local message type of the data message
+        local_type: u8,
+    },
+    /// no record of a session carries the data needed for the metric
+    MissingMetric(ActivityMetric),
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid FIT file header"),
+            Self::Truncated { offset } => {
+                write!(f, "FIT file ends inside the record at byte {offset}")
+            }
+            Self::UndefinedMessage { offset, local_type } => write!(
+                f,
+                "FIT data message at byte {offset} uses undefined local type {local_type}"
+            ),
+            Self::MissingMetric(metric) => {
+                write!(f, "FIT session has no data for metric {metric:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FitError {}
+
+/// field of a definition message: field number, size in bytes and base type
+#[derive(Debug, Clone, Copy)]
+struct FitField {
+    number: u8,
+    size: usize,
+    base_type: u8,
+}
+
+/// layout of the data messages of one local message type
+#[derive(Debug, Clone)]
+struct FitDefinition {
+    global: u16,
+    big_endian: bool,
+    fields: Vec<FitField>,
+    /// total size of the developer fields, which are skipped
+    developer_size: usize,
+}
+
+/// implement FIT import for [`HotlineTrack`]
+impl HotlineTrack {
+    ///
+    /// import the positioned `record` messages of a FIT activity file
+    ///
+    /// Each session becomes one [`HotlineTrack`], with the record times kept as
+    /// timestamps.  Records without a position are skipped, records missing the
+    /// metric take the value of the nearest earlier record (or the first later one),
+    /// and values are normalized as in [`crate::HotlinePositionVec::new`], over the
+    /// whole activity so that sessions share one color scale; see
+    /// [`HotlineTrack::from_fit_with_normalization`] to pick another [`Normalization`].
+    /// Chained FIT files are read in order; CRCs are not checked.
+    ///
+    /// # Returns
+    /// [`Vec<HotlineTrack>`], or [`FitError`] if the file is malformed or a session
+    /// has no data for `metric`
+    ///
+    #[inline]
+    pub fn from_fit(bytes: &[u8], metric: ActivityMetric) -> Result<Vec<Self>, FitError> {
+        Self::from_fit_with_normalization(bytes, metric, Normalization::default())
+    }
+
+    ///
+    /// import the positioned `record` messages of a FIT activity file, normalizing the
+    /// values of the whole activity with the given strategy
+    ///
+    /// # Returns
+    /// [`Vec<HotlineTrack>`], or [`FitError`] as for [`HotlineTrack::from_fit`]
+    ///
+    pub fn from_fit_with_normalization(
+        bytes: &[u8],
+        metric: ActivityMetric,
+        normalization: Normalization,
+    ) -> Result<Vec<Self>, FitError> {
+        activity_tracks(&parse_sessions(bytes)?, metric, normalization)
+            .ok_or(FitError::MissingMetric(metric))
+    }
+}
+
+/// read the positioned records of each session, in file order
+fn parse_sessions(bytes: &[u8]) -> Result<Vec<Vec<ActivityRecord>>, FitError> {
+    let mut sessions = Vec::new();
+    let mut records = Vec::new();
+    let mut start = 0;
+
+    while start < bytes.len() {
+        let header_size = usize::from(*bytes.get(start).ok_or(FitError::InvalidHeader)?);
+        let header = bytes
+            .get(start..start + header_size.max(12))
+            .filter(|header| header_size >= 12 && &header[8..12] == b".FIT")
+            .ok_or(FitError::InvalidHeader)?;
+        let data_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let data_start = start + header_size;
+        let data_end = data_start
+            .checked_add(data_size)
+            .filter(|&data_end| data_end <= bytes.len())
+            .ok_or(FitError::Truncated { offset: data_start })?;
+
+        parse_messages(
+            bytes,
+            data_start,
+            data_end,
+            &mut |global, fields, big_endian| match global {
+                RECORD_MESSAGE => records.extend(read_record(fields, big_endian)),
+                SESSION_MESSAGE if !records.is_empty() => {
+                    sessions.push(std::mem::take(&mut records));
+                }
+                _ => {}
+            },
+        )?;
+        // skip the two CRC bytes that follow the data
+        start = data_end + 2;
+    }
+
+    if !records.is_empty() {
+        sessions.push(records);
+    }
+    Ok(sessions)
+}
+
+///
+/// walk the messages between `start` and `end`, calling `on_message` with the global
+/// message number, (field number, base type, bytes) of each field and the byte order
+///
+/// Compressed timestamp headers are expanded into a timestamp field (253).
+///
+fn parse_messages(
+    bytes: &[u8],
+    start: usize,
+    end: usize,
+    on_message: &mut impl FnMut(u16, &[(u8, u8, Vec<u8>)], bool),
+) -> Result<(), FitError> {
+    let mut definitions: [Option<FitDefinition>; 16] = Default::default();
+    let mut last_timestamp = 0_u32;
+    let mut offset = start;
+
+    while offset < end {
+        let header = bytes[offset];
+        let truncated = FitError::Truncated { offset };
+        let mut cursor = offset + 1;
+
+        if header & 0x80 == 0 && header & 0x40 != 0 {
+            // definition message
+            let fixed = bytes.get(cursor..cursor + 5).ok_or(truncated.clone())?;
+            let big_endian = fixed[1] == 1;
+            let global = if big_endian {
+                u16::from_be_bytes([fixed[2], fixed[3]])
+            } else {
+                u16::from_le_bytes([fixed[2], fixed[3]])
+            };
+            let field_count = usize::from(fixed[4]);
+            cursor += 5;
+            let fields = bytes
+                .get(cursor..cursor + field_count * 3)
+                .ok_or(truncated.clone())?
+                .chunks(3)
+                .map(|field| FitField {
+                    number: field[0],
+                    size: usize::from(field[1]),
+                    base_type: field[2],
+                })
+                .collect();
+            cursor += field_count * 3;
+
+            let mut developer_size = 0;
+            if header & 0x20 != 0 {
+                let count = usize::from(*bytes.get(cursor).ok_or(truncated.clone())?);
+                let developer = bytes
+                    .get(cursor + 1..cursor + 1 + count * 3)
+                    .ok_or(truncated.clone())?;
+                developer_size = developer.chunks(3).map(|field| usize::from(field[1])).sum();
+                cursor += 1 + count * 3;
+            }
+
+            definitions[usize::from(header & 0x0f)] = Some(FitDefinition {
+                global,
+                big_endian,
+                fields,
+                developer_size,
+            });
+            offset = cursor;
+            continue;
+        }
+
+        // data message, possibly with a compressed timestamp header
+        let (local_type, time_offset) = if header & 0x80 != 0 {
+            ((header >> 5) & 0x03, Some(u32::from(header & 0x1f)))
+        } else {
+            (header & 0x0f, None)
+        };
+        let definition = definitions[usize::from(local_type)]
+            .as_ref()
+            .ok_or(FitError::UndefinedMessage { offset, local_type })?;
+
+        let mut fields = Vec::with_capacity(definition.fields.len() + 1);
+        for field in &definition.fields {
+            let data = bytes
+                .get(cursor..cursor + field.size)
+                .ok_or(truncated.clone())?;
+            fields.push((field.number, field.base_type, data.to_vec()));
+            cursor += field.size;
+        }
+        cursor += definition.developer_size;
+        if cursor > end {
+            return Err(truncated);
+        }
+
+        if let Some(time_offset) = time_offset {
+            let mut timestamp = (last_timestamp & !0x1f) + time_offset;
+            if time_offset < last_timestamp & 0x1f {
+                timestamp += 0x20;
+            }
+            last_timestamp = timestamp;
+            let data = if definition.big_endian {
+                timestamp.to_be_bytes()
+            } else {
+                timestamp.to_le_bytes()
+            };
+            fields.push((253, 0x86, data.to_vec()));
+        } else {
+            let timestamp = fields
+                .iter()
+                .find(|(number, _, _)| *number == 253)
+                .and_then(|(_, base_type, data)| {
+                    read_number(*base_type, data, definition.big_endian)
+                });
+            if let Some(timestamp) = timestamp {
+                last_timestamp = timestamp as u32;
+            }
+        }
+        on_message(definition.global, &fields, definition.big_endian);
+        offset = cursor;
+    }
+    Ok(())
+}
+
+/// read a `record` message into an [`ActivityRecord`], `None` if it has no position
+fn read_record(fields: &[(u8, u8, Vec<u8>)], big_endian: bool) -> Option<ActivityRecord> {
+    let field = |number: u8| {
+        fields
+            .iter()
+            .find(|(field_number, _, _)| *field_number == number)
+            .and_then(|(_, base_type, data)| read_number(*base_type, data, big_endian))
+    };
+    let scaled = |number: u8, scale: f64, offset: f64| field(number).map(|v| v / scale - offset);
+
+    Some(ActivityRecord {
+        position: FlatPosition::new(field(0)? * SEMICIRCLE, field(1)? * SEMICIRCLE),
+        time: field(253).map(|seconds| seconds + FIT_EPOCH),
+        speed: scaled(73, 1000.0, 0.0).or_else(|| scaled(6, 1000.0, 0.0)),
+        power: field(7),
+        heart_rate: field(3),
+        cadence: field(4),
+        altitude: scaled(78, 5.0, 500.0).or_else(|| scaled(2, 5.0, 500.0)),
+        temperature: field(13),
+    })
+}
+
+///
+/// read a single numeric field of the given FIT base type
+///
+/// # Returns
+/// [`Option<f64>`], `None` for the base type's invalid value, arrays and non-numeric types
+///
+fn read_number(base_type: u8, data: &[u8], big_endian: bool) -> Option<f64> {
+    macro_rules! number {
+        ($ty:ty, $invalid:expr) => {{
+            let bytes = data.try_into().ok()?;
+            let value = if big_endian {
+                <$ty>::from_be_bytes(bytes)
+            } else {
+                <$ty>::from_le_bytes(bytes)
+            };
+            (value != $invalid).then_some(value as f64)
+        }};
+    }
+    match base_type & 0x1f {
+        0x00 | 0x02 => number!(u8, u8::MAX),
+        0x01 => number!(i8, i8::MAX),
+        0x03 => number!(i16, i16::MAX),
+        0x04 => number!(u16, u16::MAX),
+        0x05 => number!(i32, i32::MAX),
+        0x06 => number!(u32, u32::MAX),
+        0x0a => number!(u8, 0),
+        0x0b => number!(u16, 0),
+        0x0c => number!(u32, 0),
+        0x08 => number!(f32, f32::from_bits(u32::MAX)).filter(|v| v.is_finite()),
+        0x09 => number!(f64, f64::from_bits(u64::MAX)).filter(|v| v.is_finite()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (field number, size, base type) of the test record definition
+    const RECORD_FIELDS: [(u8, u8, u8); 6] = [
+        (253, 4, 0x86),
+        (0, 4, 0x85),
+        (1, 4, 0x85),
+        (3, 1, 0x02),
+        (7, 2, 0x84),
+        (78, 4, 0x86),
+    ];
+
+    /// one test record: timestamp, lat, lng (degrees), heart rate, power, altitude (m)
+    type Record = (u32, f64, f64, u8, u16, f64);
+
+    fn definition(local_type: u8, global: u16, fields: &[(u8, u8, u8)]) -> Vec<u8> {
+        let mut bytes = vec![0x40 | local_type, 0, 0];
+        bytes.extend(global.to_le_bytes());
+        bytes.push(fields.len() as u8);
+        for (number, size, base_type) in fields {
+            bytes.extend([*number, *size, *base_type]);
+        }
+        bytes
+    }
+
+    fn record((time, lat, lng, heart_rate, power, altitude): Record) -> Vec<u8> {
+        let mut bytes = vec![0];
+        bytes.extend(time.to_le_bytes());
+        bytes.extend(((lat / SEMICIRCLE).round() as i32).to_le_bytes());
+        bytes.extend(((lng / SEMICIRCLE).round() as i32).to_le_bytes());
+        bytes.push(heart_rate);
+        bytes.extend(power.to_le_bytes());
+        bytes.extend((((altitude + 500.0) * 5.0) as u32).to_le_bytes());
+        bytes
+    }
+
+    fn fit_file(data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![14, 0x20, 0, 0];
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(b".FIT");
+        bytes.extend([0, 0]);
+        bytes.extend(data);
+        bytes.extend([0, 0]);
+        bytes
+    }
+
+    fn activity() -> Vec<u8> {
+        let mut data = definition(0, RECORD_MESSAGE, &RECORD_FIELDS);
+        data.extend(record((1_000, 40.0, -105.0, 120, 200, 2_500.0)));
+        data.extend(record((1_001, 40.0, -105.001, 0xff, 260, 2_501.0)));
+        // compressed timestamp header: local type 0, time offset 1_003 & 0x1f
+        let mut compressed = record((0, 40.0, -105.002, 130, 0xffff, 2_502.0));
+        compressed[0] = 0x80 | (1_003 & 0x1f) as u8;
+        let mut definition_without_time = definition(0, RECORD_MESSAGE, &RECORD_FIELDS[1..]);
+        compressed.splice(1..5, []);
+        data.append(&mut definition_without_time);
+        data.extend(compressed);
+        data.extend(definition(1, SESSION_MESSAGE, &[(253, 4, 0x86)]));
+        data.extend([1, 0, 0, 0, 0]);
+        data.extend(definition(0, RECORD_MESSAGE, &RECORD_FIELDS));
+        data.extend(record((2_000, 41.0, -106.0, 140, 300, 100.0)));
+        fit_file(&data)
+    }
+
+    #[test]
+    fn test_sessions_and_metrics() {
+        let tracks = HotlineTrack::from_fit(&activity(), ActivityMetric::HeartRate).unwrap();
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].raw_values(), &[120.0, 120.0, 130.0]);
+        assert_eq!(
+            tracks[0].timestamps(),
+            Some(
+                &[
+                    1_000.0 + FIT_EPOCH,
+                    1_001.0 + FIT_EPOCH,
+                    1_003.0 + FIT_EPOCH
+                ][..]
+            )
+        );
+        assert!((tracks[0].positions()[1].lng + 105.001).abs() < 1e-6);
+
+        let power = HotlineTrack::from_fit(&activity(), ActivityMetric::Power).unwrap();
+        assert_eq!(power[0].raw_values(), &[200.0, 260.0, 260.0]);
+
+        let altitude = HotlineTrack::from_fit(&activity(), ActivityMetric::Altitude).unwrap();
+        assert_eq!(altitude[1].raw_values(), &[100.0]);
+
+        assert_eq!(
+            HotlineTrack::from_fit(&activity(), ActivityMetric::Temperature),
+            Err(FitError::MissingMetric(ActivityMetric::Temperature))
+        );
+    }
+
+    #[test]
+    fn test_normalized_over_sessions() {
+        let tracks = HotlineTrack::from_fit_with_normalization(
+            &activity(),
+            ActivityMetric::HeartRate,
+            Normalization::MinMax,
+        )
+        .unwrap();
+        assert_eq!(tracks[0].values()[0], 0.0);
+        assert_eq!(tracks[1].values(), &[1.0]);
+        assert_eq!(tracks[0].raw_value_for(1.0), 140.0);
+    }
+
+    #[test]
+    fn test_big_endian_compressed_timestamp() {
+        // big-endian definition of lat, lng and heart rate
+        let mut data = vec![0x40, 0, 1];
+        data.extend(RECORD_MESSAGE.to_be_bytes());
+        data.extend([3, 0, 4, 0x85, 1, 4, 0x85, 3, 1, 0x02]);
+        // compressed timestamp header: local type 0, time offset 5
+        data.push(0x80 | 5);
+        data.extend(((40.0 / SEMICIRCLE).round() as i32).to_be_bytes());
+        data.extend(((-105.0 / SEMICIRCLE).round() as i32).to_be_bytes());
+        data.push(120);
+
+        let tracks = HotlineTrack::from_fit(&fit_file(&data), ActivityMetric::HeartRate).unwrap();
+        assert!((tracks[0].positions()[0].lat - 40.0).abs() < 1e-6);
+        assert_eq!(tracks[0].timestamps(), Some(&[5.0 + FIT_EPOCH][..]));
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert_eq!(
+            HotlineTrack::from_fit(b"not a fit file", ActivityMetric::Speed),
+            Err(FitError::InvalidHeader)
+        );
+
+        let mut truncated = activity();
+        truncated.truncate(40);
+        assert_eq!(
+            HotlineTrack::from_fit(&truncated, ActivityMetric::Speed),
+            Err(FitError::Truncated { offset: 14 })
+        );
+
+        // a data size past the end of the address space, not only of the file
+        let mut oversized = activity();
+        oversized[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            HotlineTrack::from_fit(&oversized, ActivityMetric::Speed),
+            Err(FitError::Truncated { offset: 14 })
+        );
+
+        let undefined = fit_file(&record((1_000, 40.0, -105.0, 120, 200, 2_500.0)));
+        assert_eq!(
+            HotlineTrack::from_fit(&undefined, ActivityMetric::Speed),
+            Err(FitError::UndefinedMessage {
+                offset: 14,
+                local_type: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,566 @@
+//! module for importing and exporting hotlines as GeoJSON
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+
+use super::hotline_color::Color;
+use super::hotline_normalization::Normalization;
+use super::hotline_palette::{ColorStop, HotlinePalette, PaletteError};
+use super::hotline_position::HotlinePositionVec;
+
+///
+/// where the values visualized along a GeoJSON line are read from
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeoJsonValues<'a> {
+    /// the third coordinate of each position, `[lng, lat, value]`
+    Coordinate,
+    /// a `properties` array with one value per position, e.g. `"speeds": [...]`;
+    /// for a `MultiLineString` either one flat array or one array per line
+    Property(&'a str),
+}
+
+///
+/// hotline data read from or written to a GeoJSON `Feature`
+///
+/// The palette and min/max are stored as foreign members of the feature:
+/// `"palette"` maps each breakpoint to a CSS color, as leaflet-hotline's
+/// `palette` option does, and `"min"` / `"max"` hold the value range.
+///
+/// # Fields
+/// * `lines` [`Vec<HotlinePositionVec>`] one entry per `LineString`
+/// * `palette` [`Option<HotlinePalette>`] palette foreign member
+/// * `min` [`Option<f64>`] min foreign member
+/// * `max` [`Option<f64>`] max foreign member
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct HotlineGeoJson {
+    /// one set of positions per line, with the raw values as given
+    pub lines: Vec<HotlinePositionVec>,
+    /// palette for the hotline
+    pub palette: Option<HotlinePalette>,
+    /// min breakpoint threshold for the hotline
+    pub min: Option<f64>,
+    /// max breakpoint threshold for the hotline
+    pub max: Option<f64>,
+}
+
+///
+/// error returned when GeoJSON cannot be imported as a hotline
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum GeoJsonError {
+    /// the text is not valid JSON
+    Json(String),
+    /// the object is not a `Feature`, `LineString` or `MultiLineString`
+    UnsupportedType(String),
+    /// a position is not an array of at least two (three for
+    /// [`GeoJsonValues::Coordinate`]) numbers
+    InvalidPosition {
+        /// index of the line
+        line: usize,
+        /// index of the position in the line
+        index: usize,
+    },
+    /// the values property is missing or not an array of numbers
+    MissingValues(String),
+    /// the values property has a different length than the line it belongs to
+    ValueCountMismatch {
+        /// number of positions
+        expected: usize,
+        /// number of values found
+        found: usize,
+    },
+    /// the `palette` foreign member is invalid
+    InvalidPalette(PaletteError),
+}
+
+impl fmt::Display for GeoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(message) => write!(f, "invalid GeoJSON: {message}"),
+            Self::UnsupportedType(kind) => {
+                write!(f, "unsupported GeoJSON type {kind:?} for a hotline")
+            }
+            Self::InvalidPosition { line, index } => {
+                write!(f, "invalid GeoJSON position {index} in line {line}")
+            }
+            Self::MissingValues(name) => {
+                write!(f, "GeoJSON property {name:?} is not an array of numbers")
+            }
+            Self::ValueCountMismatch { expected, found } => {
+                write!(f, "expected {expected} GeoJSON values but found {found}")
+            }
+            Self::InvalidPalette(err) => write!(f, "invalid GeoJSON palette: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for GeoJsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidPalette(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// implement GeoJSON import and export for [`HotlineGeoJson`]
+impl HotlineGeoJson {
+    ///
+    /// parse a GeoJSON `Feature` (or bare geometry) with a `LineString` or
+    /// `MultiLineString` geometry
+    ///
+    /// Values are normalized as in [`HotlinePositionVec::new`], over all lines of the
+    /// feature so that they share one color scale; see
+    /// [`HotlineGeoJson::parse_with_normalization`] to pick another [`Normalization`].
+    ///
+    /// # Returns
+    /// [`HotlineGeoJson`], or [`GeoJsonError`] if the text is not a supported hotline
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{GeoJsonValues, HotlineGeoJson};
+    ///
+    /// let geojson = r#"{
+    ///     "type": "Feature",
+    ///     "geometry": {"type": "LineString", "coordinates": [[-105.618, 40.2928], [-105.619, 40.2928]]},
+    ///     "properties": {"speeds": [4.5, 6.0]},
+    ///     "max": 6.0
+    /// }"#;
+    /// let hotline = HotlineGeoJson::parse(geojson, GeoJsonValues::Property("speeds")).unwrap();
+    /// assert_eq!(hotline.lines[0].raw_values, vec![4.5, 6.0]);
+    /// assert_eq!(hotline.max, Some(6.0));
+    /// ```
+    ///
+    #[inline]
+    pub fn parse(text: &str, values: GeoJsonValues<'_>) -> Result<Self, GeoJsonError> {
+        Self::parse_with_normalization(text, values, Normalization::default())
+    }
+
+    ///
+    /// parse a GeoJSON `Feature` (or bare geometry), normalizing the values of all
+    /// lines with the given strategy
+    ///
+    /// # Returns
+    /// [`HotlineGeoJson`], or [`GeoJsonError`] if the text is not a supported hotline
+    ///
+    pub fn parse_with_normalization(
+        text: &str,
+        values: GeoJsonValues<'_>,
+        normalization: Normalization,
+    ) -> Result<Self, GeoJsonError> {
+        let value: Value =
+            serde_json::from_str(text).map_err(|err| GeoJsonError::Json(err.to_string()))?;
+        Self::from_value_with_normalization(&value, values, normalization)
+    }
+
+    ///
+    /// read a GeoJSON `Feature` (or bare geometry) already parsed into a [`Value`]
+    ///
+    /// # Returns
+    /// [`HotlineGeoJson`], or [`GeoJsonError`] if the object is not a supported hotline
+    ///
+    #[inline]
+    pub fn from_value(value: &Value, values: GeoJsonValues<'_>) -> Result<Self, GeoJsonError> {
+        Self::from_value_with_normalization(value, values, Normalization::default())
+    }
+
+    ///
+    /// read a GeoJSON `Feature` (or bare geometry) already parsed into a [`Value`],
+    /// normalizing the values of all lines with the given strategy
+    ///
+    /// # Returns
+    /// [`HotlineGeoJson`], or [`GeoJsonError`] if the object is not a supported hotline
+    ///
+    pub fn from_value_with_normalization(
+        value: &Value,
+        values: GeoJsonValues<'_>,
+        normalization: Normalization,
+    ) -> Result<Self, GeoJsonError> {
+        let (geometry, properties) = match type_of(value) {
+            "Feature" => (&value["geometry"], value.get("properties")),
+            _ => (value, None),
+        };
+        let lines: Vec<&Vec<Value>> = match type_of(geometry) {
+            "LineString" => vec![array(&geometry["coordinates"])],
+            "MultiLineString" => array(&geometry["coordinates"]).iter().map(array).collect(),
+            kind => return Err(GeoJsonError::UnsupportedType(kind.to_string())),
+        };
+
+        let line_values = match values {
+            GeoJsonValues::Coordinate => None,
+            GeoJsonValues::Property(name) => {
+                let property = properties.and_then(|properties| properties.get(name));
+                Some(property_values(property, name, &lines)?)
+            }
+        };
+
+        let lines = lines
+            .iter()
+            .enumerate()
+            .map(|(line, coordinates)| {
+                coordinates
+                    .iter()
+                    .enumerate()
+                    .map(|(index, position)| {
+                        let numbers: Vec<f64> =
+                            array(position).iter().map_while(Value::as_f64).collect();
+                        let value = match &line_values {
+                            Some(line_values) => line_values[line].get(index).copied(),
+                            None => numbers.get(2).copied(),
+                        };
+                        match (numbers.first(), numbers.get(1), value) {
+                            (Some(&lng), Some(&lat), Some(value)) => Ok((lat, lng, value)),
+                            _ => Err(GeoJsonError::InvalidPosition { line, index }),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, GeoJsonError>>()?;
+        // a single line keeps its strategy, which gives the same values when resolved
+        let normalization = if lines.len() > 1 {
+            let all_values: Vec<f64> = lines.iter().flatten().map(|tuple| tuple.2).collect();
+            normalization.resolve(&all_values)
+        } else {
+            normalization
+        };
+        let lines = lines
+            .iter()
+            .map(|tuples| HotlinePositionVec::with_normalization(tuples, normalization))
+            .collect();
+
+        Ok(Self {
+            lines,
+            palette: value.get("palette").map(palette_from_value).transpose()?,
+            min: value.get("min").and_then(Value::as_f64),
+            max: value.get("max").and_then(Value::as_f64),
+        })
+    }
+
+    ///
+    /// write the hotline as a GeoJSON `Feature`, with each raw value as the third
+    /// coordinate and the palette and min/max as foreign members
+    ///
+    /// A single line is written as a `LineString`, several as a `MultiLineString`.
+    ///
+    /// # Returns
+    /// [`Value`] GeoJSON `Feature`
+    ///
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        let mut lines: Vec<Value> = self
+            .lines
+            .iter()
+            .map(|line| {
+                line.positions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, pos)| {
+                        let value = line.raw_values.get(index).copied().unwrap_or(pos.alt);
+                        json!([pos.latlng.lng, pos.latlng.lat, value])
+                    })
+                    .collect()
+            })
+            .collect();
+        let geometry = if lines.len() == 1 {
+            json!({"type": "LineString", "coordinates": lines.remove(0)})
+        } else {
+            json!({"type": "MultiLineString", "coordinates": lines})
+        };
+
+        let mut feature = Map::new();
+        feature.insert("type".into(), "Feature".into());
+        feature.insert("geometry".into(), geometry);
+        feature.insert("properties".into(), Value::Object(Map::new()));
+        if let Some(palette) = &self.palette {
+            feature.insert("palette".into(), palette_to_value(palette));
+        }
+        if let Some(min) = self.min {
+            feature.insert("min".into(), min.into());
+        }
+        if let Some(max) = self.max {
+            feature.insert("max".into(), max.into());
+        }
+        Value::Object(feature)
+    }
+}
+
+/// writes the hotline as GeoJSON text, see [`HotlineGeoJson::to_value`]
+impl fmt::Display for HotlineGeoJson {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_value())
+    }
+}
+
+/// implement GeoJSON import and export for [`HotlinePositionVec`]
+impl HotlinePositionVec {
+    ///
+    /// import the lines of a GeoJSON `LineString` / `MultiLineString` feature,
+    /// ignoring any palette and min/max; see [`HotlineGeoJson::parse`]
+    ///
+    /// # Returns
+    /// [`Vec<HotlinePositionVec>`], or [`GeoJsonError`]
+    ///
+    #[inline]
+    pub fn from_geojson(text: &str, values: GeoJsonValues<'_>) -> Result<Vec<Self>, GeoJsonError> {
+        HotlineGeoJson::parse(text, values).map(|hotline| hotline.lines)
+    }
+
+    ///
+    /// import the lines of a GeoJSON `LineString` / `MultiLineString` feature,
+    /// normalizing the values of all lines with the given strategy; see
+    /// [`HotlineGeoJson::parse_with_normalization`]
+    ///
+    /// # Returns
+    /// [`Vec<HotlinePositionVec>`], or [`GeoJsonError`]
+    ///
+    pub fn from_geojson_with_normalization(
+        text: &str,
+        values: GeoJsonValues<'_>,
+        normalization: Normalization,
+    ) -> Result<Vec<Self>, GeoJsonError> {
+        HotlineGeoJson::parse_with_normalization(text, values, normalization)
+            .map(|hotline| hotline.lines)
+    }
+
+    ///
+    /// export as a GeoJSON `LineString` feature with each raw value as the third coordinate
+    ///
+    /// # Returns
+    /// [`String`] GeoJSON text
+    ///
+    #[must_use]
+    pub fn to_geojson(&self) -> String {
+        HotlineGeoJson {
+            lines: vec![self.clone()],
+            ..HotlineGeoJson::default()
+        }
+        .to_string()
+    }
+}
+
+/// the `"type"` member of a GeoJSON object, empty if missing
+#[inline]
+fn type_of(value: &Value) -> &str {
+    value["type"].as_str().unwrap_or_default()
+}
+
+/// the elements of a JSON array, empty for anything else
+#[inline]
+fn array(value: &Value) -> &Vec<Value> {
+    static EMPTY: Vec<Value> = Vec::new();
+    value.as_array().unwrap_or(&EMPTY)
+}
+
+/// values of a `properties` array, split per line
+fn property_values(
+    property: Option<&Value>,
+    name: &str,
+    lines: &[&Vec<Value>],
+) -> Result<Vec<Vec<f64>>, GeoJsonError> {
+    let numbers = |value: &Value| -> Result<Vec<f64>, GeoJsonError> {
+        array(value)
+            .iter()
+            .map(|number| {
+                number
+                    .as_f64()
+                    .ok_or_else(|| GeoJsonError::MissingValues(name.to_string()))
+            })
+            .collect()
+    };
+    let property = property
+        .filter(|value| value.is_array())
+        .ok_or_else(|| GeoJsonError::MissingValues(name.to_string()))?;
+
+    // one array per line, whatever the number of lines; a flat array holds numbers
+    let nested = !array(property).is_empty() && array(property).iter().all(Value::is_array);
+    if nested {
+        if array(property).len() != lines.len() {
+            return Err(GeoJsonError::ValueCountMismatch {
+                expected: lines.len(),
+                found: array(property).len(),
+            });
+        }
+        return array(property)
+            .iter()
+            .zip(lines)
+            .map(|(values, line)| {
+                let values = numbers(values)?;
+                if values.len() != line.len() {
+                    return Err(GeoJsonError::ValueCountMismatch {
+                        expected: line.len(),
+                        found: values.len(),
+                    });
+                }
+                Ok(values)
+            })
+            .collect();
+    }
+
+    let flat = numbers(property)?;
+    let expected: usize = lines.iter().map(|line| line.len()).sum();
+    if flat.len() != expected {
+        return Err(GeoJsonError::ValueCountMismatch {
+            expected,
+            found: flat.len(),
+        });
+    }
+    let mut flat = flat.into_iter();
+    Ok(lines
+        .iter()
+        .map(|line| flat.by_ref().take(line.len()).collect())
+        .collect())
+}
+
+/// leaflet-hotline style palette object: breakpoint -> CSS color
+fn palette_to_value(palette: &HotlinePalette) -> Value {
+    let stops: Map<String, Value> = palette
+        .stops()
+        .iter()
+        .map(|stop| (stop.breakpoint.to_string(), stop.color.to_css().into()))
+        .collect();
+    Value::Object(stops)
+}
+
+/// read a leaflet-hotline style palette object: breakpoint -> CSS color
+fn palette_from_value(value: &Value) -> Result<HotlinePalette, GeoJsonError> {
+    let stops = value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(breakpoint, color)| {
+            let color = Color::parse(color.as_str().unwrap_or_default())?;
+            // a non-numeric key becomes a NaN breakpoint, which from_stops rejects
+            let breakpoint = breakpoint.trim().parse().unwrap_or(f64::NAN);
+            Ok(ColorStop::new(color, breakpoint))
+        })
+        .collect::<Result<Vec<_>, PaletteError>>()
+        .map_err(GeoJsonError::InvalidPalette)?;
+    HotlinePalette::from_stops(stops).map_err(GeoJsonError::InvalidPalette)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_third_coordinate() {
+        let geojson = r#"{"type": "LineString", "coordinates": [[-105.618, 40.2928, 3500], [-105.619, 40.2928, 3510]]}"#;
+        let lines = HotlinePositionVec::from_geojson(geojson, GeoJsonValues::Coordinate).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].positions[1].latlng.lng, -105.619);
+        assert_eq!(lines[0].raw_values, vec![3500.0, 3510.0]);
+
+        let min_max = HotlinePositionVec::from_geojson_with_normalization(
+            geojson,
+            GeoJsonValues::Coordinate,
+            Normalization::MinMax,
+        )
+        .unwrap();
+        assert_eq!(min_max[0].positions[0].alt, 0.0);
+        assert_eq!(min_max[0].normalization, Normalization::MinMax);
+
+        let flat = r#"{"type": "LineString", "coordinates": [[-105.618, 40.2928]]}"#;
+        assert_eq!(
+            HotlinePositionVec::from_geojson(flat, GeoJsonValues::Coordinate),
+            Err(GeoJsonError::InvalidPosition { line: 0, index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_multi_line_property_values() {
+        let geojson = r#"{
+            "type": "Feature",
+            "geometry": {"type": "MultiLineString", "coordinates": [
+                [[0, 0], [0, 1]],
+                [[1, 1], [1, 2], [1, 3]]
+            ]},
+            "properties": {"hr": [[120, 125], [130, 135, 140]], "flat": [1, 2, 3, 4, 5], "short": [1, 2]}
+        }"#;
+        let nested =
+            HotlinePositionVec::from_geojson(geojson, GeoJsonValues::Property("hr")).unwrap();
+        assert_eq!(nested[1].raw_values, vec![130.0, 135.0, 140.0]);
+        // one color scale for the feature: 140 is the largest value of both lines
+        assert_eq!(nested[0].positions[0].alt, 120.0 / 140.0);
+        assert_eq!(nested[1].positions[2].alt, 1.0);
+
+        let flat =
+            HotlinePositionVec::from_geojson(geojson, GeoJsonValues::Property("flat")).unwrap();
+        assert_eq!(flat[0].raw_values, vec![1.0, 2.0]);
+        assert_eq!(flat[1].raw_values, vec![3.0, 4.0, 5.0]);
+
+        assert_eq!(
+            HotlinePositionVec::from_geojson(geojson, GeoJsonValues::Property("short")),
+            Err(GeoJsonError::ValueCountMismatch {
+                expected: 5,
+                found: 2
+            })
+        );
+        assert_eq!(
+            HotlinePositionVec::from_geojson(geojson, GeoJsonValues::Property("speeds")),
+            Err(GeoJsonError::MissingValues("speeds".to_string()))
+        );
+
+        let single = r#"{
+            "type": "Feature",
+            "geometry": {"type": "MultiLineString", "coordinates": [[[0, 0], [0, 1]]]},
+            "properties": {"hr": [[120, 125]]}
+        }"#;
+        let single =
+            HotlinePositionVec::from_geojson(single, GeoJsonValues::Property("hr")).unwrap();
+        assert_eq!(single[0].raw_values, vec![120.0, 125.0]);
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        let point = r#"{"type": "Point", "coordinates": [0, 0]}"#;
+        assert_eq!(
+            HotlinePositionVec::from_geojson(point, GeoJsonValues::Coordinate),
+            Err(GeoJsonError::UnsupportedType("Point".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let hotline = HotlineGeoJson {
+            lines: vec![HotlinePositionVec::new(&[
+                (40.2928, -105.618, 10.0),
+                (40.2928, -105.619, 20.0),
+            ])],
+            palette: Some(HotlinePalette::new(&[("green", 0.0), ("red", 1.0)])),
+            min: Some(10.0),
+            max: Some(20.0),
+        };
+
+        let value = hotline.to_value();
+        assert_eq!(
+            value["geometry"]["coordinates"][1],
+            json!([-105.619, 40.2928, 20.0])
+        );
+        assert_eq!(value["palette"]["1"], "#ff0000");
+
+        let parsed =
+            HotlineGeoJson::parse(&hotline.to_string(), GeoJsonValues::Coordinate).unwrap();
+        assert_eq!(parsed, hotline);
+    }
+
+    #[test]
+    fn test_invalid_palette() {
+        let geojson = r#"{
+            "type": "Feature",
+            "geometry": {"type": "LineString", "coordinates": [[0, 0, 1]]},
+            "properties": {},
+            "palette": {"0": "green", "2": "red"}
+        }"#;
+        assert!(matches!(
+            HotlineGeoJson::parse(geojson, GeoJsonValues::Coordinate),
+            Err(GeoJsonError::InvalidPalette(
+                PaletteError::BreakpointOutOfRange { .. }
+            ))
+        ));
+    }
+}
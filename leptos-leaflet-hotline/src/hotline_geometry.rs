@@ -0,0 +1,335 @@
+//! module for nearest-point and segment hit testing along a hotline path
+#[cfg(feature = "wasm")]
+use leptos_leaflet::leaflet as L;
+
+use super::hotline_position::{FlatPosition, HotlinePositionVec};
+use super::hotline_track::HotlineTrack;
+
+/// mean earth radius in meters, as used by leaflet's `CRS.Earth`
+pub const EARTH_RADIUS: f64 = 6_371_000.0;
+
+///
+/// closest point on a hotline path to a query position
+///
+/// # Fields
+/// * `segment` [`usize`] index of the first point of the nearest segment
+/// * `point` [`FlatPosition`] query position projected onto that segment
+/// * `fractional_index` [`f64`] `segment` plus the fraction along the segment, e.g. 2.5 halfway between points 2 and 3
+/// * `distance_along` [`f64`] meters from the start of the path to `point`
+/// * `distance` [`f64`] meters from the query position to `point`
+/// * `value` [`f64`] raw value linearly interpolated at `point`
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct PathProjection {
+    /// index of the first point of the nearest segment
+    pub segment: usize,
+    /// query position projected onto the path
+    pub point: FlatPosition,
+    /// point index with the fraction along the segment
+    pub fractional_index: f64,
+    /// meters from the start of the path to the projected point
+    pub distance_along: f64,
+    /// meters from the query position to the projected point
+    pub distance: f64,
+    /// raw value interpolated at the projected point
+    pub value: f64,
+}
+
+/// implement hit testing on raw values for [`HotlinePositionVec`]
+impl HotlinePositionVec {
+    ///
+    /// project a position onto the path, interpolating the raw values
+    ///
+    /// # Returns
+    /// [`Option<PathProjection>`], `None` if there are no positions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{FlatPosition, HotlinePositionVec};
+    ///
+    /// let path = HotlinePositionVec::new(&[(40.0, -105.0, 10.0), (40.0, -105.01, 20.0)]);
+    /// let hit = path.project(&FlatPosition::new(40.0001, -105.005)).unwrap();
+    /// assert_eq!(hit.segment, 0);
+    /// assert!((hit.value - 15.0).abs() < 1e-6);
+    /// ```
+    ///
+    #[must_use]
+    pub fn project(&self, position: &FlatPosition) -> Option<PathProjection> {
+        let use_raw = self.raw_values.len() == self.positions.len();
+        project_onto_path(
+            self.positions.len(),
+            |index| self.positions[index].latlng,
+            |index| {
+                if use_raw {
+                    self.raw_values[index]
+                } else {
+                    self.positions[index].alt
+                }
+            },
+            position,
+        )
+    }
+
+    ///
+    /// project a map container point (e.g. a pointer position) onto the path
+    ///
+    /// # Arguments
+    /// * `map` - map the path is displayed on
+    /// * `point` - pixel position relative to the map container
+    /// * `tolerance` - largest pixel distance that counts as a hit, e.g. [`crate::Hotline::_click_tolerance`]
+    ///
+    /// # Returns
+    /// [`Option<PathProjection>`], `None` if the path is farther than `tolerance` pixels
+    ///
+    #[cfg(feature = "wasm")]
+    #[must_use]
+    pub fn hit_test(
+        &self,
+        map: &L::Map,
+        point: &L::Point,
+        tolerance: f64,
+    ) -> Option<PathProjection> {
+        let lat_lng = map.container_point_to_lat_lng(point);
+        let projection = self.project(&FlatPosition::new(lat_lng.lat(), lat_lng.lng()))?;
+        within_tolerance(map, point, projection, tolerance)
+    }
+
+    ///
+    /// length of the path in meters
+    ///
+    /// # Returns
+    /// [`f64`]
+    ///
+    #[must_use]
+    pub fn path_length(&self) -> f64 {
+        self.positions
+            .windows(2)
+            .map(|pair| haversine_distance(&pair[0].latlng, &pair[1].latlng))
+            .sum()
+    }
+}
+
+/// implement hit testing on raw values for [`HotlineTrack`]
+impl HotlineTrack {
+    ///
+    /// project a position onto the track, interpolating the raw values
+    ///
+    /// # Returns
+    /// [`Option<PathProjection>`], `None` for an empty track
+    ///
+    #[must_use]
+    #[inline]
+    pub fn project(&self, position: &FlatPosition) -> Option<PathProjection> {
+        let values = self.raw_values();
+        project_onto_path(
+            self.len(),
+            |index| self.positions()[index],
+            |index| values.get(index).copied().unwrap_or(f64::NAN),
+            position,
+        )
+    }
+
+    ///
+    /// project a map container point (e.g. a pointer position) onto the track
+    ///
+    /// # Returns
+    /// [`Option<PathProjection>`], `None` if the track is farther than `tolerance` pixels
+    ///
+    #[cfg(feature = "wasm")]
+    #[must_use]
+    pub fn hit_test(
+        &self,
+        map: &L::Map,
+        point: &L::Point,
+        tolerance: f64,
+    ) -> Option<PathProjection> {
+        let lat_lng = map.container_point_to_lat_lng(point);
+        let projection = self.project(&FlatPosition::new(lat_lng.lat(), lat_lng.lng()))?;
+        within_tolerance(map, point, projection, tolerance)
+    }
+
+    ///
+    /// length of the track in meters
+    ///
+    /// # Returns
+    /// [`f64`]
+    ///
+    #[must_use]
+    pub fn path_length(&self) -> f64 {
+        self.positions()
+            .windows(2)
+            .map(|pair| haversine_distance(&pair[0], &pair[1]))
+            .sum()
+    }
+}
+
+///
+/// great-circle distance between two positions in meters
+///
+/// # Returns
+/// [`f64`]
+///
+#[must_use]
+pub fn haversine_distance(from: &FlatPosition, to: &FlatPosition) -> f64 {
+    let (lat1, lat2) = (from.lat.to_radians(), to.lat.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lng = (to.lng - from.lng).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// keeps a projection only if it is within `tolerance` pixels of `point` on the map
+#[cfg(feature = "wasm")]
+fn within_tolerance(
+    map: &L::Map,
+    point: &L::Point,
+    projection: PathProjection,
+    tolerance: f64,
+) -> Option<PathProjection> {
+    let projected =
+        map.lat_lng_to_container_point(&L::LatLng::new(projection.point.lat, projection.point.lng));
+    let pixels = (projected.x() - point.x()).hypot(projected.y() - point.y());
+    (pixels <= tolerance).then_some(projection)
+}
+
+///
+/// project a position onto the polyline through `len` points, read with `position_at`
+/// and `value_at` so callers need not collect their points first
+///
+/// Each segment is projected in a local equirectangular plane centred on the query
+/// position, which is accurate for the short distances involved in hit testing.
+/// Distances along the path use great-circle segment lengths.
+///
+fn project_onto_path(
+    len: usize,
+    position_at: impl Fn(usize) -> FlatPosition,
+    value_at: impl Fn(usize) -> f64,
+    position: &FlatPosition,
+) -> Option<PathProjection> {
+    if len == 0 {
+        return None;
+    }
+    if len == 1 {
+        let first = position_at(0);
+        return Some(PathProjection {
+            segment: 0,
+            point: first,
+            fractional_index: 0.0,
+            distance_along: 0.0,
+            distance: haversine_distance(position, &first),
+            value: value_at(0),
+        });
+    }
+
+    let lng_scale = position.lat.to_radians().cos();
+    let to_plane = |pos: &FlatPosition| {
+        (
+            (pos.lng - position.lng).to_radians() * lng_scale * EARTH_RADIUS,
+            (pos.lat - position.lat).to_radians() * EARTH_RADIUS,
+        )
+    };
+
+    let mut best: Option<(usize, f64, f64)> = None;
+    for segment in 0..len - 1 {
+        let (ax, ay) = to_plane(&position_at(segment));
+        let (bx, by) = to_plane(&position_at(segment + 1));
+        let (dx, dy) = (bx - ax, by - ay);
+        let length_sq = dx * dx + dy * dy;
+        let t = if length_sq > 0.0 {
+            (-(ax * dx + ay * dy) / length_sq).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let distance = (ax + t * dx).hypot(ay + t * dy);
+        if best.is_none_or(|(_, _, best_distance)| distance < best_distance) {
+            best = Some((segment, t, distance));
+        }
+    }
+    let (segment, t, distance) = best?;
+
+    let (from, to) = (position_at(segment), position_at(segment + 1));
+    let point = FlatPosition::new(
+        from.lat + (to.lat - from.lat) * t,
+        from.lng + (to.lng - from.lng) * t,
+    );
+    let distance_along = (0..segment)
+        .map(|index| haversine_distance(&position_at(index), &position_at(index + 1)))
+        .sum::<f64>()
+        + haversine_distance(&from, &to) * t;
+
+    Some(PathProjection {
+        segment,
+        point,
+        fractional_index: segment as f64 + t,
+        distance_along,
+        distance,
+        value: value_at(segment) + (value_at(segment + 1) - value_at(segment)) * t,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// three points heading east along the equator, about 1113 m apart
+    fn equator_path() -> HotlinePositionVec {
+        HotlinePositionVec::new(&[(0.0, 0.0, 0.0), (0.0, 0.01, 10.0), (0.0, 0.02, 30.0)])
+    }
+
+    #[test]
+    fn test_project_between_points() {
+        let hit = equator_path()
+            .project(&FlatPosition::new(0.001, 0.015))
+            .unwrap();
+        assert_eq!(hit.segment, 1);
+        assert!((hit.fractional_index - 1.5).abs() < 1e-9);
+        assert!((hit.point.lat).abs() < 1e-12);
+        assert!((hit.point.lng - 0.015).abs() < 1e-9);
+        assert!((hit.value - 20.0).abs() < 1e-9);
+        assert!((hit.distance - 111.2).abs() < 0.1, "{}", hit.distance);
+        assert!(
+            (hit.distance_along - 1667.9).abs() < 0.1,
+            "{}",
+            hit.distance_along
+        );
+    }
+
+    #[test]
+    fn test_project_beyond_end() {
+        let hit = equator_path()
+            .project(&FlatPosition::new(0.0, 0.05))
+            .unwrap();
+        assert_eq!(hit.segment, 1);
+        assert_eq!(hit.fractional_index, 2.0);
+        assert_eq!(hit.value, 30.0);
+        assert!((hit.distance_along - equator_path().path_length()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_project_single_point_and_empty() {
+        let single = HotlinePositionVec::new(&[(40.0, -105.0, 5.0)]);
+        let hit = single.project(&FlatPosition::new(40.0, -105.0)).unwrap();
+        assert_eq!(hit.value, 5.0);
+        assert_eq!(hit.distance, 0.0);
+
+        let empty = HotlinePositionVec::new(&[]);
+        assert!(empty.project(&FlatPosition::new(40.0, -105.0)).is_none());
+    }
+
+    #[test]
+    fn test_track_projection_uses_raw_values() {
+        let track = HotlineTrack::from(equator_path());
+        let hit = track.project(&FlatPosition::new(0.0, 0.005)).unwrap();
+        assert!((hit.value - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        let london = FlatPosition::new(51.5074, -0.1278);
+        let paris = FlatPosition::new(48.8566, 2.3522);
+        let distance = haversine_distance(&london, &paris);
+        assert!((distance - 343_500.0).abs() < 1_000.0, "{distance}");
+    }
+}
@@ -0,0 +1,407 @@
+//! module for importing GPX tracks and routes as hotline positions
+use std::fmt;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::hotline_normalization::Normalization;
+use super::hotline_position::{FlatPosition, HotlinePositionVec};
+use super::hotline_samples::{between_points, fill_gaps};
+use super::hotline_time::parse_rfc3339;
+
+///
+/// value read from each GPX point to color the hotline
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GpxMetric {
+    /// `<ele>` elevation in meters
+    #[default]
+    Elevation,
+    /// speed in m/s, from the GPX 1.0 `<speed>` element or computed from `<time>` and distance
+    Speed,
+    /// grade in percent, computed from elevation change over distance
+    Grade,
+    /// Garmin `TrackPointExtension` heart rate in beats per minute
+    HeartRate,
+    /// Garmin `TrackPointExtension` cadence in revolutions per minute
+    Cadence,
+    /// Garmin `TrackPointExtension` temperature in degrees Celsius
+    Temperature,
+}
+
+///
+/// error returned when a GPX document cannot be imported
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum GpxError {
+    /// the document is not well-formed XML
+    Xml {
+        /// byte offset of the error in the document
+        position: u64,
+        /// description of the XML error
+        message: String,
+    },
+    /// a `trkpt` or `rtept` has a missing or invalid `lat` / `lon` attribute
+    InvalidPoint {
+        /// byte offset of the point in the document
+        position: u64,
+    },
+    /// no point of a segment or route carries the data needed for the metric
+    MissingMetric(GpxMetric),
+}
+
+impl fmt::Display for GpxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml { position, message } => {
+                write!(f, "invalid GPX XML at byte {position}: {message}")
+            }
+            Self::InvalidPoint { position } => {
+                write!(f, "GPX point at byte {position} has an invalid lat/lon")
+            }
+            Self::MissingMetric(metric) => {
+                write!(f, "GPX segment has no data for metric {metric:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GpxError {}
+
+/// a GPX point with the optional data the metrics are computed from
+#[derive(Debug, Default, Clone, Copy)]
+struct GpxPoint {
+    position: FlatPosition,
+    elevation: Option<f64>,
+    time: Option<f64>,
+    speed: Option<f64>,
+    heart_rate: Option<f64>,
+    cadence: Option<f64>,
+    temperature: Option<f64>,
+}
+
+/// point child element whose text is being read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GpxField {
+    Elevation,
+    Time,
+    Speed,
+    HeartRate,
+    Cadence,
+    Temperature,
+}
+
+/// implement GPX import for [`HotlinePositionVec`]
+impl HotlinePositionVec {
+    ///
+    /// import the tracks and routes of a GPX 1.0 / 1.1 document
+    ///
+    /// Each `trk/trkseg` and each `rte` becomes one [`HotlinePositionVec`], in
+    /// document order.  Points missing the metric take the value of the nearest
+    /// earlier point (or the first later one), and values are normalized as in
+    /// [`HotlinePositionVec::new`], over the whole document so that segments share one
+    /// color scale; see [`HotlinePositionVec::from_gpx_with_normalization`] to pick
+    /// another [`Normalization`].
+    ///
+    /// # Returns
+    /// [`Vec<HotlinePositionVec>`], or [`GpxError`] if the document is malformed or a
+    /// segment has no data for `metric`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{GpxMetric, HotlinePositionVec};
+    ///
+    /// let gpx = r#"<gpx version="1.1"><trk><trkseg>
+    ///     <trkpt lat="40.2928" lon="-105.6180"><ele>3500</ele></trkpt>
+    ///     <trkpt lat="40.2928" lon="-105.6190"><ele>3510</ele></trkpt>
+    /// </trkseg></trk></gpx>"#;
+    /// let segments = HotlinePositionVec::from_gpx(gpx, GpxMetric::Elevation).unwrap();
+    /// assert_eq!(segments[0].raw_values, vec![3500.0, 3510.0]);
+    /// ```
+    ///
+    #[inline]
+    pub fn from_gpx(gpx: &str, metric: GpxMetric) -> Result<Vec<Self>, GpxError> {
+        Self::from_gpx_with_normalization(gpx, metric, Normalization::default())
+    }
+
+    ///
+    /// import the tracks and routes of a GPX 1.0 / 1.1 document, normalizing the
+    /// values of the whole document with the given strategy
+    ///
+    /// # Returns
+    /// [`Vec<HotlinePositionVec>`], or [`GpxError`] as for [`HotlinePositionVec::from_gpx`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{GpxMetric, HotlinePositionVec, Normalization};
+    ///
+    /// let gpx = r#"<gpx version="1.1"><trk><trkseg>
+    ///     <trkpt lat="40.2928" lon="-105.6180"><ele>3500</ele></trkpt>
+    ///     <trkpt lat="40.2928" lon="-105.6190"><ele>3510</ele></trkpt>
+    /// </trkseg></trk></gpx>"#;
+    /// let segments =
+    ///     HotlinePositionVec::from_gpx_with_normalization(gpx, GpxMetric::Elevation, Normalization::MinMax)
+    ///         .unwrap();
+    /// assert_eq!(segments[0].positions[1].alt, 1.0);
+    /// ```
+    ///
+    pub fn from_gpx_with_normalization(
+        gpx: &str,
+        metric: GpxMetric,
+        normalization: Normalization,
+    ) -> Result<Vec<Self>, GpxError> {
+        let segments = parse_segments(gpx)?;
+        let values = segments
+            .iter()
+            .map(|points| metric_values(points, metric))
+            .collect::<Result<Vec<_>, _>>()?;
+        let all_values: Vec<f64> = values.iter().flatten().copied().collect();
+        let normalization = normalization.resolve(&all_values);
+
+        Ok(segments
+            .iter()
+            .zip(values)
+            .map(|(points, values)| {
+                let tuples: Vec<(f64, f64, f64)> = points
+                    .iter()
+                    .zip(values)
+                    .map(|(point, value)| (point.position.lat, point.position.lng, value))
+                    .collect();
+                Self::with_normalization(&tuples, normalization)
+            })
+            .collect())
+    }
+}
+
+/// read the points of every track segment and route, in document order
+fn parse_segments(gpx: &str) -> Result<Vec<Vec<GpxPoint>>, GpxError> {
+    let mut reader = Reader::from_str(gpx);
+    reader.config_mut().trim_text(true);
+
+    let mut segments = Vec::new();
+    let mut segment: Option<Vec<GpxPoint>> = None;
+    let mut point: Option<GpxPoint> = None;
+    let mut field: Option<GpxField> = None;
+
+    loop {
+        let position = reader.buffer_position();
+        let event = reader.read_event().map_err(|err| GpxError::Xml {
+            position,
+            message: err.to_string(),
+        })?;
+
+        match event {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"trkseg" | b"rte" => segment = Some(Vec::new()),
+                b"trkpt" | b"rtept" if segment.is_some() => {
+                    point = Some(read_point(&element, position)?);
+                }
+                name if point.is_some() => field = point_field(name),
+                _ => {}
+            },
+            Event::Empty(element) => {
+                if let (b"trkpt" | b"rtept", Some(points)) =
+                    (element.local_name().as_ref(), segment.as_mut())
+                {
+                    points.push(read_point(&element, position)?);
+                }
+            }
+            Event::Text(text) => {
+                if let (Some(current), Some(field)) = (point.as_mut(), field) {
+                    let text = text.unescape().map_err(|err| GpxError::Xml {
+                        position,
+                        message: err.to_string(),
+                    })?;
+                    set_field(current, field, &text);
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"trkseg" | b"rte" => {
+                    segments.extend(segment.take().filter(|points| !points.is_empty()));
+                }
+                b"trkpt" | b"rtept" => {
+                    if let (Some(points), Some(current)) = (segment.as_mut(), point.take()) {
+                        points.push(current);
+                    }
+                }
+                _ => field = None,
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(segments)
+}
+
+/// read the `lat` and `lon` attributes of a `trkpt` or `rtept`
+fn read_point(element: &BytesStart<'_>, position: u64) -> Result<GpxPoint, GpxError> {
+    let coordinate = |name: &str| -> Option<f64> {
+        let attribute = element.try_get_attribute(name).ok()??;
+        attribute.unescape_value().ok()?.trim().parse().ok()
+    };
+    match (coordinate("lat"), coordinate("lon")) {
+        (Some(lat), Some(lng)) if lat.is_finite() && lng.is_finite() => Ok(GpxPoint {
+            position: FlatPosition::new(lat, lng),
+            ..GpxPoint::default()
+        }),
+        _ => Err(GpxError::InvalidPoint { position }),
+    }
+}
+
+/// point child elements read for the metrics, matched by local name so any
+/// extension namespace prefix (`gpxtpx:`, `ns3:`, ...) is accepted
+fn point_field(name: &[u8]) -> Option<GpxField> {
+    match name {
+        b"ele" => Some(GpxField::Elevation),
+        b"time" => Some(GpxField::Time),
+        b"speed" => Some(GpxField::Speed),
+        b"hr" => Some(GpxField::HeartRate),
+        b"cad" => Some(GpxField::Cadence),
+        b"atemp" => Some(GpxField::Temperature),
+        _ => None,
+    }
+}
+
+/// store the text of a point child element; unparseable text is ignored
+fn set_field(point: &mut GpxPoint, field: GpxField, text: &str) {
+    if field == GpxField::Time {
+        point.time = parse_rfc3339(text);
+        return;
+    }
+    let value = text.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+    match field {
+        GpxField::Elevation => point.elevation = value,
+        GpxField::Speed => point.speed = value,
+        GpxField::HeartRate => point.heart_rate = value,
+        GpxField::Cadence => point.cadence = value,
+        GpxField::Temperature => point.temperature = value,
+        GpxField::Time => {}
+    }
+}
+
+/// value of `metric` for each point, filling gaps from neighbouring points
+fn metric_values(points: &[GpxPoint], metric: GpxMetric) -> Result<Vec<f64>, GpxError> {
+    let values: Vec<Option<f64>> = match metric {
+        GpxMetric::Elevation => points.iter().map(|point| point.elevation).collect(),
+        GpxMetric::HeartRate => points.iter().map(|point| point.heart_rate).collect(),
+        GpxMetric::Cadence => points.iter().map(|point| point.cadence).collect(),
+        GpxMetric::Temperature => points.iter().map(|point| point.temperature).collect(),
+        GpxMetric::Speed if points.iter().any(|point| point.speed.is_some()) => {
+            points.iter().map(|point| point.speed).collect()
+        }
+        GpxMetric::Speed => between_points(
+            points,
+            |point| point.position,
+            |from, to, distance| {
+                let seconds = to.time? - from.time?;
+                (seconds > 0.0).then(|| distance / seconds)
+            },
+        ),
+        GpxMetric::Grade => between_points(
+            points,
+            |point| point.position,
+            |from, to, distance| {
+                let rise = to.elevation? - from.elevation?;
+                (distance > 0.0).then(|| rise / distance * 100.0)
+            },
+        ),
+    };
+    fill_gaps(values).ok_or(GpxError::MissingMetric(metric))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1"
+     xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v1">
+  <trk>
+    <trkseg>
+      <trkpt lat="0.0" lon="0.0">
+        <ele>100</ele><time>2024-05-01T07:30:00Z</time>
+        <extensions><gpxtpx:TrackPointExtension><gpxtpx:hr>120</gpxtpx:hr><gpxtpx:cad>80</gpxtpx:cad></gpxtpx:TrackPointExtension></extensions>
+      </trkpt>
+      <trkpt lat="0.0" lon="0.001">
+        <ele>105</ele><time>2024-05-01T07:30:10Z</time>
+        <extensions><gpxtpx:TrackPointExtension><gpxtpx:hr>130</gpxtpx:hr></gpxtpx:TrackPointExtension></extensions>
+      </trkpt>
+      <trkpt lat="0.0" lon="0.002">
+        <ele>105</ele><time>2024-05-01T07:30:30Z</time>
+      </trkpt>
+    </trkseg>
+    <trkseg>
+      <trkpt lat="1.0" lon="1.0"><ele>50</ele></trkpt>
+    </trkseg>
+  </trk>
+  <rte>
+    <rtept lat="2.0" lon="2.0"><ele>7</ele></rtept>
+    <rtept lat="2.0" lon="2.1"/>
+  </rte>
+</gpx>"#;
+
+    #[test]
+    fn test_segments_and_routes() {
+        let segments = HotlinePositionVec::from_gpx(GPX, GpxMetric::Elevation).unwrap();
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].raw_values, vec![100.0, 105.0, 105.0]);
+        assert_eq!(segments[1].positions[0].latlng, FlatPosition::new(1.0, 1.0));
+        assert_eq!(segments[2].raw_values, vec![7.0, 7.0]);
+
+        // one color scale for the whole document: 105 is the largest elevation
+        assert_eq!(segments[0].positions[1].alt, 1.0);
+        assert_eq!(segments[2].positions[0].alt, 7.0 / 105.0);
+    }
+
+    #[test]
+    fn test_extension_metrics() {
+        let points = &parse_segments(GPX).unwrap()[0];
+        let heart_rate = metric_values(points, GpxMetric::HeartRate).unwrap();
+        assert_eq!(heart_rate, vec![120.0, 130.0, 130.0]);
+
+        let cadence = metric_values(points, GpxMetric::Cadence).unwrap();
+        assert_eq!(cadence, vec![80.0, 80.0, 80.0]);
+
+        assert_eq!(
+            HotlinePositionVec::from_gpx(GPX, GpxMetric::HeartRate),
+            Err(GpxError::MissingMetric(GpxMetric::HeartRate))
+        );
+    }
+
+    #[test]
+    fn test_speed_and_grade() {
+        let points = &parse_segments(GPX).unwrap()[0];
+
+        let speed = metric_values(points, GpxMetric::Speed).unwrap();
+        assert!((speed[1] - 11.1195).abs() < 1e-3, "{speed:?}");
+        assert_eq!(speed[0], speed[1]);
+        assert!((speed[2] - 5.5597).abs() < 1e-3, "{speed:?}");
+
+        let grade = metric_values(points, GpxMetric::Grade).unwrap();
+        assert!((grade[1] - 4.4966).abs() < 1e-3, "{grade:?}");
+        assert_eq!(grade[2], 0.0);
+    }
+
+    #[test]
+    fn test_invalid_point() {
+        let gpx = r#"<gpx><trk><trkseg><trkpt lat="north" lon="1"/></trkseg></trk></gpx>"#;
+        assert!(matches!(
+            HotlinePositionVec::from_gpx(gpx, GpxMetric::Elevation),
+            Err(GpxError::InvalidPoint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_malformed_xml() {
+        let gpx = r#"<gpx><trk><trkseg></trk></gpx>"#;
+        assert!(matches!(
+            HotlinePositionVec::from_gpx(gpx, GpxMetric::Elevation),
+            Err(GpxError::Xml { .. })
+        ));
+    }
+}
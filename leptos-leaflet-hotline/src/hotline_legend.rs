@@ -0,0 +1,227 @@
+//! module for the hotline legend functional component
+use leptos::html::Div;
+use leptos::prelude::*;
+use leptos_leaflet::leaflet as L;
+use leptos_leaflet::prelude::LeafletMapContext;
+
+use crate::hotline::hotline_export::format_tick;
+use crate::{HotlineContext, HotlinePalette, HotlineTrack, DEFAULT_MAX, DEFAULT_MIN};
+
+/// inline style for the legend container, so it renders without a stylesheet
+const LEGEND_STYLE: &str = "padding: 4px 8px; min-width: 160px; background: rgba(255, 255, 255, 0.85); border-radius: 4px; font: 12px sans-serif; color: #333;";
+
+///
+/// Creates a legend for a hotline palette: a gradient bar with a tick label at
+/// each palette breakpoint
+///
+/// The legend is plain HTML/CSS and renders during SSR.  As on the hotline, each
+/// breakpoint stands for a normalized value in `min..=max`; labels show the raw value
+/// that the normalization of `positions` maps to it, or the normalized value when
+/// there are no positions.  Inside a `HotPolyline`, `positions`, `min` and `max`
+/// default to those of the hotline.  When `position` is set and the legend is inside
+/// a map container, it is shown as a leaflet control in that corner of the map.
+///
+/// # Arguments
+///
+/// * `palette` - palette of colors and breakpoints, as passed to `HotPolyline`
+/// * `positions` - positions whose normalization is inverted for the labels
+/// * `min` - `min` of the hotline, the normalized value at breakpoint 0 (default 0)
+/// * `max` - `max` of the hotline, the normalized value at breakpoint 1 (default 1)
+/// * `unit` - unit shown next to the labels, e.g. `"km/h"`
+/// * `decimals` - number of decimals in the labels; trailing zeros are trimmed if unset
+/// * `position` - leaflet control position: `"topleft"`, `"topright"`, `"bottomleft"` or `"bottomright"`
+///
+/// # Returns
+///
+/// `impl` [`leptos::IntoView`]
+///
+/// # Examples
+///
+/// ```ignore
+/// view! {
+///     <MapContainer style="height: 100vh" center=Position::new(40.2928, -105.6180) zoom=15.0 set_view=true>
+///         <HotPolyline positions=positions palette=HotlinePalette::viridis()>
+///             <HotlineLegend palette=HotlinePalette::viridis() unit="km/h" position="bottomright"/>
+///         </HotPolyline>
+///     </MapContainer>
+/// }
+/// ```
+///
+#[component]
+pub fn HotlineLegend(
+    #[prop(into)] palette: Signal<HotlinePalette>,
+    #[prop(optional, into)] positions: Option<Signal<HotlineTrack>>,
+    #[prop(optional, into)] min: Signal<Option<f64>>,
+    #[prop(optional, into)] max: Signal<Option<f64>>,
+    #[prop(optional, into)] unit: Signal<String>,
+    #[prop(optional)] decimals: Option<usize>,
+    #[prop(optional, into)] position: Signal<Option<String>>,
+) -> impl IntoView {
+    let legend_ref = NodeRef::<Div>::new();
+    let control = StoredValue::new_with_storage(None::<L::Control>);
+
+    let hotline = use_context::<HotlineContext>();
+    let positions = positions.or(hotline.map(|hotline| hotline.positions));
+    let domain = move || {
+        let lo = min
+            .get()
+            .or_else(|| hotline.and_then(|hotline| hotline.min.get()))
+            .unwrap_or(DEFAULT_MIN);
+        let hi = max
+            .get()
+            .or_else(|| hotline.and_then(|hotline| hotline.max.get()))
+            .unwrap_or(DEFAULT_MAX);
+        (lo, hi)
+    };
+
+    let ticks = move || {
+        let domain = domain();
+        let breakpoints: Vec<f64> = palette.with(|palette| {
+            legend_palette(palette)
+                .stops()
+                .iter()
+                .map(|stop| stop.breakpoint)
+                .collect()
+        });
+        let values = |track: Option<&HotlineTrack>| -> Vec<f64> {
+            breakpoints
+                .iter()
+                .map(|&breakpoint| breakpoint_value(breakpoint, domain, track))
+                .collect()
+        };
+        let values = match positions {
+            Some(positions) => positions.with(|track| values(Some(track))),
+            None => values(None),
+        };
+
+        breakpoints
+            .iter()
+            .zip(values)
+            .map(|(breakpoint, value)| {
+                let pct = breakpoint * 100.0;
+                let label = format_tick(value, decimals);
+                view! {
+                    <span
+                        class="hotline-legend-tick"
+                        style=format!("position: absolute; left: {pct}%; transform: translateX(-{pct}%); white-space: nowrap;")
+                    >
+                        {label}
+                    </span>
+                }
+            })
+            .collect_view()
+    };
+
+    Effect::new(move |_| {
+        remove_control(control);
+
+        let (Some(corner), Some(element)) = (position.get(), legend_ref.get()) else {
+            return;
+        };
+        let Some(map) = use_context::<LeafletMapContext>().and_then(|ctx| ctx.map()) else {
+            return;
+        };
+
+        let options = L::ControlOptions::new();
+        options.set_position(corner);
+        let legend_control = L::Control::new(&options);
+        legend_control.on_add(move |_| element.clone().into());
+        legend_control.add_to(&map);
+        control.set_value(Some(legend_control));
+    });
+
+    on_cleanup(move || remove_control(control));
+
+    view! {
+        <div node_ref=legend_ref class="hotline-legend" style=LEGEND_STYLE>
+            <div
+                class="hotline-legend-bar"
+                style=move || {
+                    palette
+                        .with(|palette| {
+                            format!("height: 10px; background: {};", css_linear_gradient(palette))
+                        })
+                }
+            ></div>
+            <div class="hotline-legend-ticks" style="position: relative; height: 1.4em;">
+                {ticks}
+            </div>
+            <Show when=move || unit.with(|unit| !unit.is_empty())>
+                <div class="hotline-legend-unit" style="text-align: right;">
+                    {unit}
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+/// removes the legend control from its map, if there is one
+#[inline]
+fn remove_control(control: StoredValue<Option<L::Control>, LocalStorage>) {
+    if let Some(legend_control) = control.try_update_value(Option::take).flatten() {
+        legend_control.remove();
+    }
+}
+
+/// the palette drawn by the legend; an empty palette is drawn as the default one,
+/// as `HotPolyline` does
+#[inline]
+fn legend_palette(palette: &HotlinePalette) -> HotlinePalette {
+    if palette.is_empty() {
+        HotlinePalette::default()
+    } else {
+        palette.clone()
+    }
+}
+
+/// raw value labelled at a breakpoint: the normalized value at the breakpoint of
+/// `lo..=hi`, mapped back through the normalization of `track` if there is one
+#[inline]
+fn breakpoint_value(breakpoint: f64, (lo, hi): (f64, f64), track: Option<&HotlineTrack>) -> f64 {
+    let normalized = lo + breakpoint * (hi - lo);
+    track.map_or(normalized, |track| track.raw_value_for(normalized))
+}
+
+/// CSS `linear-gradient` matching the palette gradient leaflet-hotline draws
+fn css_linear_gradient(palette: &HotlinePalette) -> String {
+    let stops: Vec<String> = legend_palette(palette)
+        .stops()
+        .iter()
+        .map(|stop| format!("{} {}%", stop.color, stop.breakpoint * 100.0))
+        .collect();
+    format!("linear-gradient(to right, {})", stops.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css_linear_gradient() {
+        let palette = HotlinePalette::new(&[("green", 0.0), ("#ff000080", 0.25), ("red", 1.0)]);
+        assert_eq!(
+            css_linear_gradient(&palette),
+            "linear-gradient(to right, #008000 0%, #ff000080 25%, #ff0000 100%)"
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_value() {
+        let track = HotlineTrack::from(crate::HotlinePositionVec::new(&[
+            (40.0, -105.0, 20.0),
+            (40.1, -105.1, 80.0),
+        ]));
+        assert_eq!(breakpoint_value(0.5, (0.0, 1.0), Some(&track)), 40.0);
+        assert_eq!(breakpoint_value(1.0, (0.0, 0.5), Some(&track)), 40.0);
+        assert_eq!(breakpoint_value(0.5, (0.0, 1.0), None), 0.5);
+    }
+
+    #[test]
+    fn test_css_linear_gradient_empty_palette() {
+        let empty = HotlinePalette::from_stops(Vec::new()).unwrap();
+        assert_eq!(
+            css_linear_gradient(&empty),
+            css_linear_gradient(&HotlinePalette::default())
+        );
+    }
+}
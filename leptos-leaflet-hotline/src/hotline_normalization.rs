@@ -0,0 +1,454 @@
+//! module for normalizing the values visualized along a hotline
+use std::fmt;
+
+///
+/// strategy used to map raw values onto the range used by the palette
+///
+/// Non-finite raw values (NaN, infinities) are ignored when computing statistics
+/// and are passed through unchanged.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Normalization {
+    /// use the raw values as they are
+    None,
+    /// map the smallest value to 0 and the largest to 1
+    MinMax,
+    /// divide every value by the largest absolute value, giving values in -1..=1
+    #[default]
+    DivideByMax,
+    /// subtract the mean and divide by the standard deviation;
+    /// pair with hotline `min`/`max` such as -2 and 2
+    ZScore,
+    /// clamp to the given lower and upper percentiles (0..=100), then map to 0..=1,
+    /// e.g. `PercentileClamp { lower: 2.0, upper: 98.0 }` to ignore outliers
+    PercentileClamp {
+        /// lower percentile
+        lower: f64,
+        /// upper percentile
+        upper: f64,
+    },
+    /// clamp to a fixed `[lo, hi]` domain, then map to 0..=1,
+    /// e.g. a heart rate zone or a temperature range shared across tracks
+    FixedDomain {
+        /// value mapped to 0
+        lo: f64,
+        /// value mapped to 1
+        hi: f64,
+    },
+    /// subtract `offset` and divide by `scale` without clamping, e.g. another
+    /// strategy fixed over a whole activity by [`Normalization::resolve`]
+    Linear {
+        /// value mapped to 0
+        offset: f64,
+        /// difference between the values mapped to 0 and 1
+        scale: f64,
+    },
+}
+
+///
+/// error returned when a [`Normalization`] has parameters it cannot be applied with
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum NormalizationError {
+    /// a percentile or domain bound is NaN or infinite
+    NonFiniteBound {
+        /// lower percentile, `lo` or `offset` given
+        lower: f64,
+        /// upper percentile, `hi` or `scale` given
+        upper: f64,
+    },
+}
+
+impl fmt::Display for NormalizationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFiniteBound { lower, upper } => {
+                write!(f, "normalization bounds [{lower}, {upper}] must be finite")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NormalizationError {}
+
+/// implement normalization of raw values for [`Normalization`]
+impl Normalization {
+    ///
+    /// check that the percentiles or domain bounds are finite
+    ///
+    /// # Returns
+    /// [`Result<(), NormalizationError>`]
+    ///
+    pub fn validate(&self) -> Result<(), NormalizationError> {
+        match *self {
+            Self::PercentileClamp {
+                lower: lo,
+                upper: hi,
+            }
+            | Self::FixedDomain { lo, hi }
+            | Self::Linear {
+                offset: lo,
+                scale: hi,
+            } if !(lo.is_finite() && hi.is_finite()) => Err(NormalizationError::NonFiniteBound {
+                lower: lo,
+                upper: hi,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    ///
+    /// normalize raw values with this strategy
+    ///
+    /// Degenerate input never produces NaN: when all values are equal (or zero for
+    /// [`Normalization::DivideByMax`]) every finite value normalizes to 0.  Values
+    /// are returned unchanged if [`Normalization::validate`] fails.
+    ///
+    /// # Returns
+    /// [`Vec<f64>`] normalized values, same length as `values`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::Normalization;
+    ///
+    /// let temperatures = [-10.0, 0.0, 30.0];
+    /// assert_eq!(Normalization::MinMax.apply(&temperatures), vec![0.0, 0.25, 1.0]);
+    /// ```
+    ///
+    #[must_use]
+    pub fn apply(&self, values: &[f64]) -> Vec<f64> {
+        let affine = self.affine(values);
+        values
+            .iter()
+            .map(|&v| if v.is_finite() { affine.forward(v) } else { v })
+            .collect()
+    }
+
+    ///
+    /// raw value that normalizes to `normalized` over `values`, the inverse of
+    /// [`Normalization::apply`]
+    ///
+    /// Values clamped by [`Normalization::PercentileClamp`] or
+    /// [`Normalization::FixedDomain`] invert to the clamp bounds.
+    ///
+    /// # Returns
+    /// [`f64`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::Normalization;
+    ///
+    /// let temperatures = [-10.0, 0.0, 30.0];
+    /// assert_eq!(Normalization::MinMax.invert(&temperatures, 0.25), 0.0);
+    /// ```
+    ///
+    #[must_use]
+    #[inline]
+    pub fn invert(&self, values: &[f64], normalized: f64) -> f64 {
+        self.affine(values).inverse(normalized)
+    }
+
+    ///
+    /// fix the statistics of this strategy over `values`, e.g. all the laps of an
+    /// activity, so that other values are mapped the same way
+    ///
+    /// # Returns
+    /// [`Normalization`] that does not depend on the values it is applied to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::Normalization;
+    ///
+    /// let activity = [100.0, 150.0, 200.0];
+    /// let resolved = Normalization::MinMax.resolve(&activity);
+    /// assert_eq!(resolved.apply(&[150.0]), vec![0.5]);
+    /// ```
+    ///
+    #[must_use]
+    pub fn resolve(&self, values: &[f64]) -> Self {
+        let affine = self.affine(values);
+        match affine.clamp {
+            Some((lo, hi)) => Self::FixedDomain { lo, hi },
+            None if affine.offset == 0.0 && affine.scale == 1.0 => Self::None,
+            None => Self::Linear {
+                offset: affine.offset,
+                scale: affine.scale,
+            },
+        }
+    }
+
+    /// true if the mapping depends on statistics of the values it is applied to
+    #[inline]
+    pub(crate) fn depends_on_values(&self) -> bool {
+        matches!(
+            self,
+            Self::MinMax | Self::DivideByMax | Self::ZScore | Self::PercentileClamp { .. }
+        )
+    }
+
+    /// the linear map this normalization applies to the finite `values`
+    fn affine(&self, values: &[f64]) -> Affine {
+        let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        if finite.is_empty() || self.validate().is_err() {
+            return Affine::IDENTITY;
+        }
+
+        match *self {
+            Self::None => Affine::IDENTITY,
+            Self::MinMax => {
+                let (lo, hi) = min_max(&finite);
+                Affine::domain(lo, hi)
+            }
+            Self::DivideByMax => Affine {
+                offset: 0.0,
+                scale: finite.iter().fold(0.0_f64, |acc, v| acc.max(v.abs())),
+                clamp: None,
+            },
+            Self::ZScore => {
+                let count = finite.len() as f64;
+                let mean = finite.iter().sum::<f64>() / count;
+                let variance = finite.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+                Affine {
+                    offset: mean,
+                    scale: variance.sqrt(),
+                    clamp: None,
+                }
+            }
+            Self::PercentileClamp { lower, upper } => {
+                let mut sorted = finite;
+                sorted.sort_by(f64::total_cmp);
+                let lo = percentile(&sorted, lower);
+                let hi = percentile(&sorted, upper);
+                Affine {
+                    clamp: Some((lo, hi)),
+                    ..Affine::domain(lo, hi)
+                }
+            }
+            Self::FixedDomain { lo, hi } => Affine {
+                clamp: Some((lo, hi)),
+                ..Affine::domain(lo, hi)
+            },
+            Self::Linear { offset, scale } => Affine {
+                offset,
+                scale,
+                clamp: None,
+            },
+        }
+    }
+}
+
+/// `(v - offset) / scale` after clamping, giving 0 when `scale` is 0
+#[derive(Debug, Clone, Copy)]
+struct Affine {
+    offset: f64,
+    scale: f64,
+    clamp: Option<(f64, f64)>,
+}
+
+impl Affine {
+    /// leaves values unchanged
+    const IDENTITY: Self = Self {
+        offset: 0.0,
+        scale: 1.0,
+        clamp: None,
+    };
+
+    /// maps `lo..=hi` onto `0..=1`
+    #[inline]
+    fn domain(lo: f64, hi: f64) -> Self {
+        Self {
+            offset: lo,
+            scale: hi - lo,
+            clamp: None,
+        }
+    }
+
+    #[inline]
+    fn forward(self, v: f64) -> f64 {
+        let v = match self.clamp {
+            Some((lo, hi)) => v.clamp(lo.min(hi), hi.max(lo)),
+            None => v,
+        };
+        if self.scale == 0.0 {
+            0.0
+        } else {
+            (v - self.offset) / self.scale
+        }
+    }
+
+    #[inline]
+    fn inverse(self, normalized: f64) -> f64 {
+        let v = self.offset + normalized * self.scale;
+        match self.clamp {
+            Some((lo, hi)) => v.clamp(lo.min(hi), hi.max(lo)),
+            None => v,
+        }
+    }
+}
+
+/// smallest and largest of a non-empty slice of finite values
+#[inline]
+fn min_max(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        })
+}
+
+/// percentile (0..=100) of sorted values, interpolating linearly between ranks
+#[inline]
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let rank = (pct.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    let frac = rank - below as f64;
+    sorted[below] + (sorted[above] - sorted[below]) * frac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HotlinePositionVec;
+
+    #[test]
+    fn test_divide_by_max_all_zero() {
+        let normalized = Normalization::DivideByMax.apply(&[0.0, 0.0, 0.0]);
+        assert_eq!(normalized, vec![0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_divide_by_max_negative() {
+        let normalized = Normalization::DivideByMax.apply(&[-4.0, 2.0, 1.0]);
+        assert_eq!(normalized, vec![-1.0, 0.5, 0.25]);
+    }
+
+    #[test]
+    fn test_min_max_constant() {
+        let normalized = Normalization::MinMax.apply(&[5.0, 5.0]);
+        assert_eq!(normalized, vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_z_score() {
+        let normalized = Normalization::ZScore.apply(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(normalized[0], -1.5);
+        assert_eq!(normalized[7], 2.0);
+    }
+
+    #[test]
+    fn test_percentile_clamp() {
+        let mut values: Vec<f64> = (0..=100).map(f64::from).collect();
+        values.push(10_000.0);
+        let normalized = Normalization::PercentileClamp {
+            lower: 0.0,
+            upper: 99.0,
+        }
+        .apply(&values);
+        assert_eq!(normalized[0], 0.0);
+        assert_eq!(*normalized.last().unwrap(), 1.0);
+        assert!(normalized[50] > 0.49 && normalized[50] < 0.51);
+    }
+
+    #[test]
+    fn test_fixed_domain() {
+        let normalized =
+            Normalization::FixedDomain { lo: 10.0, hi: 20.0 }.apply(&[0.0, 15.0, 30.0]);
+        assert_eq!(normalized, vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_non_finite_bounds() {
+        let nan_domain = Normalization::FixedDomain {
+            lo: f64::NAN,
+            hi: f64::NAN,
+        };
+        assert!(matches!(
+            nan_domain.validate(),
+            Err(NormalizationError::NonFiniteBound { .. })
+        ));
+        assert_eq!(nan_domain.apply(&[1.0, 2.0]), vec![1.0, 2.0]);
+        assert!(Normalization::PercentileClamp {
+            lower: 0.0,
+            upper: f64::INFINITY
+        }
+        .validate()
+        .is_err());
+        assert_eq!(Normalization::MinMax.validate(), Ok(()));
+
+        let points = [(40.0, -105.0, 1.0), (40.1, -105.1, 2.0)];
+        let fallback = HotlinePositionVec::with_normalization(&points, nan_domain);
+        assert_eq!(fallback.normalization, Normalization::None);
+        assert_eq!(fallback.positions[1].alt, 2.0);
+        assert!(matches!(
+            HotlinePositionVec::try_with_normalization(&points, nan_domain),
+            Err(NormalizationError::NonFiniteBound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invert() {
+        let values = [2.0, 4.0, 6.0, 8.0, 10.0];
+        for normalization in [
+            Normalization::None,
+            Normalization::MinMax,
+            Normalization::DivideByMax,
+            Normalization::ZScore,
+            Normalization::FixedDomain { lo: 0.0, hi: 20.0 },
+        ] {
+            for (raw, normalized) in values.iter().zip(normalization.apply(&values)) {
+                let inverted = normalization.invert(&values, normalized);
+                assert!((inverted - raw).abs() < 1e-9, "{normalization:?}");
+            }
+        }
+        let clamp = Normalization::PercentileClamp {
+            lower: 25.0,
+            upper: 75.0,
+        };
+        assert_eq!(clamp.invert(&values, 0.0), 4.0);
+        assert_eq!(clamp.invert(&values, 2.0), 8.0);
+        assert_eq!(Normalization::MinMax.invert(&[3.0, 3.0], 0.5), 3.0);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let activity = [-4.0, 2.0, 8.0, 30.0];
+        let lap = [2.0, 8.0];
+        for normalization in [
+            Normalization::None,
+            Normalization::MinMax,
+            Normalization::DivideByMax,
+            Normalization::ZScore,
+            Normalization::PercentileClamp {
+                lower: 10.0,
+                upper: 90.0,
+            },
+            Normalization::FixedDomain { lo: 0.0, hi: 10.0 },
+        ] {
+            let resolved = normalization.resolve(&activity);
+            assert_eq!(resolved.apply(&activity), normalization.apply(&activity));
+            assert_eq!(resolved.resolve(&lap), resolved, "{normalization:?}");
+        }
+        assert_eq!(
+            Normalization::DivideByMax.resolve(&activity),
+            Normalization::Linear {
+                offset: 0.0,
+                scale: 30.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_non_finite_passthrough() {
+        let normalized = Normalization::MinMax.apply(&[0.0, f64::NAN, 2.0]);
+        assert_eq!(normalized[0], 0.0);
+        assert!(normalized[1].is_nan());
+        assert_eq!(normalized[2], 1.0);
+    }
+}
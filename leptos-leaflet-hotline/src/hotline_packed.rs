@@ -0,0 +1,555 @@
+//! module for the compact columnar binary encoding of hotline positions
+use std::fmt;
+use std::future::Future;
+
+use super::hotline_normalization::Normalization;
+use super::hotline_position::HotlinePositionVec;
+
+/// magic bytes and format version at the start of every packed track
+const MAGIC: &[u8; 4] = b"HLP\x01";
+/// coordinates are stored as integer multiples of 1e-7 degrees, about 1 cm
+const COORDINATE_SCALE: f64 = 1e7;
+/// most chunks reserved up front when fetching, whatever `total` the server reports
+const MAX_RESERVED_CHUNKS: usize = 1024;
+
+///
+/// hotline positions packed into a compact columnar binary form
+///
+/// The latitudes and longitudes are stored as delta-encoded varints at 1e-7 degree
+/// resolution, and the raw values as `f32`, which typically takes 8-10 bytes per
+/// point instead of the ~50 bytes of JSON.  The normalization is stored alongside
+/// and reapplied when unpacking.
+///
+/// With the `serde` feature this serializes as a base64 string in human-readable
+/// formats such as JSON and as raw bytes otherwise, so it can be returned from a
+/// Leptos `#[server]` function.
+///
+/// # Examples
+///
+/// ```
+/// use leptos_leaflet_hotline::HotlinePositionVec;
+///
+/// let track = HotlinePositionVec::new(&[(40.2928, -105.618, 3500.0), (40.2928, -105.619, 3510.0)]);
+/// let packed = track.to_packed();
+/// let unpacked = HotlinePositionVec::from_packed(&packed).unwrap();
+/// assert_eq!(unpacked.raw_values, vec![3500.0, 3510.0]);
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PackedPositions(Vec<u8>);
+
+///
+/// one chunk of a packed track, for sending very long tracks in several requests
+///
+/// # Fields
+/// * `index` [`usize`] position of the chunk in the track, starting at 0
+/// * `total` [`usize`] number of chunks in the track
+/// * `positions` [`PackedPositions`] points of the chunk
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct PackedChunk {
+    /// position of the chunk in the track, starting at 0
+    pub index: usize,
+    /// number of chunks in the track
+    pub total: usize,
+    /// points of the chunk
+    pub positions: PackedPositions,
+}
+
+///
+/// error returned when packed positions cannot be unpacked
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PackedError {
+    /// the data does not start with the packed track header, or uses an unknown version
+    InvalidHeader,
+    /// the data ends before all points are read
+    Truncated,
+    /// the normalization tag is unknown or its bounds are not finite
+    InvalidNormalization(u8),
+    /// a chunk is missing, duplicated or disagrees on the number of chunks
+    MissingChunk {
+        /// index of the first missing chunk
+        index: usize,
+    },
+}
+
+impl fmt::Display for PackedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid packed hotline header"),
+            Self::Truncated => write!(f, "packed hotline data is truncated"),
+            Self::InvalidNormalization(tag) => {
+                write!(f, "unknown packed hotline normalization {tag}")
+            }
+            Self::MissingChunk { index } => write!(f, "packed hotline chunk {index} is missing"),
+        }
+    }
+}
+
+impl std::error::Error for PackedError {}
+
+/// implement construction for [`PackedChunk`]
+impl PackedChunk {
+    ///
+    /// wrap the packed points of one chunk, e.g. a slice of a track packed with
+    /// [`HotlinePositionVec::to_packed`] on its own
+    ///
+    /// # Returns
+    /// [`PackedChunk`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn new(index: usize, total: usize, positions: PackedPositions) -> Self {
+        Self {
+            index,
+            total,
+            positions,
+        }
+    }
+}
+
+/// implement access to the encoded bytes for [`PackedPositions`]
+impl PackedPositions {
+    ///
+    /// wrap bytes produced by [`PackedPositions::as_bytes`], e.g. read from a cache;
+    /// the bytes are validated when unpacked
+    ///
+    /// # Returns
+    /// [`PackedPositions`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// encoded bytes
+    #[must_use]
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// consume into the encoded bytes
+    #[must_use]
+    #[inline]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+/// implement packing and chunking for [`HotlinePositionVec`]
+impl HotlinePositionVec {
+    ///
+    /// pack the positions, raw values and normalization into the columnar binary form
+    ///
+    /// # Returns
+    /// [`PackedPositions`]
+    ///
+    #[must_use]
+    pub fn to_packed(&self) -> PackedPositions {
+        encode(&self.raw_points(), self.normalization)
+    }
+
+    ///
+    /// unpack positions packed with [`HotlinePositionVec::to_packed`]
+    ///
+    /// # Returns
+    /// [`HotlinePositionVec`], or [`PackedError`] if the data is malformed
+    ///
+    pub fn from_packed(packed: &PackedPositions) -> Result<Self, PackedError> {
+        let (normalization, points) = decode(packed.as_bytes())?;
+        Ok(Self::with_normalization(&points, normalization))
+    }
+
+    ///
+    /// split the track into packed chunks of at most `points_per_chunk` points
+    ///
+    /// An empty track gives a single empty chunk, so the receiver always learns
+    /// the number of chunks from the first one.
+    ///
+    /// # Returns
+    /// [`Vec<PackedChunk>`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::HotlinePositionVec;
+    ///
+    /// let points: Vec<_> = (0..10).map(|i| (40.0, -105.0 + i as f64 * 1e-4, i as f64)).collect();
+    /// let track = HotlinePositionVec::new(&points);
+    ///
+    /// let chunks = track.to_packed_chunks(4);
+    /// assert_eq!(chunks.len(), 3);
+    /// let reassembled = HotlinePositionVec::from_packed_chunks(chunks).unwrap();
+    /// assert_eq!(reassembled.raw_values, track.raw_values);
+    /// ```
+    ///
+    #[must_use]
+    pub fn to_packed_chunks(&self, points_per_chunk: usize) -> Vec<PackedChunk> {
+        let points = self.raw_points();
+        let points_per_chunk = points_per_chunk.max(1);
+        let total = points.len().div_ceil(points_per_chunk).max(1);
+        (0..total)
+            .map(|index| {
+                let start = (index * points_per_chunk).min(points.len());
+                let end = (start + points_per_chunk).min(points.len());
+                PackedChunk::new(
+                    index,
+                    total,
+                    encode(&points[start..end], self.normalization),
+                )
+            })
+            .collect()
+    }
+
+    ///
+    /// reassemble a track from all of its packed chunks, in any order
+    ///
+    /// The values are normalized over the whole track, using the normalization of
+    /// the first chunk.
+    ///
+    /// # Returns
+    /// [`HotlinePositionVec`], or [`PackedError`] if a chunk is malformed or missing
+    ///
+    pub fn from_packed_chunks(
+        chunks: impl IntoIterator<Item = PackedChunk>,
+    ) -> Result<Self, PackedError> {
+        let mut chunks: Vec<PackedChunk> = chunks.into_iter().collect();
+        chunks.sort_by_key(|chunk| chunk.index);
+        let total = chunks.first().map_or(1, |chunk| chunk.total);
+        if let Some(index) = (0..total).find(|&index| {
+            chunks
+                .get(index)
+                .is_none_or(|chunk| chunk.index != index || chunk.total != total)
+        }) {
+            return Err(PackedError::MissingChunk { index });
+        }
+        if chunks.len() > total {
+            return Err(PackedError::MissingChunk { index: total });
+        }
+
+        let mut normalization = None;
+        let mut points = Vec::new();
+        for chunk in &chunks {
+            let (chunk_normalization, chunk_points) = decode(chunk.positions.as_bytes())?;
+            normalization.get_or_insert(chunk_normalization);
+            points.extend(chunk_points);
+        }
+        Ok(Self::with_normalization(
+            &points,
+            normalization.unwrap_or_default(),
+        ))
+    }
+}
+
+///
+/// fetch every chunk of a packed track, e.g. with a Leptos `#[server]` function
+/// taking the chunk index
+///
+/// The first chunk is fetched to learn the number of chunks, then the rest in order.
+/// Pass the result to [`HotlinePositionVec::from_packed_chunks`].
+///
+/// # Returns
+/// [`Vec<PackedChunk>`], or the first error returned by `fetch`
+///
+/// # Examples
+///
+/// ```ignore
+/// #[server]
+/// async fn track_chunk(index: usize) -> Result<PackedChunk, ServerFnError> {
+///     let chunks = load_track().await?.to_packed_chunks(10_000);
+///     chunks.into_iter().nth(index).ok_or_else(|| ServerFnError::new("no such chunk"))
+/// }
+///
+/// let track = Resource::new(|| (), |_| fetch_packed_chunks(track_chunk));
+/// ```
+///
+pub async fn fetch_packed_chunks<F, Fut, E>(fetch: F) -> Result<Vec<PackedChunk>, E>
+where
+    F: Fn(usize) -> Fut,
+    Fut: Future<Output = Result<PackedChunk, E>>,
+{
+    let first = fetch(0).await?;
+    let mut chunks = Vec::with_capacity(first.total.min(MAX_RESERVED_CHUNKS));
+    let total = first.total;
+    chunks.push(first);
+    for index in 1..total {
+        chunks.push(fetch(index).await?);
+    }
+    Ok(chunks)
+}
+
+/// encode (lat, lng, raw value) points with their normalization
+fn encode(points: &[(f64, f64, f64)], normalization: Normalization) -> PackedPositions {
+    let mut bytes = Vec::with_capacity(MAGIC.len() + 18 + points.len() * 10);
+    bytes.extend_from_slice(MAGIC);
+
+    let (tag, params) = match normalization {
+        Normalization::None => (0, None),
+        Normalization::MinMax => (1, None),
+        Normalization::DivideByMax => (2, None),
+        Normalization::ZScore => (3, None),
+        Normalization::PercentileClamp { lower, upper } => (4, Some((lower, upper))),
+        Normalization::FixedDomain { lo, hi } => (5, Some((lo, hi))),
+        Normalization::Linear { offset, scale } => (6, Some((offset, scale))),
+    };
+    bytes.push(tag);
+    if let Some((first, second)) = params {
+        bytes.extend_from_slice(&first.to_le_bytes());
+        bytes.extend_from_slice(&second.to_le_bytes());
+    }
+
+    write_varint(&mut bytes, points.len() as u64);
+    for column in [|p: &(f64, f64, f64)| p.0, |p: &(f64, f64, f64)| p.1] {
+        let mut previous = 0_i64;
+        for point in points {
+            let scaled = (column(point) * COORDINATE_SCALE).round() as i64;
+            // wrapping, so coordinates far outside ±180° still round-trip
+            let delta = scaled.wrapping_sub(previous);
+            write_varint(&mut bytes, ((delta << 1) ^ (delta >> 63)) as u64);
+            previous = scaled;
+        }
+    }
+    for point in points {
+        bytes.extend_from_slice(&(point.2 as f32).to_le_bytes());
+    }
+    PackedPositions(bytes)
+}
+
+/// normalization and (lat, lng, raw value) points read from packed bytes
+type DecodedPoints = (Normalization, Vec<(f64, f64, f64)>);
+
+/// decode the normalization and (lat, lng, raw value) points
+fn decode(bytes: &[u8]) -> Result<DecodedPoints, PackedError> {
+    let mut rest = bytes
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or(PackedError::InvalidHeader)?;
+
+    let (&tag, after_tag) = rest.split_first().ok_or(PackedError::Truncated)?;
+    rest = after_tag;
+    let mut param = || -> Result<f64, PackedError> {
+        let (value, after) = rest
+            .split_first_chunk::<8>()
+            .ok_or(PackedError::Truncated)?;
+        rest = after;
+        Ok(f64::from_le_bytes(*value))
+    };
+    let normalization = match tag {
+        0 => Normalization::None,
+        1 => Normalization::MinMax,
+        2 => Normalization::DivideByMax,
+        3 => Normalization::ZScore,
+        4 => Normalization::PercentileClamp {
+            lower: param()?,
+            upper: param()?,
+        },
+        5 => Normalization::FixedDomain {
+            lo: param()?,
+            hi: param()?,
+        },
+        6 => Normalization::Linear {
+            offset: param()?,
+            scale: param()?,
+        },
+        tag => return Err(PackedError::InvalidNormalization(tag)),
+    };
+    normalization
+        .validate()
+        .map_err(|_| PackedError::InvalidNormalization(tag))?;
+
+    let count = usize::try_from(read_varint(&mut rest)?).map_err(|_| PackedError::Truncated)?;
+    // every point takes at least 6 bytes, so reject counts the data cannot hold
+    if count > rest.len() / 6 {
+        return Err(PackedError::Truncated);
+    }
+    let mut columns = [Vec::with_capacity(count), Vec::with_capacity(count)];
+    for column in &mut columns {
+        let mut previous = 0_i64;
+        for _ in 0..count {
+            let zigzag = read_varint(&mut rest)?;
+            previous = previous.wrapping_add((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64));
+            column.push(previous as f64 / COORDINATE_SCALE);
+        }
+    }
+    let [lats, lngs] = columns;
+
+    let points = lats
+        .into_iter()
+        .zip(lngs)
+        .map(|(lat, lng)| {
+            let (value, after) = rest
+                .split_first_chunk::<4>()
+                .ok_or(PackedError::Truncated)?;
+            rest = after;
+            Ok((lat, lng, f64::from(f32::from_le_bytes(*value))))
+        })
+        .collect::<Result<Vec<_>, PackedError>>()?;
+    Ok((normalization, points))
+}
+
+/// append an unsigned LEB128 varint
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// read an unsigned LEB128 varint, advancing `bytes`
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, PackedError> {
+    let mut value = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or(PackedError::Truncated)?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(PackedError::Truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(len: usize) -> HotlinePositionVec {
+        let points: Vec<_> = (0..len)
+            .map(|i| {
+                let i = i as f64;
+                (40.0 + i * 1e-5, -105.0 - i * 2e-5, (i * 0.5).sin() * 100.0)
+            })
+            .collect();
+        HotlinePositionVec::with_normalization(
+            &points,
+            Normalization::PercentileClamp {
+                lower: 5.0,
+                upper: 95.0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let original = track(1_000);
+        let packed = original.to_packed();
+        assert!(
+            packed.as_bytes().len() < 1_000 * 10,
+            "{}",
+            packed.as_bytes().len()
+        );
+
+        let unpacked = HotlinePositionVec::from_packed(&packed).unwrap();
+        assert_eq!(unpacked.normalization, original.normalization);
+        for (a, b) in unpacked.positions.iter().zip(&original.positions) {
+            assert!((a.latlng.lat - b.latlng.lat).abs() < 1e-7);
+            assert!((a.latlng.lng - b.latlng.lng).abs() < 1e-7);
+        }
+        for (a, b) in unpacked.raw_values.iter().zip(&original.raw_values) {
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_chunks() {
+        let original = track(25);
+        let mut chunks = original.to_packed_chunks(10);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|chunk| chunk.total == 3));
+
+        chunks.swap(0, 2);
+        let reassembled = HotlinePositionVec::from_packed_chunks(chunks.clone()).unwrap();
+        assert_eq!(reassembled.positions.len(), 25);
+        assert_eq!(
+            reassembled,
+            HotlinePositionVec::from_packed(&original.to_packed()).unwrap()
+        );
+
+        chunks.remove(1);
+        assert_eq!(
+            HotlinePositionVec::from_packed_chunks(chunks),
+            Err(PackedError::MissingChunk { index: 1 })
+        );
+
+        let empty = HotlinePositionVec::new(&[]).to_packed_chunks(10);
+        assert_eq!(empty.len(), 1);
+        assert!(HotlinePositionVec::from_packed_chunks(empty)
+            .unwrap()
+            .positions
+            .is_empty());
+    }
+
+    #[test]
+    fn test_fetch_packed_chunks() {
+        let chunks = track(25).to_packed_chunks(10);
+        let fetched = std::cell::RefCell::new(Vec::new());
+        let fetch = |index: usize| {
+            fetched.borrow_mut().push(index);
+            let chunk = chunks.get(index).cloned().ok_or("no such chunk");
+            async move { chunk }
+        };
+
+        let future = std::pin::pin!(fetch_packed_chunks(fetch));
+        let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+        let std::task::Poll::Ready(result) = future.poll(&mut context) else {
+            panic!("fetch_packed_chunks should not wait on ready futures");
+        };
+        assert_eq!(result, Ok(chunks.clone()));
+        assert_eq!(*fetched.borrow(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let packed = track(10).to_packed().into_bytes();
+        assert_eq!(
+            HotlinePositionVec::from_packed(&PackedPositions::from_bytes(packed[..30].to_vec())),
+            Err(PackedError::Truncated)
+        );
+        assert_eq!(
+            HotlinePositionVec::from_packed(&PackedPositions::from_bytes(b"JSON".to_vec())),
+            Err(PackedError::InvalidHeader)
+        );
+        let mut unknown = MAGIC.to_vec();
+        unknown.push(9);
+        assert_eq!(
+            HotlinePositionVec::from_packed(&PackedPositions::from_bytes(unknown)),
+            Err(PackedError::InvalidNormalization(9))
+        );
+        let mut nan_domain = MAGIC.to_vec();
+        nan_domain.push(5);
+        nan_domain.extend_from_slice(&f64::NAN.to_le_bytes());
+        nan_domain.extend_from_slice(&f64::NAN.to_le_bytes());
+        nan_domain.push(0);
+        assert_eq!(
+            HotlinePositionVec::from_packed(&PackedPositions::from_bytes(nan_domain)),
+            Err(PackedError::InvalidNormalization(5))
+        );
+
+        // deltas whose running sum overflows i64
+        let mut overflowing = MAGIC.to_vec();
+        overflowing.push(0);
+        write_varint(&mut overflowing, 2);
+        for zigzag in [u64::MAX - 1, u64::MAX - 1, 0, 0] {
+            write_varint(&mut overflowing, zigzag);
+        }
+        overflowing.extend_from_slice(&[0; 8]);
+        assert!(HotlinePositionVec::from_packed(&PackedPositions::from_bytes(overflowing)).is_ok());
+    }
+
+    #[test]
+    fn test_out_of_range_coordinates() {
+        let track = HotlinePositionVec::new(&[(-1e300, 0.0, 1.0), (1e300, 0.0, 2.0)]);
+        let unpacked = HotlinePositionVec::from_packed(&track.to_packed()).unwrap();
+        assert_eq!(unpacked.raw_values, vec![1.0, 2.0]);
+        assert_eq!(
+            unpacked.positions[1].latlng.lat,
+            i64::MAX as f64 / COORDINATE_SCALE
+        );
+    }
+}
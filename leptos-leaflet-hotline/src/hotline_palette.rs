@@ -1,10 +1,12 @@
 //! module for hotline palette data structure and functions
-use std::collections::HashMap;
+use std::fmt;
+
+use super::hotline_color::{Color, ColorError, Interpolation};
 
 /// the default color palette used
 /// hotline options will be created with the default palette
 /// if no palette is available.  \
-/// Colors can be color names or hex codes, breakpoints
+/// Colors can be any CSS color accepted by [`Color::parse`], breakpoints
 /// indicate the relative cutoff in the values for each color.
 const DEFAULT_PALETTE_VALUES: &[(&str, f64)] = &[
     ("green", 0.0),
@@ -14,37 +16,327 @@ const DEFAULT_PALETTE_VALUES: &[(&str, f64)] = &[
 ];
 
 ///
-/// struct data type for hotline palette
+/// struct data type for a single palette color stop
 ///
 /// # Fields
-/// * `palette` [`HashMap<String, f64>`] mapping of colors to breakpoints
+/// * `color` [`Color`] parsed color
+/// * `breakpoint` [`f64`] relative cutoff in 0..=1 where the color applies
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ColorStop {
+    pub color: Color,
+    pub breakpoint: f64,
+}
+
+/// implement constructor for [`ColorStop`]
+impl ColorStop {
+    ///
+    /// construct a new [`ColorStop`]
+    ///
+    /// # Returns
+    /// [`ColorStop`]
+    ///
+    #[must_use]
+    #[inline]
+    pub const fn new(color: Color, breakpoint: f64) -> Self {
+        Self { color, breakpoint }
+    }
+}
+
+///
+/// error returned when a [`HotlinePalette`] is constructed from invalid color stops
 ///
 #[derive(Debug, Clone, PartialEq)]
 #[non_exhaustive]
+pub enum PaletteError {
+    /// a color is not a valid CSS color
+    InvalidColor(ColorError),
+    /// a breakpoint is NaN
+    NanBreakpoint {
+        /// color of the offending stop
+        color: Color,
+    },
+    /// a breakpoint is outside 0..=1
+    BreakpointOutOfRange {
+        /// color of the offending stop
+        color: Color,
+        /// the breakpoint given
+        breakpoint: f64,
+    },
+    /// two stops share the same breakpoint
+    DuplicateBreakpoint {
+        /// the breakpoint given more than once
+        breakpoint: f64,
+    },
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidColor(err) => write!(f, "invalid palette color: {err}"),
+            Self::NanBreakpoint { color } => {
+                write!(f, "breakpoint for color '{color}' is NaN")
+            }
+            Self::BreakpointOutOfRange { color, breakpoint } => write!(
+                f,
+                "breakpoint {breakpoint} for color '{color}' is outside 0..=1"
+            ),
+            Self::DuplicateBreakpoint { breakpoint } => {
+                write!(f, "breakpoint {breakpoint} is used more than once")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidColor(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ColorError> for PaletteError {
+    #[inline]
+    fn from(value: ColorError) -> Self {
+        Self::InvalidColor(value)
+    }
+}
+
+///
+/// struct data type for hotline palette
+///
+/// Color stops are kept sorted by breakpoint, so the same color may appear at
+/// several breakpoints (e.g. a diverging white -> red -> white palette).
+///
+/// # Fields
+/// * `stops` [`Vec<ColorStop>`] color stops ordered by breakpoint
+/// * `error` [`Option<PaletteError>`] why [`HotlinePalette::new`] fell back to the default stops;
+///   not compared by `==`, which only looks at the stops drawn
+///
+#[derive(Debug, Clone)]
 pub struct HotlinePalette {
-    pub palette: HashMap<String, f64>,
+    stops: Vec<ColorStop>,
+    error: Option<PaletteError>,
 }
 
-/// implement constructor for [`HotlinePalette`]
+/// palettes are equal when they draw the same color stops
+impl PartialEq for HotlinePalette {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.stops == other.stops
+    }
+}
+
+/// implement constructors and accessors for [`HotlinePalette`]
 impl HotlinePalette {
     ///
-    /// construct a new [`HotlinePalette`]
+    /// construct a new [`HotlinePalette`] from (color, breakpoint) pairs
+    ///
+    /// Invalid stops do not panic: the default palette is used instead and the
+    /// reason is kept in [`HotlinePalette::error`], which `HotPolyline` passes to
+    /// its `on_error` callback.  Use [`HotlinePalette::try_new`] to handle it upfront.
     ///
     /// # Returns
     /// [`HotlinePalette`]
     ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{HotlinePalette, PaletteError};
+    ///
+    /// let typo = HotlinePalette::new(&[("gren", 0.0), ("red", 1.0)]);
+    /// assert!(matches!(typo.error(), Some(PaletteError::InvalidColor(_))));
+    /// assert_eq!(typo.stops(), HotlinePalette::default().stops());
+    /// ```
+    ///
     #[must_use]
     #[inline]
     pub fn new(palette: &[(&str, f64)]) -> Self {
-        let mut palette_hashmap = HashMap::new();
+        Self::try_new(palette).unwrap_or_else(|err| Self {
+            error: Some(err),
+            ..Self::default()
+        })
+    }
+
+    ///
+    /// construct a new [`HotlinePalette`] from (color, breakpoint) pairs
+    ///
+    /// # Returns
+    /// [`HotlinePalette`] with stops sorted by breakpoint, or a [`PaletteError`]
+    /// if a color cannot be parsed, or a breakpoint is NaN, outside 0..=1,
+    /// or given more than once
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{HotlinePalette, PaletteError};
+    ///
+    /// let diverging = HotlinePalette::try_new(&[("white", 0.0), ("red", 0.5), ("white", 1.0)]);
+    /// assert!(diverging.is_ok());
+    ///
+    /// let invalid = HotlinePalette::try_new(&[("green", 0.0), ("red", 1.5)]);
+    /// assert!(matches!(invalid, Err(PaletteError::BreakpointOutOfRange { .. })));
+    ///
+    /// let typo = HotlinePalette::try_new(&[("gren", 0.0), ("red", 1.0)]);
+    /// assert!(matches!(typo, Err(PaletteError::InvalidColor(_))));
+    /// ```
+    ///
+    #[inline]
+    pub fn try_new(palette: &[(&str, f64)]) -> Result<Self, PaletteError> {
+        let stops = palette
+            .iter()
+            .map(|&(color, breakpoint)| Ok(ColorStop::new(Color::parse(color)?, breakpoint)))
+            .collect::<Result<Vec<_>, PaletteError>>()?;
+
+        Self::from_stops(stops)
+    }
+
+    ///
+    /// construct a new [`HotlinePalette`] from color stops in any order
+    ///
+    /// # Returns
+    /// [`HotlinePalette`] with stops sorted by breakpoint, or a [`PaletteError`]
+    ///
+    pub fn from_stops(stops: impl IntoIterator<Item = ColorStop>) -> Result<Self, PaletteError> {
+        let mut stops: Vec<ColorStop> = stops.into_iter().collect();
+
+        for stop in &stops {
+            if stop.breakpoint.is_nan() {
+                return Err(PaletteError::NanBreakpoint { color: stop.color });
+            }
+            if !(0.0..=1.0).contains(&stop.breakpoint) {
+                return Err(PaletteError::BreakpointOutOfRange {
+                    color: stop.color,
+                    breakpoint: stop.breakpoint,
+                });
+            }
+        }
 
-        for &(key, val) in palette {
-            palette_hashmap.insert(key.to_owned(), val);
+        stops.sort_by(|a, b| a.breakpoint.total_cmp(&b.breakpoint));
+        if let Some(pair) = stops
+            .windows(2)
+            .find(|pair| pair[0].breakpoint == pair[1].breakpoint)
+        {
+            return Err(PaletteError::DuplicateBreakpoint {
+                breakpoint: pair[0].breakpoint,
+            });
         }
 
-        Self {
-            palette: palette_hashmap,
+        Ok(Self { stops, error: None })
+    }
+
+    ///
+    /// error that made [`HotlinePalette::new`] fall back to the default palette
+    ///
+    /// # Returns
+    /// [`Option<&PaletteError>`], `None` for valid palettes
+    ///
+    #[must_use]
+    #[inline]
+    pub fn error(&self) -> Option<&PaletteError> {
+        self.error.as_ref()
+    }
+
+    ///
+    /// color stops ordered by breakpoint
+    ///
+    /// # Returns
+    /// [`&[ColorStop]`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn stops(&self) -> &[ColorStop] {
+        &self.stops
+    }
+
+    /// true if the palette has no color stops
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.stops.is_empty()
+    }
+
+    ///
+    /// color of the gradient at `t` in 0..=1, interpolated in sRGB like the
+    /// canvas gradient leaflet-hotline draws its palette with
+    ///
+    /// Values before the first or after the last breakpoint take the color of
+    /// that stop; an empty palette behaves like [`HotlinePalette::default`].
+    ///
+    /// # Returns
+    /// [`Color`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{Color, HotlinePalette};
+    ///
+    /// let palette = HotlinePalette::new(&[("#000000", 0.0), ("#ffffff", 1.0)]);
+    /// assert_eq!(palette.color_at(0.5), Color::rgb(128, 128, 128));
+    /// ```
+    ///
+    #[must_use]
+    #[inline]
+    pub fn color_at(&self, t: f64) -> Color {
+        self.color_at_in(t, Interpolation::Srgb)
+    }
+
+    ///
+    /// color of the gradient at `t` in 0..=1, interpolated in the given color space
+    ///
+    /// # Returns
+    /// [`Color`]
+    ///
+    #[must_use]
+    pub fn color_at_in(&self, t: f64, space: Interpolation) -> Color {
+        if self.stops.is_empty() {
+            return Self::default().color_at_in(t, space);
+        }
+
+        let t = if t.is_nan() { 0.0 } else { t };
+        let first = &self.stops[0];
+        let last = &self.stops[self.stops.len() - 1];
+        if t <= first.breakpoint {
+            return first.color;
+        }
+        if t >= last.breakpoint {
+            return last.color;
         }
+
+        let upper = self
+            .stops
+            .iter()
+            .position(|stop| stop.breakpoint >= t)
+            .unwrap_or(self.stops.len() - 1);
+        let (from, to) = (&self.stops[upper - 1], &self.stops[upper]);
+        let local = (t - from.breakpoint) / (to.breakpoint - from.breakpoint);
+        from.color.mix_in(&to.color, local, space)
+    }
+
+    ///
+    /// color leaflet-hotline uses for a value, given the hotline `min` and `max`
+    ///
+    /// Mirrors `getRGBForValue`: the value is made relative to `min..max`, clamped
+    /// to `0..=0.999`, and looked up in a 256 pixel palette gradient.  The color of a
+    /// drawn segment runs between the colors of its two end points.
+    ///
+    /// # Returns
+    /// opaque [`Color`]
+    ///
+    #[must_use]
+    pub fn color_for_value(&self, value: f64, min: f64, max: f64) -> Color {
+        let relative = (value - min) / (max - min);
+        let relative = if relative.is_nan() {
+            0.0
+        } else {
+            relative.clamp(0.0, 0.999)
+        };
+        let pixel = (relative * 256.0).floor();
+        let color = self.color_at((pixel + 0.5) / 256.0);
+        Color::rgb(color.r, color.g, color.b)
     }
 }
 
@@ -58,6 +350,88 @@ impl Default for HotlinePalette {
     ///
     #[inline]
     fn default() -> Self {
-        Self::new(DEFAULT_PALETTE_VALUES)
+        Self::try_new(DEFAULT_PALETTE_VALUES).unwrap_or(Self {
+            stops: Vec::new(),
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stops_sorted() {
+        let palette = HotlinePalette::new(&[("red", 1.0), ("green", 0.0), ("blue", 0.5)]);
+        let breakpoints: Vec<f64> = palette.stops().iter().map(|s| s.breakpoint).collect();
+        assert_eq!(breakpoints, vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_color_at() {
+        let palette = HotlinePalette::new(&[("red", 0.25), ("blue", 0.75)]);
+        assert_eq!(palette.color_at(0.0), Color::rgb(255, 0, 0));
+        assert_eq!(palette.color_at(0.5), Color::rgb(128, 0, 128));
+        assert_eq!(palette.color_at(1.0), Color::rgb(0, 0, 255));
+        assert_eq!(
+            palette.color_at_in(0.5, Interpolation::Hsl),
+            Color::rgb(255, 0, 255)
+        );
+    }
+
+    #[test]
+    fn test_color_for_value() {
+        let palette = HotlinePalette::new(&[("#000000", 0.0), ("#ffffff", 1.0)]);
+        assert_eq!(
+            palette.color_for_value(-5.0, 0.0, 10.0),
+            Color::rgb(0, 0, 0)
+        );
+        assert_eq!(
+            palette.color_for_value(10.0, 0.0, 10.0),
+            Color::rgb(255, 255, 255)
+        );
+        assert_eq!(palette.color_for_value(1.0, 1.0, 1.0), Color::rgb(0, 0, 0));
+    }
+
+    #[test]
+    fn test_repeated_color() {
+        let palette = HotlinePalette::new(&[("white", 0.0), ("red", 0.5), ("white", 1.0)]);
+        assert_eq!(palette.stops().len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_breakpoints() {
+        assert_eq!(
+            HotlinePalette::try_new(&[("red", f64::NAN)]),
+            Err(PaletteError::NanBreakpoint {
+                color: Color::rgb(255, 0, 0)
+            })
+        );
+        assert_eq!(
+            HotlinePalette::try_new(&[("red", -0.1)]),
+            Err(PaletteError::BreakpointOutOfRange {
+                color: Color::rgb(255, 0, 0),
+                breakpoint: -0.1
+            })
+        );
+        assert_eq!(
+            HotlinePalette::try_new(&[("red", 0.5), ("blue", 0.5)]),
+            Err(PaletteError::DuplicateBreakpoint { breakpoint: 0.5 })
+        );
+    }
+
+    #[test]
+    fn test_new_falls_back_to_default() {
+        let default = HotlinePalette::default();
+        assert_eq!(default.stops().len(), DEFAULT_PALETTE_VALUES.len());
+        assert_eq!(default.error(), None);
+
+        let invalid = HotlinePalette::new(&[("red", 1.5)]);
+        assert_eq!(invalid, default);
+        assert!(matches!(
+            invalid.error(),
+            Some(PaletteError::BreakpointOutOfRange { .. })
+        ));
     }
 }
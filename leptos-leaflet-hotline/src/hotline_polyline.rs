@@ -0,0 +1,359 @@
+//! module for the Google encoded polyline algorithm
+use std::fmt;
+
+use super::hotline_normalization::Normalization;
+use super::hotline_position::{FlatPosition, HotlinePositionVec};
+
+///
+/// number of decimal places kept by an encoded polyline
+///
+/// Google Maps and OSRM use 5 by default; Valhalla and OSRM's `polyline6` use 6.
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PolylinePrecision {
+    /// 5 decimal places, about 1 m
+    #[default]
+    Five,
+    /// 6 decimal places, about 0.1 m
+    Six,
+}
+
+/// implement the coordinate scale for [`PolylinePrecision`]
+impl PolylinePrecision {
+    ///
+    /// factor coordinates are multiplied by before rounding
+    ///
+    /// # Returns
+    /// [`f64`], `1e5` or `1e6`
+    ///
+    #[must_use]
+    #[inline]
+    pub fn factor(self) -> f64 {
+        match self {
+            Self::Five => 1e5,
+            Self::Six => 1e6,
+        }
+    }
+}
+
+///
+/// error returned when an encoded polyline cannot be decoded
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PolylineError {
+    /// a character is outside the `?`..=`~` range used by the encoding
+    InvalidCharacter {
+        /// byte index of the character
+        index: usize,
+    },
+    /// the string ends in the middle of a coordinate
+    Truncated,
+    /// the deltas add up to a coordinate outside the `i64` range
+    Overflow {
+        /// index of the decoded position
+        position: usize,
+    },
+    /// the values slice has a different length than the decoded positions
+    ValueCountMismatch {
+        /// number of decoded positions
+        positions: usize,
+        /// number of values given
+        values: usize,
+    },
+}
+
+impl fmt::Display for PolylineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter { index } => {
+                write!(f, "invalid encoded polyline character at byte {index}")
+            }
+            Self::Truncated => write!(f, "encoded polyline ends mid-coordinate"),
+            Self::Overflow { position } => {
+                write!(f, "encoded polyline position {position} overflows")
+            }
+            Self::ValueCountMismatch { positions, values } => {
+                write!(
+                    f,
+                    "encoded polyline has {positions} positions but {values} values were given"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PolylineError {}
+
+/// implement encoded polyline conversion for [`HotlinePositionVec`]
+impl HotlinePositionVec {
+    ///
+    /// decode an encoded polyline, e.g. from a routing engine, pairing each position
+    /// with the value at the same index
+    ///
+    /// Values are normalized as in [`HotlinePositionVec::new`]; see
+    /// [`HotlinePositionVec::from_encoded_polyline_with_normalization`] to pick another
+    /// [`Normalization`].
+    ///
+    /// # Returns
+    /// [`HotlinePositionVec`], or [`PolylineError`] if the string is malformed or the
+    /// number of values does not match the number of positions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{HotlinePositionVec, PolylinePrecision};
+    ///
+    /// let route = HotlinePositionVec::from_encoded_polyline(
+    ///     "_p~iF~ps|U_ulLnnqC_mqNvxq`@",
+    ///     &[10.0, 20.0, 15.0],
+    ///     PolylinePrecision::Five,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(route.positions[1].latlng.lat, 40.7);
+    /// assert_eq!(route.raw_values, vec![10.0, 20.0, 15.0]);
+    /// ```
+    ///
+    #[inline]
+    pub fn from_encoded_polyline(
+        encoded: &str,
+        values: &[f64],
+        precision: PolylinePrecision,
+    ) -> Result<Self, PolylineError> {
+        Self::from_encoded_polyline_with_normalization(
+            encoded,
+            values,
+            precision,
+            Normalization::default(),
+        )
+    }
+
+    ///
+    /// decode an encoded polyline, pairing each position with the value at the same
+    /// index and normalizing the values with the given strategy
+    ///
+    /// # Returns
+    /// [`HotlinePositionVec`], or [`PolylineError`] as for
+    /// [`HotlinePositionVec::from_encoded_polyline`]
+    ///
+    pub fn from_encoded_polyline_with_normalization(
+        encoded: &str,
+        values: &[f64],
+        precision: PolylinePrecision,
+        normalization: Normalization,
+    ) -> Result<Self, PolylineError> {
+        let positions = decode_polyline(encoded, precision)?;
+        if positions.len() != values.len() {
+            return Err(PolylineError::ValueCountMismatch {
+                positions: positions.len(),
+                values: values.len(),
+            });
+        }
+        let tuples: Vec<(f64, f64, f64)> = positions
+            .iter()
+            .zip(values)
+            .map(|(position, value)| (position.lat, position.lng, *value))
+            .collect();
+        Ok(Self::with_normalization(&tuples, normalization))
+    }
+
+    ///
+    /// encode the positions as a polyline string; the values are not part of the
+    /// encoding and stay available in `raw_values`
+    ///
+    /// # Returns
+    /// [`String`]
+    ///
+    #[must_use]
+    pub fn to_encoded_polyline(&self, precision: PolylinePrecision) -> String {
+        let positions: Vec<FlatPosition> = self.positions.iter().map(|pos| pos.latlng).collect();
+        encode_polyline(&positions, precision)
+    }
+}
+
+///
+/// decode an encoded polyline into positions
+///
+/// # Returns
+/// [`Vec<FlatPosition>`], or [`PolylineError`] if the string is malformed
+///
+/// # Examples
+///
+/// ```
+/// use leptos_leaflet_hotline::{decode_polyline, FlatPosition, PolylinePrecision};
+///
+/// let positions = decode_polyline("_p~iF~ps|U", PolylinePrecision::Five).unwrap();
+/// assert_eq!(positions, vec![FlatPosition::new(38.5, -120.2)]);
+/// ```
+///
+pub fn decode_polyline(
+    encoded: &str,
+    precision: PolylinePrecision,
+) -> Result<Vec<FlatPosition>, PolylineError> {
+    let factor = precision.factor();
+    let mut bytes = encoded.bytes().enumerate();
+    let mut positions = Vec::new();
+    let (mut lat, mut lng) = (0_i64, 0_i64);
+
+    while let Some(delta_lat) = decode_number(&mut bytes)? {
+        let delta_lng = decode_number(&mut bytes)?.ok_or(PolylineError::Truncated)?;
+        let overflow = || PolylineError::Overflow {
+            position: positions.len(),
+        };
+        lat = lat.checked_add(delta_lat).ok_or_else(overflow)?;
+        lng = lng.checked_add(delta_lng).ok_or_else(overflow)?;
+        positions.push(FlatPosition::new(lat as f64 / factor, lng as f64 / factor));
+    }
+    Ok(positions)
+}
+
+///
+/// encode positions as a polyline string
+///
+/// # Returns
+/// [`String`]
+///
+#[must_use]
+pub fn encode_polyline(positions: &[FlatPosition], precision: PolylinePrecision) -> String {
+    let factor = precision.factor();
+    let mut encoded = String::new();
+    let (mut lat, mut lng) = (0_i64, 0_i64);
+
+    for position in positions {
+        let (next_lat, next_lng) = (
+            (position.lat * factor).round() as i64,
+            (position.lng * factor).round() as i64,
+        );
+        encode_number(next_lat - lat, &mut encoded);
+        encode_number(next_lng - lng, &mut encoded);
+        (lat, lng) = (next_lat, next_lng);
+    }
+    encoded
+}
+
+/// read one zigzag-encoded number, or `None` at the end of the string
+fn decode_number(
+    bytes: &mut impl Iterator<Item = (usize, u8)>,
+) -> Result<Option<i64>, PolylineError> {
+    let mut result = 0_u64;
+    let mut shift = 0;
+    loop {
+        let Some((index, byte)) = bytes.next() else {
+            return if shift == 0 {
+                Ok(None)
+            } else {
+                Err(PolylineError::Truncated)
+            };
+        };
+        // 13 chunks of 5 bits cover any i64; more means the string is corrupt
+        if !(63..=126).contains(&byte) || shift > 60 {
+            return Err(PolylineError::InvalidCharacter { index });
+        }
+        let chunk = u64::from(byte - 63);
+        result |= (chunk & 0x1f) << shift;
+        shift += 5;
+        if chunk < 0x20 {
+            let value = (result >> 1) as i64;
+            return Ok(Some(if result & 1 == 1 { !value } else { value }));
+        }
+    }
+}
+
+/// append one number in zigzag encoding, five bits per character
+fn encode_number(value: i64, encoded: &mut String) {
+    let mut value = if value < 0 {
+        !((value as u64) << 1)
+    } else {
+        (value as u64) << 1
+    };
+    while value >= 0x20 {
+        encoded.push(char::from((0x20 | (value & 0x1f)) as u8 + 63));
+        value >>= 5;
+    }
+    encoded.push(char::from(value as u8 + 63));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// example from Google's polyline algorithm documentation
+    const GOOGLE: &str = "_p~iF~ps|U_ulLnnqC_mqNvxq`@";
+
+    #[test]
+    fn test_decode_google_example() {
+        assert_eq!(
+            decode_polyline(GOOGLE, PolylinePrecision::Five).unwrap(),
+            vec![
+                FlatPosition::new(38.5, -120.2),
+                FlatPosition::new(40.7, -120.95),
+                FlatPosition::new(43.252, -126.453),
+            ]
+        );
+        assert_eq!(decode_polyline("", PolylinePrecision::Five), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_round_trip_precision_six() {
+        let positions = vec![
+            FlatPosition::new(40.292_812, -105.618_034),
+            FlatPosition::new(40.292_901, -105.619_117),
+            FlatPosition::new(-33.868_820, 151.209_296),
+        ];
+        let encoded = encode_polyline(&positions, PolylinePrecision::Six);
+        assert_eq!(
+            decode_polyline(&encoded, PolylinePrecision::Six),
+            Ok(positions)
+        );
+
+        let five = HotlinePositionVec::from_encoded_polyline(
+            GOOGLE,
+            &[1.0, 2.0, 3.0],
+            PolylinePrecision::Five,
+        )
+        .unwrap();
+        assert_eq!(five.to_encoded_polyline(PolylinePrecision::Five), GOOGLE);
+
+        let min_max = HotlinePositionVec::from_encoded_polyline_with_normalization(
+            GOOGLE,
+            &[1.0, 2.0, 3.0],
+            PolylinePrecision::Five,
+            Normalization::MinMax,
+        )
+        .unwrap();
+        let alts: Vec<f64> = min_max.positions.iter().map(|pos| pos.alt).collect();
+        assert_eq!(alts, vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert_eq!(
+            decode_polyline("_p~iF", PolylinePrecision::Five),
+            Err(PolylineError::Truncated)
+        );
+        assert_eq!(
+            decode_polyline("_p~i", PolylinePrecision::Five),
+            Err(PolylineError::Truncated)
+        );
+        assert_eq!(
+            decode_polyline("_p~iF ps|U", PolylinePrecision::Five),
+            Err(PolylineError::InvalidCharacter { index: 5 })
+        );
+
+        let mut huge = String::new();
+        encode_number(i64::MAX, &mut huge);
+        encode_number(0, &mut huge);
+        let overflowing = huge.repeat(2);
+        assert_eq!(
+            decode_polyline(&overflowing, PolylinePrecision::Five),
+            Err(PolylineError::Overflow { position: 1 })
+        );
+        assert_eq!(
+            HotlinePositionVec::from_encoded_polyline(GOOGLE, &[1.0], PolylinePrecision::Five),
+            Err(PolylineError::ValueCountMismatch {
+                positions: 3,
+                values: 1
+            })
+        );
+    }
+}
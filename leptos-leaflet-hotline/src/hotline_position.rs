@@ -1,6 +1,5 @@
 //! module for hotline position data structures and functions
-use js_sys::Array;
-use wasm_bindgen::prelude::*;
+use super::hotline_normalization::{Normalization, NormalizationError};
 
 /// Struct for conventional lat, lng position
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
@@ -16,7 +15,8 @@ pub struct FlatPosition {
 /// in addition to lat and lng, there is a 3rd dimension \
 /// for the value to be visualized.  \
 /// This is consistent with JS leaflet-hotline which puts
-/// the value in the Leaflet altitude placeholder.
+/// the value in the Leaflet altitude placeholder.  \
+/// See [`crate::HotlineTrack`] for keeping values separate from positions.
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 #[non_exhaustive]
 pub struct HotlinePosition {
@@ -27,32 +27,136 @@ pub struct HotlinePosition {
 }
 
 /// Struct for vector of leaflet hotline positions
+///
+/// The `alt` of each position holds the normalized value drawn by the hotline,
+/// while `raw_values` keeps the values as given so tooltips and legends can
+/// show them in their real units.
 #[derive(Debug, Default, Clone, PartialEq)]
 #[non_exhaustive]
 pub struct HotlinePositionVec {
     /// vec of positions
     pub positions: Vec<HotlinePosition>,
+    /// values before normalization, same length as `positions`
+    pub raw_values: Vec<f64>,
+    /// strategy used to normalize `raw_values` into each position's `alt`
+    pub normalization: Normalization,
+}
+
+impl FlatPosition {
+    #[must_use]
+    #[inline]
+    pub const fn new(lat: f64, lng: f64) -> Self {
+        Self { lat, lng }
+    }
 }
 
 impl HotlinePositionVec {
+    ///
+    /// construct a new [`HotlinePositionVec`] from (lat, lng, value) tuples,
+    /// dividing each value by the largest absolute value
+    ///
+    /// # Returns
+    /// [`HotlinePositionVec`]
+    ///
     #[must_use]
     #[inline]
     pub fn new(arr: &[(f64, f64, f64)]) -> Self {
-        let mut position_vec: Vec<HotlinePosition> = vec![];
+        Self::with_normalization(arr, Normalization::default())
+    }
 
-        let max_val = arr
+    ///
+    /// construct a new [`HotlinePositionVec`] from (lat, lng, value) tuples,
+    /// normalizing values with the given strategy
+    ///
+    /// A strategy that fails [`Normalization::validate`], such as a NaN
+    /// [`Normalization::FixedDomain`], is replaced by [`Normalization::None`]; use
+    /// [`HotlinePositionVec::try_with_normalization`] to get the error instead.
+    ///
+    /// # Returns
+    /// [`HotlinePositionVec`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{HotlinePositionVec, Normalization};
+    ///
+    /// let positions = &[(40.293, -105.618, -5.0), (40.2928, -105.6190, 15.0)];
+    /// let position_vec = HotlinePositionVec::with_normalization(positions, Normalization::MinMax);
+    /// assert_eq!(position_vec.positions[0].alt, 0.0);
+    /// assert_eq!(position_vec.raw_values, vec![-5.0, 15.0]);
+    /// ```
+    ///
+    #[must_use]
+    #[inline]
+    pub fn with_normalization(arr: &[(f64, f64, f64)], normalization: Normalization) -> Self {
+        let normalization = match normalization.validate() {
+            Ok(()) => normalization,
+            Err(_) => Normalization::None,
+        };
+        let raw_values: Vec<f64> = arr.iter().map(|val| val.2).collect();
+        let normalized = normalization.apply(&raw_values);
+
+        let positions = arr
             .iter()
-            .map(|val| val.2)
-            .fold(f64::NEG_INFINITY, f64::max);
-
-        for &elem in arr {
-            position_vec.push(HotlinePosition::new(elem.0, elem.1, elem.2 / max_val));
-        }
+            .zip(normalized)
+            .map(|(elem, val)| HotlinePosition::new(elem.0, elem.1, val))
+            .collect();
 
         Self {
-            positions: position_vec,
+            positions,
+            raw_values,
+            normalization,
         }
     }
+
+    ///
+    /// construct a new [`HotlinePositionVec`] from (lat, lng, value) tuples,
+    /// normalizing values with the given strategy if it is valid
+    ///
+    /// # Returns
+    /// [`HotlinePositionVec`], or [`NormalizationError`] if the strategy fails
+    /// [`Normalization::validate`]
+    ///
+    #[inline]
+    pub fn try_with_normalization(
+        arr: &[(f64, f64, f64)],
+        normalization: Normalization,
+    ) -> Result<Self, NormalizationError> {
+        normalization.validate()?;
+        Ok(Self::with_normalization(arr, normalization))
+    }
+
+    /// (lat, lng, raw value) of each position, falling back to the normalized value
+    /// where `raw_values` is shorter than `positions`
+    pub(crate) fn raw_points(&self) -> Vec<(f64, f64, f64)> {
+        self.positions
+            .iter()
+            .enumerate()
+            .map(|(index, pos)| {
+                let value = self.raw_values.get(index).copied().unwrap_or(pos.alt);
+                (pos.latlng.lat, pos.latlng.lng, value)
+            })
+            .collect()
+    }
+
+    ///
+    /// smallest and largest finite raw value
+    ///
+    /// # Returns
+    /// [`Option<(f64, f64)>`], `None` if there are no finite values
+    ///
+    #[must_use]
+    #[inline]
+    pub fn raw_domain(&self) -> Option<(f64, f64)> {
+        self.raw_values
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .fold(None, |domain, v| match domain {
+                None => Some((v, v)),
+                Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+            })
+    }
 }
 
 impl HotlinePosition {
@@ -77,108 +181,3 @@ impl HotlinePosition {
         self.latlng.lng
     }
 }
-
-/// to override the latlng bindings with the 3-d data structure the hotline JS code uses
-/// eventually, re-write so the values are in a separate array,
-/// instead of being passed in the z dimension like the current leaflet-hotline
-/// then, drop this once there is no need to have a separate latlng binding
-#[wasm_bindgen(js_namespace=L)]
-extern "C" {
-    /// LatLng type for hotlines (lat, lng, value)
-    #[derive(Debug, Default, Clone)]
-    pub type LatLng;
-
-    /// construct a new hotline [`LatLng`]
-    ///
-    /// # Returns
-    /// [`LatLng`]
-    ///
-    #[wasm_bindgen(constructor, js_name = LatLng)]
-    pub fn new(lat: f64, lng: f64, alt: f64) -> LatLng;
-
-    ///
-    /// get hotline position latitude
-    ///
-    /// # Returns
-    /// [`f64`]
-    ///
-    #[wasm_bindgen(method, getter)]
-    pub fn lat(this: &LatLng) -> f64;
-
-    ///
-    /// get hotline position longitude
-    ///
-    /// # Returns
-    /// [`f64`]
-    ///
-    #[wasm_bindgen(method, getter)]
-    pub fn lng(this: &LatLng) -> f64;
-
-    ///
-    /// get hotline position value
-    ///
-    /// # Returns
-    /// [`f64`]
-    ///
-    #[wasm_bindgen(method, getter)]
-    pub fn alt(this: &LatLng) -> f64;
-
-    ///
-    /// set hotline position latitude
-    ///
-    /// # Returns
-    /// [`f64`]
-    ///
-    #[wasm_bindgen(method, setter)]
-    pub fn set_lat(this: &LatLng, value: f64) -> f64;
-
-    ///
-    /// set hotline position longitude
-    ///
-    /// # Returns
-    /// [`f64`]
-    ///
-    #[wasm_bindgen(method, setter)]
-    pub fn set_lng(this: &LatLng, value: f64) -> f64;
-
-    ///
-    /// set hotline position value
-    ///
-    /// # Returns
-    /// [`f64`]
-    ///
-    #[wasm_bindgen(method, setter)]
-    pub fn set_alt(this: &LatLng, value: f64) -> f64;
-}
-
-/// Creates a JS Array of objects of type ```{lat: number, lng: number, alt: number}``` \
-/// for passing to the JS leaflet-hotline code through wasm bindings.  This
-/// must be used to convert a slice of [`HotlinePosition`] values to JS Array,
-/// prior to calling the hotline constructor bound to JS by wasm-bindgen
-///
-/// # Args
-/// `vals`: slice of hotline positions with values.
-///
-/// # Returns
-/// JS Array of objects containing hotline positions and values.
-///
-/// # Examples
-///
-/// Basic usage:
-/// ```no_run
-/// let positions = &[(40.293, -105.618, 25.0), (40.2928, -105.6190, 0.0)];
-/// let position_vec = leptos_leaflet_hotline::HotlinePositionVec::new(positions);
-/// let arr = leptos_leaflet_hotline::to_hotline_lat_lng_array(&position_vec);
-/// assert!(arr.is_array())
-/// ```
-///
-#[must_use]
-#[inline]
-pub fn to_hotline_lat_lng_array(vals: &HotlinePositionVec) -> Array {
-    let array = Array::new();
-    for val in vals.positions.iter().copied() {
-        let new_latlng = LatLng::new(val.get_lat(), val.get_lng(), val.alt);
-        array.push(&new_latlng);
-    }
-    array
-}
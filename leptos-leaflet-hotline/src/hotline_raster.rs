@@ -0,0 +1,561 @@
+//! module for drawing hotlines into RGBA pixel buffers without a browser
+use std::f64::consts::PI;
+
+use super::hotline_color::Color;
+use super::hotline_position::{FlatPosition, HotlinePositionVec};
+use super::hotline_style::HotlineStyle;
+use super::hotline_track::HotlineTrack;
+
+/// largest latitude Web Mercator can show, as in leaflet's `SphericalMercator`
+const MAX_LATITUDE: f64 = 85.051_128_779_806_6;
+
+/// smallest extent, in Web Mercator units, [`MercatorBounds::fit`] zooms in to
+const MIN_SPAN: f64 = 1e-9;
+
+///
+/// geographic area shown by a raster, stretched onto it in Web Mercator
+///
+/// # Fields
+/// * `south_west` [`FlatPosition`] bottom left corner
+/// * `north_east` [`FlatPosition`] top right corner
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct MercatorBounds {
+    /// bottom left corner
+    pub south_west: FlatPosition,
+    /// top right corner
+    pub north_east: FlatPosition,
+}
+
+/// implement constructors and projection for [`MercatorBounds`]
+impl MercatorBounds {
+    ///
+    /// construct new [`MercatorBounds`] from its south west and north east corners
+    ///
+    /// # Returns
+    /// [`MercatorBounds`]
+    ///
+    #[must_use]
+    #[inline]
+    pub const fn new(south_west: FlatPosition, north_east: FlatPosition) -> Self {
+        Self {
+            south_west,
+            north_east,
+        }
+    }
+
+    ///
+    /// smallest bounds showing all positions on a `width` x `height` raster, keeping
+    /// at least `padding` pixels free on each side and the aspect ratio undistorted
+    ///
+    /// # Returns
+    /// [`Option<MercatorBounds>`], `None` if there are no positions or they leave no
+    /// room inside the padding
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{FlatPosition, MercatorBounds};
+    ///
+    /// let path = [FlatPosition::new(40.0, -105.0), FlatPosition::new(40.01, -105.02)];
+    /// let bounds = MercatorBounds::fit(&path, 200, 100, 10.0).unwrap();
+    /// // the track is limited by the height, so its southern end touches the padding
+    /// let (x, y) = bounds.to_pixel(&path[0], 200, 100);
+    /// assert!((y - 90.0).abs() < 1e-6 && x > 100.0);
+    /// ```
+    ///
+    #[must_use]
+    pub fn fit(positions: &[FlatPosition], width: u32, height: u32, padding: f64) -> Option<Self> {
+        let inner_width = f64::from(width) - 2.0 * padding;
+        let inner_height = f64::from(height) - 2.0 * padding;
+        if positions.is_empty() || inner_width <= 0.0 || inner_height <= 0.0 {
+            return None;
+        }
+
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for (x, y) in positions.iter().map(project) {
+            (min_x, max_x) = (min_x.min(x), max_x.max(x));
+            (min_y, max_y) = (min_y.min(y), max_y.max(y));
+        }
+
+        let scale = (inner_width / (max_x - min_x).max(MIN_SPAN))
+            .min(inner_height / (max_y - min_y).max(MIN_SPAN));
+        let (center_x, center_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+        let (half_width, half_height) = (
+            f64::from(width) / scale / 2.0,
+            f64::from(height) / scale / 2.0,
+        );
+        Some(Self::new(
+            unproject(center_x - half_width, center_y + half_height),
+            unproject(center_x + half_width, center_y - half_height),
+        ))
+    }
+
+    ///
+    /// pixel coordinates of a position on a `width` x `height` raster of these bounds,
+    /// with the origin at the top left corner
+    ///
+    /// # Returns
+    /// `(x, y)` in pixels, outside `0..width` / `0..height` for positions out of bounds
+    ///
+    #[must_use]
+    pub fn to_pixel(&self, position: &FlatPosition, width: u32, height: u32) -> (f64, f64) {
+        let (west, north) = project(&FlatPosition::new(self.north_east.lat, self.south_west.lng));
+        let (east, south) = project(&FlatPosition::new(self.south_west.lat, self.north_east.lng));
+        let (x, y) = project(position);
+        (
+            (x - west) / (east - west) * f64::from(width),
+            (y - north) / (south - north) * f64::from(height),
+        )
+    }
+}
+
+///
+/// RGBA image a hotline is drawn into, 8 bits per channel, not premultiplied and
+/// stored row by row from the top left corner
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HotlineRaster {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// implement construction, pixel access and drawing for [`HotlineRaster`]
+impl HotlineRaster {
+    ///
+    /// construct a new transparent [`HotlineRaster`]
+    ///
+    /// # Returns
+    /// [`HotlineRaster`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::filled(width, height, Color::rgba(0, 0, 0, 0))
+    }
+
+    ///
+    /// construct a new [`HotlineRaster`] filled with a background color
+    ///
+    /// # Returns
+    /// [`HotlineRaster`]
+    ///
+    #[must_use]
+    pub fn filled(width: u32, height: u32, background: Color) -> Self {
+        let pixels = [background.r, background.g, background.b, background.a]
+            .repeat(width as usize * height as usize);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    ///
+    /// wrap RGBA bytes, four per pixel row by row, such as the `ImageData` of a canvas
+    ///
+    /// # Returns
+    /// [`Option<HotlineRaster>`], `None` if `pixels` does not hold `width` x `height` pixels
+    ///
+    #[must_use]
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        (pixels.len() == width as usize * height as usize * 4).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// width in pixels
+    #[must_use]
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// height in pixels
+    #[must_use]
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// RGBA bytes, four per pixel, row by row
+    #[must_use]
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// consume the raster, returning its RGBA bytes
+    #[must_use]
+    #[inline]
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    ///
+    /// color of the pixel in column `x` and row `y`
+    ///
+    /// # Returns
+    /// [`Option<Color>`], `None` outside the raster
+    ///
+    #[must_use]
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = self.index(x, y) * 4;
+        let [r, g, b, a] = self.pixels[index..index + 4] else {
+            return None;
+        };
+        Some(Color::rgba(r, g, b, a))
+    }
+
+    /// replace the pixel in column `x` and row `y`, ignoring pixels outside the raster
+    pub(crate) fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if x < self.width && y < self.height {
+            let index = self.index(x, y) * 4;
+            self.pixels[index..index + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    ///
+    /// draw a path of positions and values, as the hotline canvas renderer does: the
+    /// outline of the whole path, then each segment with a gradient between the
+    /// colors of its two end points
+    ///
+    /// Lines have round caps and joins and anti-aliased edges.  Positions are placed
+    /// with [`MercatorBounds::to_pixel`]; segments with a non-finite end are skipped.
+    ///
+    pub fn draw(
+        &mut self,
+        path: impl IntoIterator<Item = (FlatPosition, f64)>,
+        bounds: &MercatorBounds,
+        style: &HotlineStyle,
+    ) {
+        let points: Vec<(f64, f64, f64)> = path
+            .into_iter()
+            .map(|(position, value)| {
+                let (x, y) = bounds.to_pixel(&position, self.width, self.height);
+                (x, y, value)
+            })
+            .collect();
+        let segments = || {
+            points
+                .windows(2)
+                .map(|segment| (segment[0], segment[1]))
+                .filter(|((x0, y0, _), (x1, y1, _))| {
+                    [x0, y0, x1, y1].iter().all(|coord| coord.is_finite())
+                })
+        };
+
+        if style.outline_width > 0.0 {
+            // one coverage mask for the whole outline, so overlapping segments do not
+            // blend a translucent outline twice
+            let mut coverage = vec![0.0_f64; self.width as usize * self.height as usize];
+            for (from, to) in segments() {
+                self.stroke_segment(from, to, style.outer_width() / 2.0, |index, cover, _| {
+                    coverage[index] = coverage[index].max(cover);
+                });
+            }
+            for (index, cover) in coverage.into_iter().enumerate() {
+                if cover > 0.0 {
+                    blend(
+                        &mut self.pixels[index * 4..index * 4 + 4],
+                        style.outline_color,
+                        cover,
+                    );
+                }
+            }
+        }
+
+        for (from, to) in segments() {
+            let (start, end) = (style.color_for_value(from.2), style.color_for_value(to.2));
+            let mut covered = Vec::new();
+            self.stroke_segment(from, to, style.weight / 2.0, |index, cover, t| {
+                covered.push((index, cover, start.mix(&end, t)));
+            });
+            for (index, cover, color) in covered {
+                blend(&mut self.pixels[index * 4..index * 4 + 4], color, cover);
+            }
+        }
+    }
+
+    /// call `cover` with the pixel index, coverage and gradient position of every pixel
+    /// within `radius` of the segment
+    fn stroke_segment(
+        &self,
+        (x0, y0, _): (f64, f64, f64),
+        (x1, y1, _): (f64, f64, f64),
+        radius: f64,
+        mut cover: impl FnMut(usize, f64, f64),
+    ) {
+        if radius <= 0.0 || self.width == 0 || self.height == 0 {
+            return;
+        }
+        let reach = radius + 1.0;
+        let column = |x: f64| x.clamp(0.0, f64::from(self.width - 1)) as u32;
+        let row = |y: f64| y.clamp(0.0, f64::from(self.height - 1)) as u32;
+        let (left, right) = (column(x0.min(x1) - reach), column(x0.max(x1) + reach));
+        let (top, bottom) = (row(y0.min(y1) - reach), row(y0.max(y1) + reach));
+
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let length_squared = dx * dx + dy * dy;
+        for y in top..=bottom {
+            for x in left..=right {
+                let (px, py) = (f64::from(x) + 0.5 - x0, f64::from(y) + 0.5 - y0);
+                let t = if length_squared > 0.0 {
+                    ((px * dx + py * dy) / length_squared).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let distance = (px - t * dx).hypot(py - t * dy);
+                let coverage = (radius + 0.5 - distance).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    cover(self.index(x, y), coverage, t);
+                }
+            }
+        }
+    }
+
+    /// number of a pixel, counted row by row
+    #[inline]
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+}
+
+/// implement rasterizing for [`HotlinePositionVec`]
+impl HotlinePositionVec {
+    ///
+    /// draw the positions into a new transparent `width` x `height` raster showing
+    /// `bounds`, coloring the normalized values as the hotline layer does
+    ///
+    /// # Returns
+    /// [`HotlineRaster`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{HotlinePositionVec, HotlineStyle, MercatorBounds};
+    ///
+    /// let positions = HotlinePositionVec::new(&[(40.0, -105.0, 0.0), (40.0, -104.99, 1.0)]);
+    /// let flat: Vec<_> = positions.positions.iter().map(|pos| pos.latlng).collect();
+    /// let bounds = MercatorBounds::fit(&flat, 64, 32, 4.0).unwrap();
+    /// let raster = positions.rasterize(&bounds, 64, 32, &HotlineStyle::default());
+    /// assert_eq!(raster.pixels().len(), 64 * 32 * 4);
+    /// assert_eq!(raster.pixel(0, 0).unwrap().a, 0);
+    /// ```
+    ///
+    #[must_use]
+    pub fn rasterize(
+        &self,
+        bounds: &MercatorBounds,
+        width: u32,
+        height: u32,
+        style: &HotlineStyle,
+    ) -> HotlineRaster {
+        let mut raster = HotlineRaster::new(width, height);
+        raster.draw(
+            self.positions.iter().map(|pos| (pos.latlng, pos.alt)),
+            bounds,
+            style,
+        );
+        raster
+    }
+}
+
+/// implement rasterizing for [`HotlineTrack`]
+impl HotlineTrack {
+    ///
+    /// draw the track into a new transparent `width` x `height` raster showing `bounds`
+    ///
+    /// # Returns
+    /// [`HotlineRaster`]
+    ///
+    #[must_use]
+    pub fn rasterize(
+        &self,
+        bounds: &MercatorBounds,
+        width: u32,
+        height: u32,
+        style: &HotlineStyle,
+    ) -> HotlineRaster {
+        let mut raster = HotlineRaster::new(width, height);
+        raster.draw(self.iter(), bounds, style);
+        raster
+    }
+}
+
+/// Web Mercator coordinates of a position, both in `0..=1` with y growing southwards
+fn project(position: &FlatPosition) -> (f64, f64) {
+    let lat = position.lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    (
+        (position.lng + 180.0) / 360.0,
+        (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0,
+    )
+}
+
+/// position at Web Mercator coordinates, the inverse of [`project`]
+fn unproject(x: f64, y: f64) -> FlatPosition {
+    let lat = (PI * (1.0 - 2.0 * y)).sinh().atan().to_degrees();
+    FlatPosition::new(lat, x * 360.0 - 180.0)
+}
+
+/// composite `color` at `coverage` over an RGBA pixel (source-over)
+fn blend(pixel: &mut [u8], color: Color, coverage: f64) {
+    let source_alpha = f64::from(color.a) / 255.0 * coverage;
+    let dest_alpha = f64::from(pixel[3]) / 255.0;
+    let alpha = source_alpha + dest_alpha * (1.0 - source_alpha);
+    if alpha <= 0.0 {
+        return;
+    }
+    for (channel, source) in pixel.iter_mut().zip([color.r, color.g, color.b]) {
+        let mixed = (f64::from(source) * source_alpha
+            + f64::from(*channel) * dest_alpha * (1.0 - source_alpha))
+            / alpha;
+        *channel = mixed.round() as u8;
+    }
+    pixel[3] = (alpha * 255.0).round() as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HotlinePalette;
+    use std::path::PathBuf;
+
+    /// largest per-channel difference allowed against a golden image, for
+    /// floating point differences between platforms
+    const GOLDEN_TOLERANCE: u8 = 2;
+
+    /// compare a raster with `tests/golden/<name>.pam`; with `HOTLINE_BLESS` set in the
+    /// environment the golden image is written instead
+    fn assert_golden(name: &str, raster: &HotlineRaster) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{name}.pam"));
+        let header = format!(
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            raster.width(),
+            raster.height()
+        );
+        if std::env::var_os("HOTLINE_BLESS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, [header.as_bytes(), raster.pixels()].concat()).unwrap();
+            return;
+        }
+
+        let golden = std::fs::read(&path).unwrap_or_else(|err| {
+            panic!("missing golden image {path:?} ({err}); run with HOTLINE_BLESS=1")
+        });
+        let (golden_header, golden_pixels) = golden.split_at(header.len().min(golden.len()));
+        assert_eq!(
+            golden_header,
+            header.as_bytes(),
+            "size differs from {path:?}"
+        );
+        let worst = golden_pixels
+            .iter()
+            .zip(raster.pixels())
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+        assert!(
+            golden_pixels.len() == raster.pixels().len() && worst <= GOLDEN_TOLERANCE,
+            "raster differs from {path:?} by up to {worst}"
+        );
+    }
+
+    /// palette used by the golden images
+    fn palette() -> HotlinePalette {
+        HotlinePalette::new(&[("#00ff00", 0.0), ("#ffff00", 0.5), ("#ff0000", 1.0)])
+    }
+
+    #[test]
+    fn test_projection() {
+        let bounds = MercatorBounds::new(
+            FlatPosition::new(-10.0, -20.0),
+            FlatPosition::new(10.0, 20.0),
+        );
+        assert_eq!(
+            bounds.to_pixel(&FlatPosition::new(10.0, -20.0), 100, 50),
+            (0.0, 0.0)
+        );
+        let (x, y) = bounds.to_pixel(&FlatPosition::new(0.0, 0.0), 100, 50);
+        assert!((x - 50.0).abs() < 1e-9 && (y - 25.0).abs() < 1e-9);
+
+        let path = [FlatPosition::new(60.0, 10.0), FlatPosition::new(61.0, 14.0)];
+        let fitted = MercatorBounds::fit(&path, 300, 300, 20.0).unwrap();
+        let corners: Vec<_> = path
+            .iter()
+            .map(|pos| fitted.to_pixel(pos, 300, 300))
+            .collect();
+        assert!((corners[0].0 - 20.0).abs() < 1e-6 && (corners[1].0 - 280.0).abs() < 1e-6);
+        assert!((corners[0].1 + corners[1].1 - 300.0).abs() < 1e-6);
+        assert_eq!(MercatorBounds::fit(&path, 30, 30, 20.0), None);
+        assert_eq!(MercatorBounds::fit(&[], 30, 30, 0.0), None);
+    }
+
+    #[test]
+    fn test_gradient_colors() {
+        let positions = HotlinePositionVec::with_normalization(
+            &[(0.0, 0.0, 0.0), (0.0, 1.0, 1.0)],
+            crate::Normalization::None,
+        );
+        let bounds = MercatorBounds::new(FlatPosition::new(-0.5, 0.0), FlatPosition::new(0.5, 1.0));
+        let style = HotlineStyle::new(palette()).with_outline(Color::BLACK, 0.0);
+        let raster = positions.rasterize(&bounds, 100, 20, &style);
+
+        let (start, end) = (style.color_for_value(0.0), style.color_for_value(1.0));
+        let close = |x: u32, expected: Color| {
+            let color = raster.pixel(x, 10).unwrap();
+            let worst = [
+                (color.r, expected.r),
+                (color.g, expected.g),
+                (color.b, expected.b),
+            ]
+            .iter()
+            .map(|(a, b)| a.abs_diff(*b))
+            .max();
+            assert!(
+                worst <= Some(1) && color.a == 255,
+                "{color:?} != {expected:?}"
+            );
+        };
+        close(0, start.mix(&end, 0.005));
+        close(50, start.mix(&end, 0.505));
+        close(99, start.mix(&end, 0.995));
+        assert_eq!(raster.pixel(50, 0), Some(Color::rgba(0, 0, 0, 0)));
+        assert_eq!(raster.pixel(100, 10), None);
+    }
+
+    #[test]
+    fn test_golden_images() {
+        let track = HotlineTrack::new(
+            vec![
+                FlatPosition::new(40.000, -105.000),
+                FlatPosition::new(40.004, -104.994),
+                FlatPosition::new(40.001, -104.988),
+                FlatPosition::new(40.006, -104.982),
+            ],
+            vec![0.0, 0.4, 0.7, 1.0],
+        )
+        .unwrap();
+        let bounds = MercatorBounds::fit(track.positions(), 48, 32, 6.0).unwrap();
+
+        let plain = HotlineStyle::new(palette()).with_outline(Color::BLACK, 0.0);
+        assert_golden("gradient", &track.rasterize(&bounds, 48, 32, &plain));
+
+        let outlined = HotlineStyle::new(palette())
+            .with_weight(4.0)
+            .with_outline(Color::rgba(0, 0, 128, 160), 1.5);
+        let mut raster = HotlineRaster::filled(48, 32, Color::WHITE);
+        raster.draw(track.iter(), &bounds, &outlined);
+        assert_golden("outline", &raster);
+    }
+}
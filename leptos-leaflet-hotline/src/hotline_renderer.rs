@@ -0,0 +1,435 @@
+//! module for the native canvas renderer that draws hotlines, registered as `L.Hotline`
+//!
+//! Leaflet projects and simplifies the hotline like any polyline; the Rust side keeps
+//! each projected point's value (as `z`) through clipping and strokes every segment
+//! with a canvas gradient running between the palette colors of its two end points,
+//! on top of an outline stroke.
+use std::ops::Range;
+
+use js_sys::{Array, Function, Object, Reflect};
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+use leptos_leaflet::leaflet as L;
+
+use super::hotline_color::Color;
+use super::hotline_error::HotlineError;
+use super::hotline_palette::{ColorStop, HotlinePalette};
+use super::hotline_style::HotlineStyle;
+
+#[wasm_bindgen(
+    inline_js = "export function bindThis(method) { return function (...args) { return method(this, ...args); }; }"
+)]
+extern "C" {
+    /// wrap a function taking `this` as its first argument into a JS method
+    #[wasm_bindgen(js_name = "bindThis")]
+    fn bind_this(method: &JsValue) -> Function;
+}
+
+#[wasm_bindgen]
+extern "C" {
+
+    /// struct for binding to the canvas renderer class of [`crate::Hotline`], `L.Hotline.Renderer`
+    #[wasm_bindgen(extends = L::Layer, js_namespace = ["L", "Hotline"], js_name = "Renderer")]
+    #[derive(Debug, Clone)]
+    pub type HotlineRenderer;
+
+    /// construct a new [`HotlineRenderer`] from leaflet `L.Canvas` options such as
+    /// `padding` and `tolerance`; [`register_hotline`] must have been called
+    ///
+    /// # Returns
+    /// [`HotlineRenderer`]
+    ///
+    #[wasm_bindgen(constructor, js_namespace = ["L", "Hotline"], js_class = "Renderer")]
+    pub fn new(options: &JsValue) -> HotlineRenderer;
+}
+
+///
+/// register the native hotline layer as `L.Hotline`, so [`crate::Hotline::new`] works
+/// without loading leaflet-hotline
+///
+/// `L.Hotline` extends `L.Polyline`, and `L.Hotline.Renderer` extends `L.Canvas`.  Each
+/// hotline added without a `renderer` option gets its own renderer in its pane, removed
+/// again with the hotline.  Does nothing if `L.Hotline` is already defined, e.g. because
+/// leaflet-hotline is loaded; `HotPolyline` calls this before creating its layer.
+///
+/// # Returns
+/// [`Result<(), HotlineError>`], [`HotlineError::MissingGlobal`] if leaflet (global `L`)
+/// is not loaded
+///
+pub fn register_hotline() -> Result<(), HotlineError> {
+    let leaflet = get(&js_sys::global(), "L")?;
+    if !leaflet.is_object() {
+        return Err(HotlineError::MissingGlobal("L"));
+    }
+    if Reflect::has(&leaflet, &"Hotline".into())? {
+        return Ok(());
+    }
+
+    let canvas = get(&leaflet, "Canvas")?;
+    let polyline = get(&leaflet, "Polyline")?;
+
+    let renderer = extend(&canvas, &[("_updatePoly", method(update_poly))])?;
+    let before_add = {
+        let (renderer, polyline) = (renderer.clone(), polyline.clone());
+        method(move |this, map| before_add(&this, &map, &renderer, &polyline))
+    };
+    let on_remove = {
+        let polyline = polyline.clone();
+        method(move |this, map| on_remove(&this, &map, &polyline))
+    };
+    let hotline = extend(
+        &polyline,
+        &[
+            ("statics", object(&[("Renderer", renderer)])?.into()),
+            ("options", default_options()?.into()),
+            ("beforeAdd", before_add),
+            ("onRemove", on_remove),
+            ("getRGBForValue", method(rgb_for_value)),
+            ("_clipPoints", method(clip_points)),
+            ("_clickTolerance", method(click_tolerance)),
+        ],
+    )?;
+    Reflect::set(&leaflet, &"Hotline".into(), &hotline)?;
+    Ok(())
+}
+
+/// read a hotline's style from its leaflet options; missing or invalid entries keep
+/// the defaults
+fn style_from_options(options: &JsValue) -> HotlineStyle {
+    let defaults = HotlineStyle::default();
+    let number = |key: &str, default: f64| {
+        get(options, key)
+            .ok()
+            .and_then(|value| value.as_f64())
+            .filter(|value| value.is_finite())
+            .unwrap_or(default)
+    };
+    HotlineStyle {
+        palette: get(options, "palette")
+            .map(|palette| palette_from_js(&palette))
+            .unwrap_or(defaults.palette),
+        min: number("min", defaults.min),
+        max: number("max", defaults.max),
+        weight: number("weight", defaults.weight),
+        outline_width: number("outlineWidth", defaults.outline_width),
+        outline_color: get(options, "outlineColor")
+            .ok()
+            .and_then(|color| color.as_string())
+            .and_then(|color| Color::parse(&color).ok())
+            .unwrap_or(defaults.outline_color),
+    }
+}
+
+/// stroke the outline of every part, then each segment with its value gradient
+fn draw_parts(
+    ctx: &CanvasRenderingContext2d,
+    style: &HotlineStyle,
+    parts: &[Vec<(f64, f64, f64)>],
+) -> Result<(), JsValue> {
+    ctx.set_global_composite_operation("source-over")?;
+    ctx.set_line_cap("round");
+    ctx.set_line_join("round");
+
+    if style.outline_width > 0.0 {
+        ctx.set_line_width(style.outer_width());
+        ctx.set_stroke_style_str(&style.outline_color.to_css());
+        for part in parts.iter().filter(|part| part.len() > 1) {
+            ctx.begin_path();
+            ctx.move_to(part[0].0, part[0].1);
+            for &(x, y, _) in &part[1..] {
+                ctx.line_to(x, y);
+            }
+            ctx.stroke();
+        }
+    }
+
+    ctx.set_line_width(style.weight);
+    for segment in parts.iter().flat_map(|part| part.windows(2)) {
+        let ((x0, y0, from), (x1, y1, to)) = (segment[0], segment[1]);
+        let gradient = ctx.create_linear_gradient(x0, y0, x1, y1);
+        gradient.add_color_stop(0.0, &style.color_for_value(from).to_css())?;
+        gradient.add_color_stop(1.0, &style.color_for_value(to).to_css())?;
+        ctx.set_stroke_style_canvas_gradient(&gradient);
+        ctx.begin_path();
+        ctx.move_to(x0, y0);
+        ctx.line_to(x1, y1);
+        ctx.stroke();
+    }
+    Ok(())
+}
+
+/// index ranges of the runs of consecutive segments whose bounding boxes touch the
+/// pixel bounds `min..=max`; the canvas clips whatever of them lies outside
+fn visible_runs(points: &[(f64, f64)], min: (f64, f64), max: (f64, f64)) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (index, segment) in points.windows(2).enumerate() {
+        let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+        let visible = x0.min(x1) <= max.0
+            && x0.max(x1) >= min.0
+            && y0.min(y1) <= max.1
+            && y0.max(y1) >= min.1;
+        match (visible, start) {
+            (true, None) => start = Some(index),
+            (false, Some(first)) => {
+                runs.push(first..index + 1);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(first) = start {
+        runs.push(first..points.len());
+    }
+    runs
+}
+
+/// read a leaflet-hotline style palette object (breakpoint -> CSS color), falling
+/// back to the default palette if it has no valid stops
+fn palette_from_js(palette: &JsValue) -> HotlinePalette {
+    if !palette.is_object() {
+        return HotlinePalette::default();
+    }
+    let stops = Object::entries(palette.unchecked_ref())
+        .iter()
+        .filter_map(|entry| {
+            let entry: Array = entry.unchecked_into();
+            let breakpoint = entry.get(0).as_string()?.parse::<f64>().ok()?;
+            let color = Color::parse(&entry.get(1).as_string()?).ok()?;
+            Some(ColorStop::new(color, breakpoint))
+        })
+        .collect::<Vec<_>>();
+    HotlinePalette::from_stops(stops)
+        .ok()
+        .filter(|palette| !palette.is_empty())
+        .unwrap_or_default()
+}
+
+/// default options of `L.Hotline`, merged into each instance's options by leaflet
+fn default_options() -> Result<Object, JsValue> {
+    let style = HotlineStyle::default();
+    let palette = Object::new();
+    for stop in style.palette.stops() {
+        Reflect::set(
+            &palette,
+            &JsValue::from_f64(stop.breakpoint),
+            &stop.color.to_css().into(),
+        )?;
+    }
+    object(&[
+        ("palette", palette.into()),
+        ("weight", style.weight.into()),
+        ("outlineColor", style.outline_color.to_css().into()),
+        ("outlineWidth", style.outline_width.into()),
+        ("min", style.min.into()),
+        ("max", style.max.into()),
+    ])
+}
+
+/// `L.Hotline.Renderer#_updatePoly`: draw a hotline's projected parts on the canvas
+fn update_poly(this: JsValue, layer: JsValue) -> Result<JsValue, JsValue> {
+    let parts: Array = get(&layer, "_parts")?.unchecked_into();
+    if !get(&this, "_drawing")?.is_truthy() || parts.length() == 0 {
+        return Ok(JsValue::UNDEFINED);
+    }
+
+    let parts = parts
+        .iter()
+        .map(|part| {
+            part.unchecked_into::<Array>()
+                .iter()
+                .map(|point| {
+                    (
+                        number(&point, "x"),
+                        number(&point, "y"),
+                        number(&point, "z"),
+                    )
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<_>>>();
+    let ctx: CanvasRenderingContext2d = get(&this, "_ctx")?.unchecked_into();
+    draw_parts(&ctx, &style_from_options(&get(&layer, "options")?), &parts)?;
+    Ok(JsValue::UNDEFINED)
+}
+
+/// `L.Hotline#beforeAdd`: give the hotline its own renderer unless one is set
+fn before_add(
+    this: &JsValue,
+    map: &JsValue,
+    renderer_class: &JsValue,
+    polyline: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let options = get(this, "options")?;
+    if get(&options, "renderer")?.is_truthy() {
+        return call_prototype(polyline, "beforeAdd", this, map);
+    }
+
+    let renderer_options = Object::new();
+    let pane = get(&options, "pane")?;
+    if pane.is_string() {
+        Reflect::set(&renderer_options, &"pane".into(), &pane)?;
+    }
+    let renderer = Reflect::construct(
+        renderer_class.unchecked_ref(),
+        &Array::of1(&renderer_options),
+    )?;
+    Reflect::set(this, &"_hotlineRenderer".into(), &renderer)?;
+    Reflect::set(this, &"_renderer".into(), &renderer)?;
+    map.unchecked_ref::<L::Map>()
+        .add_layer(renderer.unchecked_ref());
+    Ok(JsValue::UNDEFINED)
+}
+
+/// `L.Hotline#onRemove`: remove the hotline, then the renderer created for it
+fn on_remove(this: &JsValue, map: &JsValue, polyline: &JsValue) -> Result<JsValue, JsValue> {
+    call_prototype(polyline, "onRemove", this, map)?;
+    let renderer = get(this, "_hotlineRenderer")?;
+    if renderer.is_object() {
+        map.unchecked_ref::<L::Map>()
+            .remove_layer(renderer.unchecked_ref());
+        Reflect::delete_property(this.unchecked_ref(), &"_hotlineRenderer".into())?;
+    }
+    Ok(JsValue::UNDEFINED)
+}
+
+/// `L.Hotline#getRGBForValue`: `[r, g, b]` drawn for a value, as in leaflet-hotline
+fn rgb_for_value(this: JsValue, value: JsValue) -> Result<JsValue, JsValue> {
+    let style = style_from_options(&get(&this, "options")?);
+    let value = value.as_f64().unwrap_or(f64::NAN);
+    let color = style.color_for_value(value);
+    Ok(Array::of3(&color.r.into(), &color.g.into(), &color.b.into()).into())
+}
+
+/// `L.Hotline#_clipPoints`: split the projected rings into the parts to draw, keeping
+/// the original point objects so the values set on them survive simplification
+fn clip_points(this: JsValue, _: JsValue) -> Result<JsValue, JsValue> {
+    let parts = Array::new();
+    Reflect::set(&this, &"_parts".into(), &parts)?;
+
+    let bounds = get(&get(&this, "_renderer")?, "_bounds")?;
+    let px_bounds = get(&this, "_pxBounds")?;
+    if !px_bounds.is_object() || !call_method(&px_bounds, "intersects", &bounds)?.is_truthy() {
+        return Ok(JsValue::UNDEFINED);
+    }
+
+    let rings: Array = get(&this, "_rings")?.unchecked_into();
+    let latlngs: Array = get(&this, "_latlngs")?.unchecked_into();
+    let flat = !Array::is_array(&latlngs.get(0));
+    let no_clip = get(&get(&this, "options")?, "noClip")?.is_truthy();
+    let corner = |name: &str| -> Result<(f64, f64), JsValue> {
+        let point = get(&bounds, name)?;
+        Ok((number(&point, "x"), number(&point, "y")))
+    };
+    let (min, max) = (corner("min")?, corner("max")?);
+
+    for (index, ring) in rings.iter().enumerate() {
+        let ring: Array = ring.unchecked_into();
+        let latlngs: Array = if flat {
+            latlngs.clone()
+        } else {
+            latlngs.get(index as u32).unchecked_into()
+        };
+
+        let mut points = Vec::with_capacity(ring.length() as usize);
+        for (point, latlng) in ring.iter().zip(latlngs.iter()) {
+            Reflect::set(&point, &"z".into(), &get(&latlng, "alt")?)?;
+            points.push((number(&point, "x"), number(&point, "y")));
+        }
+
+        let runs: Vec<Range<usize>> = if no_clip {
+            std::iter::once(0..points.len()).collect()
+        } else {
+            visible_runs(&points, min, max)
+        };
+        for run in runs.into_iter().filter(|run| !run.is_empty()) {
+            parts.push(&ring.slice(run.start as u32, run.end as u32));
+        }
+    }
+    Ok(JsValue::UNDEFINED)
+}
+
+/// `L.Hotline#_clickTolerance`: half the line plus the outline and renderer tolerance
+fn click_tolerance(this: JsValue, _: JsValue) -> Result<JsValue, JsValue> {
+    let style = style_from_options(&get(&this, "options")?);
+    let tolerance = get(&this, "_renderer")
+        .and_then(|renderer| get(&renderer, "options"))
+        .map(|options| number(&options, "tolerance"))
+        .ok()
+        .filter(|tolerance| tolerance.is_finite())
+        .unwrap_or(0.0);
+    Ok((style.outer_width() / 2.0 + tolerance).into())
+}
+
+/// wrap a Rust function into a JS method receiving `this` and its first argument
+fn method(f: impl Fn(JsValue, JsValue) -> Result<JsValue, JsValue> + 'static) -> JsValue {
+    let closure = Closure::<dyn Fn(JsValue, JsValue) -> Result<JsValue, JsValue>>::new(f);
+    bind_this(&closure.into_js_value()).into()
+}
+
+/// create a leaflet class from `class.extend(props)`
+fn extend(class: &JsValue, props: &[(&str, JsValue)]) -> Result<JsValue, JsValue> {
+    call_method(class, "extend", &object(props)?.into())
+}
+
+/// call a method of a leaflet class prototype on `this`
+fn call_prototype(
+    class: &JsValue,
+    name: &str,
+    this: &JsValue,
+    arg: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let function: Function = get(&get(class, "prototype")?, name)?.dyn_into()?;
+    function.call1(this, arg)
+}
+
+/// call a method of a JS object with one argument
+fn call_method(target: &JsValue, name: &str, arg: &JsValue) -> Result<JsValue, JsValue> {
+    let function: Function = get(target, name)?.dyn_into()?;
+    function.call1(target, arg)
+}
+
+/// create a JS object from key, value pairs
+fn object(props: &[(&str, JsValue)]) -> Result<Object, JsValue> {
+    let object = Object::new();
+    for (key, value) in props {
+        Reflect::set(&object, &JsValue::from_str(key), value)?;
+    }
+    Ok(object)
+}
+
+/// read a property of a JS value
+fn get(target: &JsValue, key: &str) -> Result<JsValue, JsValue> {
+    Reflect::get(target, &JsValue::from_str(key))
+}
+
+/// read a numeric property of a JS value, NaN if it is missing or not a number
+fn number(target: &JsValue, key: &str) -> f64 {
+    get(target, key)
+        .ok()
+        .and_then(|value| value.as_f64())
+        .unwrap_or(f64::NAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_runs() {
+        let points = [
+            (-50.0, 10.0),
+            (-20.0, 10.0),
+            (10.0, 10.0),
+            (50.0, 10.0),
+            (150.0, 10.0),
+            (200.0, 10.0),
+            (90.0, 90.0),
+        ];
+        let runs = visible_runs(&points, (0.0, 0.0), (100.0, 100.0));
+        assert_eq!(runs, vec![1..5, 5..7]);
+        assert!(visible_runs(&points[..2], (0.0, 0.0), (100.0, 100.0)).is_empty());
+        assert!(visible_runs(&points[..1], (-100.0, 0.0), (100.0, 100.0)).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! module for the resource-backed hot polyline functional component
+use leptos::children::Children;
+use leptos::prelude::*;
+use wasm_bindgen::JsValue;
+
+use crate::hotline_component::report_error;
+use crate::{
+    HotPolyline, HotlineError, HotlineEvents, HotlinePalette, HotlinePositionVec, HotlineTrack,
+    PackedChunk, PackedPositions,
+};
+
+///
+/// data a [`HotPolylineResource`] can draw once its resource has loaded
+///
+/// Implemented for the track types, their packed forms, and `Option` / `Result`
+/// wrappers of them; `None` draws nothing, while `Err` and malformed packed data
+/// draw nothing and are reported as a [`HotlineError`].
+///
+pub trait IntoHotlineTrack {
+    ///
+    /// convert into the track to draw
+    ///
+    /// # Returns
+    /// [`Result<Option<HotlineTrack>, HotlineError>`], `Ok(None)` if there is nothing
+    /// to draw
+    ///
+    fn into_hotline_track(self) -> Result<Option<HotlineTrack>, HotlineError>;
+}
+
+impl IntoHotlineTrack for HotlineTrack {
+    #[inline]
+    fn into_hotline_track(self) -> Result<Option<HotlineTrack>, HotlineError> {
+        Ok(Some(self))
+    }
+}
+
+impl IntoHotlineTrack for HotlinePositionVec {
+    #[inline]
+    fn into_hotline_track(self) -> Result<Option<HotlineTrack>, HotlineError> {
+        Ok(Some(self.into()))
+    }
+}
+
+impl IntoHotlineTrack for PackedPositions {
+    #[inline]
+    fn into_hotline_track(self) -> Result<Option<HotlineTrack>, HotlineError> {
+        HotlinePositionVec::from_packed(&self)?.into_hotline_track()
+    }
+}
+
+impl IntoHotlineTrack for Vec<PackedChunk> {
+    #[inline]
+    fn into_hotline_track(self) -> Result<Option<HotlineTrack>, HotlineError> {
+        HotlinePositionVec::from_packed_chunks(self)?.into_hotline_track()
+    }
+}
+
+impl<T: IntoHotlineTrack> IntoHotlineTrack for Option<T> {
+    #[inline]
+    fn into_hotline_track(self) -> Result<Option<HotlineTrack>, HotlineError> {
+        self.map_or(Ok(None), IntoHotlineTrack::into_hotline_track)
+    }
+}
+
+impl<T: IntoHotlineTrack, E: std::fmt::Display> IntoHotlineTrack for Result<T, E> {
+    #[inline]
+    fn into_hotline_track(self) -> Result<Option<HotlineTrack>, HotlineError> {
+        self.map_err(|err| HotlineError::ResourceFailed(err.to_string()))?
+            .into_hotline_track()
+    }
+}
+
+///
+/// Creates a [`HotPolyline`] whose positions are loaded by a leptos [`Resource`],
+/// e.g. from a server function, showing a placeholder until the data arrives
+///
+/// The hotline layer is added to the map right away and updated in place once the
+/// resource resolves, and again whenever it is refetched.  The `fallback` is rendered
+/// inside a `Suspense`, so it is part of the server-rendered HTML while the resource
+/// is pending; as a child of the map container, it usually needs absolute positioning.
+///
+/// # Arguments
+///
+/// * `resource` - resource resolving to any [`IntoHotlineTrack`] type, such as
+///   `Result<Vec<PackedChunk>, ServerFnError>`
+/// * `fallback` - placeholder view shown while the resource is pending
+/// * `on_error` - called with a [`HotlineError`] when the resource resolves to an
+///   error or to packed data that cannot be unpacked, and forwarded to [`HotPolyline`]
+/// * `palette`, `outline_color`, `max`, `min`, `weight`, `outline_width`, `smooth_factor`,
+///   `no_clip`, `interactive`, `pane`, `class_name`, `renderer`, `events` and `children` -
+///   as for [`HotPolyline`]
+///
+/// # Returns
+///
+/// `impl` [`leptos::IntoView`]
+///
+/// # Examples
+///
+/// ```ignore
+/// let track = Resource::new(|| (), |_| fetch_packed_chunks(track_chunk));
+///
+/// view! {
+///     <MapContainer class="map-container" center=Position::new(40.2928, -105.6170) zoom=15.0 set_view=true>
+///         <HotPolylineResource
+///             resource=track
+///             fallback=|| view! { <div class="map-loading">"Loading track..."</div> }
+///             palette=HotlinePalette::new(&[("green", 0.0), ("red", 1.0)])
+///         />
+///     </MapContainer>
+/// }
+/// ```
+///
+#[component]
+pub fn HotPolylineResource<T, Ser>(
+    resource: Resource<T, Ser>,
+    #[prop(optional, into)] fallback: ViewFn,
+    #[prop(into)] palette: Signal<HotlinePalette>,
+    #[prop(optional, into)] outline_color: Signal<Option<String>>,
+    #[prop(optional, into)] max: Signal<Option<f64>>,
+    #[prop(optional, into)] min: Signal<Option<f64>>,
+    #[prop(optional, into)] weight: Signal<Option<f64>>,
+    #[prop(optional, into)] outline_width: Signal<Option<f64>>,
+    #[prop(optional, into)] smooth_factor: Signal<Option<f64>>,
+    #[prop(optional, into)] no_clip: Signal<Option<bool>>,
+    #[prop(optional, into)] interactive: Signal<Option<bool>>,
+    #[prop(optional, into)] pane: Signal<Option<String>>,
+    #[prop(optional, into)] class_name: Signal<Option<String>>,
+    #[prop(optional)] renderer: Option<JsValue>,
+    #[prop(optional)] events: HotlineEvents,
+    #[prop(optional, into)] on_error: Option<Callback<(HotlineError,)>>,
+    #[prop(optional)] children: Option<Children>,
+) -> impl IntoView
+where
+    T: IntoHotlineTrack + Clone + Send + Sync + 'static,
+    Ser: Send + Sync + 'static,
+{
+    // memoized, so the loaded data is decoded once rather than on every read
+    let loaded = Memo::new(move |_| resource.get().into_hotline_track());
+    let positions =
+        Memo::new(move |_| loaded.with(|loaded| loaded.clone().ok().flatten().unwrap_or_default()));
+    Effect::watch(
+        move || loaded.with(|loaded| loaded.as_ref().map(|_| ()).map_err(Clone::clone)),
+        move |result, _, _| report_error(on_error, result.clone()),
+        true,
+    );
+
+    view! {
+        <Suspense fallback=move || fallback.run()>
+            {move || Suspend::new(async move {
+                let _ = resource.await;
+            })}
+        </Suspense>
+        <HotPolyline
+            positions=positions
+            palette=palette
+            outline_color=outline_color
+            max=max
+            min=min
+            weight=weight
+            outline_width=outline_width
+            smooth_factor=smooth_factor
+            no_clip=no_clip
+            interactive=interactive
+            pane=pane
+            class_name=class_name
+            renderer=renderer.unwrap_or(JsValue::UNDEFINED)
+            events=events
+            on_error=move |err: HotlineError| report_error(on_error, Err(err))
+        >
+            {children.map(|children| children())}
+        </HotPolyline>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_hotline_track() {
+        let positions = HotlinePositionVec::new(&[(40.0, -105.0, 10.0), (40.1, -105.1, 20.0)]);
+        let packed: Result<_, String> = Ok(positions.to_packed_chunks(1));
+        assert_eq!(
+            packed
+                .into_hotline_track()
+                .map(|track| track.map(|track| track.len())),
+            Ok(Some(2))
+        );
+
+        let failed: Result<PackedPositions, String> = Err("server error".to_string());
+        assert_eq!(
+            failed.into_hotline_track(),
+            Err(HotlineError::ResourceFailed("server error".to_string()))
+        );
+        assert_eq!(
+            PackedPositions::from_bytes(vec![1, 2, 3]).into_hotline_track(),
+            Err(HotlineError::InvalidPacked(
+                crate::PackedError::InvalidHeader
+            ))
+        );
+        assert_eq!(None::<HotlineTrack>.into_hotline_track(), Ok(None));
+        assert_eq!(
+            Some(positions.clone()).into_hotline_track(),
+            Ok(Some(HotlineTrack::from(positions)))
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! module for the per-point values shared by the activity file importers (GPX, FIT, TCX)
+use super::hotline_geometry::haversine_distance;
+use super::hotline_position::FlatPosition;
+
+///
+/// value computed for each pair of consecutive points from the two points and the
+/// distance between them in meters, assigned to the second point
+///
+/// The first point takes the value of the pair after it.
+///
+pub(crate) fn between_points<T>(
+    points: &[T],
+    position: impl Fn(&T) -> FlatPosition,
+    value: impl Fn(&T, &T, f64) -> Option<f64>,
+) -> Vec<Option<f64>> {
+    let pairs = points.windows(2).map(|pair| {
+        let distance = haversine_distance(&position(&pair[0]), &position(&pair[1]));
+        value(&pair[0], &pair[1], distance)
+    });
+    let mut values: Vec<Option<f64>> = Vec::with_capacity(points.len());
+    for pair_value in pairs {
+        if values.is_empty() {
+            values.push(pair_value);
+        }
+        values.push(pair_value);
+    }
+    values.resize(points.len(), None);
+    values
+}
+
+/// replace missing values with the previous known value, or the first known one at the start
+pub(crate) fn fill_gaps(values: Vec<Option<f64>>) -> Option<Vec<f64>> {
+    let first = values.iter().copied().flatten().next()?;
+    let mut last = first;
+    Some(
+        values
+            .into_iter()
+            .map(|value| {
+                last = value.unwrap_or(last);
+                last
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_between_points() {
+        let points = [
+            FlatPosition::new(0.0, 0.0),
+            FlatPosition::new(0.0, 0.001),
+            FlatPosition::new(0.0, 0.003),
+        ];
+        let distances = between_points(&points, |point| *point, |_, _, distance| Some(distance));
+        assert_eq!(distances[0], distances[1]);
+        assert!((distances[2].unwrap() - 2.0 * distances[1].unwrap()).abs() < 1e-6);
+        assert!(between_points(&points[..1], |point| *point, |_, _, _| Some(1.0))[0].is_none());
+    }
+
+    #[test]
+    fn test_fill_gaps() {
+        assert_eq!(
+            fill_gaps(vec![None, Some(1.0), None, Some(3.0)]),
+            Some(vec![1.0, 1.0, 1.0, 3.0])
+        );
+        assert_eq!(fill_gaps(vec![None, None]), None);
+    }
+}
@@ -0,0 +1,215 @@
+//! module for the compact serde wire format of the hotline data types
+//!
+//! * [`FlatPosition`] is a `[lat, lng]` array
+//! * [`HotlinePosition`] is a `[lat, lng, value]` array
+//! * [`Color`] is a CSS hex string, `"#rrggbb"` or `"#rrggbbaa"`
+//! * [`ColorStop`] is a `[color, breakpoint]` array and [`HotlinePalette`] an array of stops
+//! * [`HotlinePositionVec`] is `{"points": [[lat, lng, raw value], ...], "normalization": ...}`;
+//!   the normalized values are recomputed on deserialization and `normalization` is
+//!   omitted when it is the default
+//! * [`PackedPositions`] is a base64 string in human-readable formats and raw bytes otherwise
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::hotline_color::Color;
+use super::hotline_normalization::Normalization;
+use super::hotline_packed::PackedPositions;
+use super::hotline_palette::{ColorStop, HotlinePalette};
+use super::hotline_position::{FlatPosition, HotlinePosition, HotlinePositionVec};
+
+impl Serialize for FlatPosition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.lat, self.lng).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FlatPosition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (lat, lng) = <(f64, f64)>::deserialize(deserializer)?;
+        Ok(Self::new(lat, lng))
+    }
+}
+
+impl Serialize for HotlinePosition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.latlng.lat, self.latlng.lng, self.alt).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for HotlinePosition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (lat, lng, alt) = <(f64, f64, f64)>::deserialize(deserializer)?;
+        Ok(Self::new(lat, lng, alt))
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let css = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Self::parse(&css).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for ColorStop {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.color, self.breakpoint).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorStop {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (color, breakpoint) = <(Color, f64)>::deserialize(deserializer)?;
+        Ok(Self::new(color, breakpoint))
+    }
+}
+
+impl Serialize for HotlinePalette {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.stops().serialize(serializer)
+    }
+}
+
+/// palettes are validated as in [`HotlinePalette::from_stops`]
+impl<'de> Deserialize<'de> for HotlinePalette {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let stops = Vec::<ColorStop>::deserialize(deserializer)?;
+        Self::from_stops(stops).map_err(D::Error::custom)
+    }
+}
+
+/// wire form of [`HotlinePositionVec`]: raw points plus the normalization to reapply
+#[derive(Serialize, Deserialize)]
+struct PositionVecWire {
+    points: Vec<(f64, f64, f64)>,
+    #[serde(default, skip_serializing_if = "is_default_normalization")]
+    normalization: Normalization,
+}
+
+/// true for the normalization [`HotlinePositionVec::new`] uses
+fn is_default_normalization(normalization: &Normalization) -> bool {
+    *normalization == Normalization::default()
+}
+
+impl Serialize for HotlinePositionVec {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PositionVecWire {
+            points: self.raw_points(),
+            normalization: self.normalization,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for HotlinePositionVec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = PositionVecWire::deserialize(deserializer)?;
+        Ok(Self::with_normalization(&wire.points, wire.normalization))
+    }
+}
+
+impl Serialize for PackedPositions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&BASE64.encode(self.as_bytes()))
+        } else {
+            serializer.serialize_bytes(self.as_bytes())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PackedPositions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let encoded = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+            let bytes = BASE64
+                .decode(encoded.as_bytes())
+                .map_err(D::Error::custom)?;
+            Ok(Self::from_bytes(bytes))
+        } else {
+            let bytes = <std::borrow::Cow<'de, [u8]>>::deserialize(deserializer)?;
+            Ok(Self::from_bytes(bytes.into_owned()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PackedChunk;
+
+    #[test]
+    fn test_positions_wire_format() {
+        let position = HotlinePosition::new(40.5, -105.25, 0.75);
+        assert_eq!(
+            serde_json::to_string(&position).unwrap(),
+            "[40.5,-105.25,0.75]"
+        );
+        assert_eq!(
+            serde_json::from_str::<FlatPosition>("[40.5,-105.25]").unwrap(),
+            position.latlng
+        );
+
+        let positions = HotlinePositionVec::new(&[(40.0, -105.0, 10.0), (40.1, -105.1, 20.0)]);
+        let json = serde_json::to_string(&positions).unwrap();
+        assert_eq!(
+            json,
+            r#"{"points":[[40.0,-105.0,10.0],[40.1,-105.1,20.0]]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<HotlinePositionVec>(&json).unwrap(),
+            positions
+        );
+
+        let clamped = HotlinePositionVec::with_normalization(
+            &[(40.0, -105.0, 10.0), (40.1, -105.1, 20.0)],
+            Normalization::FixedDomain { lo: 0.0, hi: 40.0 },
+        );
+        let json = serde_json::to_string(&clamped).unwrap();
+        assert_eq!(
+            serde_json::from_str::<HotlinePositionVec>(&json).unwrap(),
+            clamped
+        );
+    }
+
+    #[test]
+    fn test_palette_wire_format() {
+        let palette = HotlinePalette::new(&[("green", 0.0), ("rgba(255, 0, 0, 0.5)", 1.0)]);
+        let json = serde_json::to_string(&palette).unwrap();
+        assert_eq!(json, r##"[["#008000",0.0],["#ff000080",1.0]]"##);
+        assert_eq!(
+            serde_json::from_str::<HotlinePalette>(&json).unwrap(),
+            palette
+        );
+
+        // the fallback reason is not serialized, and not compared
+        let fallback = HotlinePalette::new(&[("gren", 0.0)]);
+        let json = serde_json::to_string(&fallback).unwrap();
+        assert_eq!(
+            serde_json::from_str::<HotlinePalette>(&json).unwrap(),
+            fallback
+        );
+
+        assert!(serde_json::from_str::<HotlinePalette>(r#"[["red",1.5]]"#).is_err());
+        assert!(serde_json::from_str::<HotlinePalette>(r#"[["not a color",0.5]]"#).is_err());
+    }
+
+    #[test]
+    fn test_packed_wire_format() {
+        let positions = HotlinePositionVec::new(&[(40.0, -105.0, 10.0), (40.1, -105.1, 20.0)]);
+        let chunk = positions.to_packed_chunks(10).remove(0);
+        let json = serde_json::to_string(&chunk).unwrap();
+        assert!(
+            json.starts_with(r#"{"index":0,"total":1,"positions":"SExQ"#),
+            "{json}"
+        );
+        assert_eq!(serde_json::from_str::<PackedChunk>(&json).unwrap(), chunk);
+    }
+}
@@ -0,0 +1,111 @@
+//! module for the drawing style shared by the hotline renderers
+use super::hotline_color::Color;
+use super::hotline_palette::HotlinePalette;
+
+/// default line width in pixels, as in leaflet-hotline
+pub const DEFAULT_WEIGHT: f64 = 5.0;
+
+/// default outline width in pixels on each side of the line, as in leaflet-hotline
+pub const DEFAULT_OUTLINE_WIDTH: f64 = 1.0;
+
+///
+/// how a hotline is drawn: palette, value range and line widths
+///
+/// The defaults match leaflet-hotline: the default palette over `0..=1`, a 5 pixel
+/// line and a 1 pixel black outline.
+///
+/// # Fields
+/// * `palette` [`HotlinePalette`] colors the values are drawn with
+/// * `min`, `max` [`f64`] values drawn with the first and last palette color
+/// * `weight` [`f64`] line width in pixels
+/// * `outline_width` [`f64`] outline width in pixels on each side of the line, 0 for none
+/// * `outline_color` [`Color`] outline color
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct HotlineStyle {
+    /// colors the values are drawn with
+    pub palette: HotlinePalette,
+    /// value drawn with the first palette color
+    pub min: f64,
+    /// value drawn with the last palette color
+    pub max: f64,
+    /// line width in pixels
+    pub weight: f64,
+    /// outline width in pixels on each side of the line
+    pub outline_width: f64,
+    /// outline color
+    pub outline_color: Color,
+}
+
+/// implement default for [`HotlineStyle`]
+impl Default for HotlineStyle {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            palette: HotlinePalette::default(),
+            min: 0.0,
+            max: 1.0,
+            weight: DEFAULT_WEIGHT,
+            outline_width: DEFAULT_OUTLINE_WIDTH,
+            outline_color: Color::BLACK,
+        }
+    }
+}
+
+/// implement builder methods for [`HotlineStyle`]
+impl HotlineStyle {
+    ///
+    /// construct a new [`HotlineStyle`] drawing with `palette`, other options default
+    ///
+    /// # Returns
+    /// [`HotlineStyle`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn new(palette: HotlinePalette) -> Self {
+        Self {
+            palette,
+            ..Self::default()
+        }
+    }
+
+    /// set the values drawn with the first and last palette color
+    #[must_use]
+    #[inline]
+    pub fn with_range(self, min: f64, max: f64) -> Self {
+        Self { min, max, ..self }
+    }
+
+    /// set the line width in pixels
+    #[must_use]
+    #[inline]
+    pub fn with_weight(self, weight: f64) -> Self {
+        Self { weight, ..self }
+    }
+
+    /// set the outline color and width in pixels on each side of the line
+    #[must_use]
+    #[inline]
+    pub fn with_outline(self, outline_color: Color, outline_width: f64) -> Self {
+        Self {
+            outline_color,
+            outline_width,
+            ..self
+        }
+    }
+
+    /// color drawn for a value, as in [`HotlinePalette::color_for_value`]
+    #[must_use]
+    #[inline]
+    pub fn color_for_value(&self, value: f64) -> Color {
+        self.palette.color_for_value(value, self.min, self.max)
+    }
+
+    /// width in pixels covered by the line and its outline
+    #[must_use]
+    #[inline]
+    pub fn outer_width(&self) -> f64 {
+        self.weight + 2.0 * self.outline_width.max(0.0)
+    }
+}
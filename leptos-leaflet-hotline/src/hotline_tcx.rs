@@ -0,0 +1,313 @@
+//! module for importing Garmin TCX activity files as hotline tracks
+use std::fmt;
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use super::hotline_activity::{activity_tracks, ActivityMetric, ActivityRecord};
+use super::hotline_normalization::Normalization;
+use super::hotline_position::FlatPosition;
+use super::hotline_time::parse_rfc3339;
+use super::hotline_track::HotlineTrack;
+
+///
+/// error returned when a TCX document cannot be imported
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum TcxError {
+    /// the document is not well-formed XML
+    Xml {
+        /// byte offset of the error in the document
+        position: u64,
+        /// description of the XML error
+        message: String,
+    },
+    /// no trackpoint of a track carries the data needed for the metric
+    MissingMetric(ActivityMetric),
+}
+
+impl fmt::Display for TcxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Xml { position, message } => {
+                write!(f, "invalid TCX XML at byte {position}: {message}")
+            }
+            Self::MissingMetric(metric) => {
+                write!(f, "TCX track has no data for metric {metric:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TcxError {}
+
+/// a trackpoint being read; the position is only known once both coordinates are read
+#[derive(Debug, Default, Clone, Copy)]
+struct TcxPoint {
+    lat: Option<f64>,
+    lng: Option<f64>,
+    record: ActivityRecord,
+}
+
+/// trackpoint child element whose text is being read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TcxField {
+    Time,
+    Latitude,
+    Longitude,
+    Altitude,
+    HeartRate,
+    Cadence,
+    Speed,
+    Power,
+    Temperature,
+}
+
+/// implement TCX import for [`HotlineTrack`]
+impl HotlineTrack {
+    ///
+    /// import the trackpoints of a TCX activity document
+    ///
+    /// Each `Track` becomes one [`HotlineTrack`], with the trackpoint times kept as
+    /// timestamps.  Trackpoints without a position are skipped, trackpoints missing
+    /// the metric take the value of the nearest earlier point (or the first later
+    /// one), and values are normalized as in [`crate::HotlinePositionVec::new`], over
+    /// the whole activity so that laps share one color scale; see
+    /// [`HotlineTrack::from_tcx_with_normalization`] to pick another [`Normalization`].
+    /// Speed, power and temperature are read from the `TPX` activity extension.
+    ///
+    /// # Returns
+    /// [`Vec<HotlineTrack>`], or [`TcxError`] if the document is malformed or a track
+    /// has no data for `metric`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{ActivityMetric, HotlineTrack};
+    ///
+    /// let tcx = r#"<TrainingCenterDatabase><Activities><Activity><Lap><Track>
+    ///     <Trackpoint>
+    ///         <Time>2024-05-01T07:30:00Z</Time>
+    ///         <Position><LatitudeDegrees>40.2928</LatitudeDegrees><LongitudeDegrees>-105.618</LongitudeDegrees></Position>
+    ///         <HeartRateBpm><Value>121</Value></HeartRateBpm>
+    ///     </Trackpoint>
+    /// </Track></Lap></Activity></Activities></TrainingCenterDatabase>"#;
+    /// let tracks = HotlineTrack::from_tcx(tcx, ActivityMetric::HeartRate).unwrap();
+    /// assert_eq!(tracks[0].raw_values(), &[121.0]);
+    /// assert_eq!(tracks[0].timestamps(), Some(&[1_714_548_600.0][..]));
+    /// ```
+    ///
+    #[inline]
+    pub fn from_tcx(tcx: &str, metric: ActivityMetric) -> Result<Vec<Self>, TcxError> {
+        Self::from_tcx_with_normalization(tcx, metric, Normalization::default())
+    }
+
+    ///
+    /// import the trackpoints of a TCX activity document, normalizing the values of
+    /// the whole activity with the given strategy
+    ///
+    /// # Returns
+    /// [`Vec<HotlineTrack>`], or [`TcxError`] as for [`HotlineTrack::from_tcx`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{ActivityMetric, HotlineTrack, Normalization};
+    ///
+    /// let tcx = r#"<TrainingCenterDatabase><Activities><Activity><Lap><Track>
+    ///     <Trackpoint>
+    ///         <Position><LatitudeDegrees>40.2928</LatitudeDegrees><LongitudeDegrees>-105.618</LongitudeDegrees></Position>
+    ///         <HeartRateBpm><Value>121</Value></HeartRateBpm>
+    ///     </Trackpoint>
+    /// </Track></Lap><Lap><Track>
+    ///     <Trackpoint>
+    ///         <Position><LatitudeDegrees>40.2938</LatitudeDegrees><LongitudeDegrees>-105.618</LongitudeDegrees></Position>
+    ///         <HeartRateBpm><Value>141</Value></HeartRateBpm>
+    ///     </Trackpoint>
+    /// </Track></Lap></Activity></Activities></TrainingCenterDatabase>"#;
+    /// let laps =
+    ///     HotlineTrack::from_tcx_with_normalization(tcx, ActivityMetric::HeartRate, Normalization::MinMax)
+    ///         .unwrap();
+    /// assert_eq!((laps[0].values()[0], laps[1].values()[0]), (0.0, 1.0));
+    /// ```
+    ///
+    pub fn from_tcx_with_normalization(
+        tcx: &str,
+        metric: ActivityMetric,
+        normalization: Normalization,
+    ) -> Result<Vec<Self>, TcxError> {
+        activity_tracks(&parse_tracks(tcx)?, metric, normalization)
+            .ok_or(TcxError::MissingMetric(metric))
+    }
+}
+
+/// read the positioned trackpoints of every track, in document order
+fn parse_tracks(tcx: &str) -> Result<Vec<Vec<ActivityRecord>>, TcxError> {
+    let mut reader = Reader::from_str(tcx);
+    reader.config_mut().trim_text(true);
+
+    let mut tracks = Vec::new();
+    let mut track: Option<Vec<ActivityRecord>> = None;
+    let mut point: Option<TcxPoint> = None;
+    let mut field: Option<TcxField> = None;
+    let mut in_heart_rate = false;
+
+    loop {
+        let position = reader.buffer_position();
+        let xml_error = |err: &dyn fmt::Display| TcxError::Xml {
+            position,
+            message: err.to_string(),
+        };
+        let event = reader.read_event().map_err(|err| xml_error(&err))?;
+
+        match event {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"Track" => track = Some(Vec::new()),
+                b"Trackpoint" if track.is_some() => point = Some(TcxPoint::default()),
+                b"HeartRateBpm" => in_heart_rate = true,
+                name if point.is_some() => field = point_field(name, in_heart_rate),
+                _ => {}
+            },
+            Event::Text(text) => {
+                if let (Some(current), Some(field)) = (point.as_mut(), field) {
+                    let text = text.unescape().map_err(|err| xml_error(&err))?;
+                    set_field(current, field, &text);
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"Track" => {
+                    tracks.extend(track.take().filter(|records| !records.is_empty()));
+                }
+                b"Trackpoint" => {
+                    if let (Some(records), Some(current)) = (track.as_mut(), point.take()) {
+                        if let (Some(lat), Some(lng)) = (current.lat, current.lng) {
+                            records.push(ActivityRecord {
+                                position: FlatPosition::new(lat, lng),
+                                ..current.record
+                            });
+                        }
+                    }
+                }
+                b"HeartRateBpm" => in_heart_rate = false,
+                _ => field = None,
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(tracks)
+}
+
+/// trackpoint child elements read for the metrics, matched by local name so any
+/// extension namespace prefix (`ns3:`, `ax:`, ...) is accepted
+fn point_field(name: &[u8], in_heart_rate: bool) -> Option<TcxField> {
+    match name {
+        b"Time" => Some(TcxField::Time),
+        b"LatitudeDegrees" => Some(TcxField::Latitude),
+        b"LongitudeDegrees" => Some(TcxField::Longitude),
+        b"AltitudeMeters" => Some(TcxField::Altitude),
+        b"Value" if in_heart_rate => Some(TcxField::HeartRate),
+        b"Cadence" | b"RunCadence" => Some(TcxField::Cadence),
+        b"Speed" => Some(TcxField::Speed),
+        b"Watts" => Some(TcxField::Power),
+        b"Temperature" => Some(TcxField::Temperature),
+        _ => None,
+    }
+}
+
+/// store the text of a trackpoint child element; unparseable text is ignored
+fn set_field(point: &mut TcxPoint, field: TcxField, text: &str) {
+    if field == TcxField::Time {
+        point.record.time = parse_rfc3339(text);
+        return;
+    }
+    let value = text.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+    match field {
+        TcxField::Latitude => point.lat = value,
+        TcxField::Longitude => point.lng = value,
+        TcxField::Altitude => point.record.altitude = value,
+        TcxField::HeartRate => point.record.heart_rate = value,
+        TcxField::Cadence => point.record.cadence = value,
+        TcxField::Speed => point.record.speed = value,
+        TcxField::Power => point.record.power = value,
+        TcxField::Temperature => point.record.temperature = value,
+        TcxField::Time => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TCX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TrainingCenterDatabase xmlns="http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2"
+    xmlns:ns3="http://www.garmin.com/xmlschemas/ActivityExtension/v2">
+  <Activities>
+    <Activity Sport="Biking">
+      <Lap StartTime="2024-05-01T07:30:00Z">
+        <Cadence>85</Cadence>
+        <Track>
+          <Trackpoint>
+            <Time>2024-05-01T07:30:00Z</Time>
+            <Position><LatitudeDegrees>0.0</LatitudeDegrees><LongitudeDegrees>0.0</LongitudeDegrees></Position>
+            <AltitudeMeters>100.5</AltitudeMeters>
+            <HeartRateBpm><Value>120</Value></HeartRateBpm>
+            <Cadence>80</Cadence>
+            <Extensions><ns3:TPX><ns3:Speed>5.5</ns3:Speed><ns3:Watts>210</ns3:Watts></ns3:TPX></Extensions>
+          </Trackpoint>
+          <Trackpoint>
+            <Time>2024-05-01T07:30:01Z</Time>
+            <HeartRateBpm><Value>121</Value></HeartRateBpm>
+          </Trackpoint>
+          <Trackpoint>
+            <Time>2024-05-01T07:30:02Z</Time>
+            <Position><LatitudeDegrees>0.0</LatitudeDegrees><LongitudeDegrees>0.0001</LongitudeDegrees></Position>
+            <HeartRateBpm><Value>125</Value></HeartRateBpm>
+            <Extensions><ns3:TPX><ns3:Speed>6.0</ns3:Speed></ns3:TPX></Extensions>
+          </Trackpoint>
+        </Track>
+      </Lap>
+      <Lap StartTime="2024-05-01T08:00:00Z">
+        <Track>
+          <Trackpoint>
+            <Position><LatitudeDegrees>1.0</LatitudeDegrees><LongitudeDegrees>1.0</LongitudeDegrees></Position>
+            <HeartRateBpm><Value>140</Value></HeartRateBpm>
+          </Trackpoint>
+        </Track>
+      </Lap>
+    </Activity>
+  </Activities>
+</TrainingCenterDatabase>"#;
+
+    #[test]
+    fn test_tracks_and_metrics() {
+        let tracks = HotlineTrack::from_tcx(TCX, ActivityMetric::HeartRate).unwrap();
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].raw_values(), &[120.0, 125.0]);
+        assert_eq!(
+            tracks[0].timestamps(),
+            Some(&[1_714_548_600.0, 1_714_548_602.0][..])
+        );
+        assert_eq!(tracks[1].timestamps(), None);
+
+        let records = &parse_tracks(TCX).unwrap()[0];
+        assert_eq!(records[0].cadence, Some(80.0));
+        assert_eq!(records[0].power, Some(210.0));
+        assert_eq!(records[1].speed, Some(6.0));
+        assert_eq!(records[0].altitude, Some(100.5));
+    }
+
+    #[test]
+    fn test_missing_metric_and_malformed_xml() {
+        assert_eq!(
+            HotlineTrack::from_tcx(TCX, ActivityMetric::Temperature),
+            Err(TcxError::MissingMetric(ActivityMetric::Temperature))
+        );
+        assert!(matches!(
+            HotlineTrack::from_tcx("<Track><Trackpoint></Track>", ActivityMetric::Speed),
+            Err(TcxError::Xml { .. })
+        ));
+    }
+}
@@ -0,0 +1,109 @@
+//! module for parsing the timestamps found in activity files
+
+///
+/// parse an RFC 3339 / ISO 8601 date-time such as `2024-05-01T07:30:15.250Z`
+/// or `2024-05-01T09:30:15+02:00` into seconds since the Unix epoch
+///
+/// A missing offset is read as UTC, as GPX and TCX timestamps are UTC.
+///
+/// # Returns
+/// [`Option<f64>`], `None` if the text is not a valid date-time
+///
+pub(crate) fn parse_rfc3339(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (date, time) = text.split_once(['T', 't', ' '])?;
+
+    let mut date_parts = date.splitn(3, '-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: u32 = date_parts.next()?.parse().ok()?;
+    let day: u32 = date_parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (clock, offset) = split_offset(time)?;
+    let mut clock_parts = clock.splitn(3, ':');
+    let hour: f64 = clock_parts.next()?.parse().ok()?;
+    let minute: f64 = clock_parts.next()?.parse().ok()?;
+    let second: f64 = clock_parts.next().unwrap_or("0").parse().ok()?;
+    if hour >= 24.0 || minute >= 60.0 || !(0.0..61.0).contains(&second) {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day) as f64;
+    Some(days * 86_400.0 + hour * 3_600.0 + minute * 60.0 + second - offset)
+}
+
+/// split the time of day from its UTC offset (`Z`, `±hh:mm`, `±hhmm` or `±hh`),
+/// returning the offset in seconds
+fn split_offset(time: &str) -> Option<(&str, f64)> {
+    if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        return Some((clock, 0.0));
+    }
+    match time.rfind(['+', '-']) {
+        Some(index) => {
+            let (clock, offset) = time.split_at(index);
+            let sign = if offset.starts_with('-') { -1.0 } else { 1.0 };
+            let digits = &offset[1..];
+            let (hours, minutes) = match digits.split_once(':') {
+                Some(parts) => parts,
+                None if digits.len() == 4 => digits.split_at(2),
+                None => (digits, "00"),
+            };
+            let two_digits = |text: &str| -> Option<u32> {
+                (text.len() == 2 && text.bytes().all(|b| b.is_ascii_digit()))
+                    .then(|| text.parse().ok())?
+            };
+            let (hours, minutes) = (two_digits(hours)?, two_digits(minutes)?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            Some((clock, sign * f64::from(hours * 3_600 + minutes * 60)))
+        }
+        None => Some((time, 0.0)),
+    }
+}
+
+/// days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rfc3339() {
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0.0));
+        assert_eq!(parse_rfc3339("2024-05-01T07:30:15Z"), Some(1_714_548_615.0));
+        assert_eq!(
+            parse_rfc3339("2024-05-01T09:30:15.5+02:00"),
+            Some(1_714_548_615.5)
+        );
+        assert_eq!(parse_rfc3339("2024-05-01T07:30:15"), Some(1_714_548_615.0));
+        assert_eq!(
+            parse_rfc3339("2024-05-01T09:30:15+0200"),
+            Some(1_714_548_615.0)
+        );
+        assert_eq!(
+            parse_rfc3339("2024-05-01T05:00:15-02:30"),
+            Some(1_714_548_615.0)
+        );
+        assert_eq!(
+            parse_rfc3339("2024-05-01T09:30:15+02"),
+            Some(1_714_548_615.0)
+        );
+        assert_eq!(parse_rfc3339("2024-05-01T07:30:15+24:00"), None);
+        assert_eq!(parse_rfc3339("2024-05-01T07:30:15+02:60"), None);
+        assert_eq!(parse_rfc3339("2024-05-01T07:30:15+200"), None);
+        assert_eq!(parse_rfc3339("2024-13-01T07:30:15Z"), None);
+        assert_eq!(parse_rfc3339("yesterday"), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! module for the hotline hover tooltip functional component
+use leptos::html::Div;
+use leptos::logging::log;
+use leptos::prelude::*;
+use leptos_leaflet::leaflet as L;
+use leptos_leaflet::prelude::LeafletOverlayContainerContext;
+use wasm_bindgen::prelude::*;
+
+use crate::{Color, FlatPosition, Hotline, HotlineContext};
+
+///
+/// point of a hotline under the cursor, passed to the [`HotlineTooltip`] label formatter
+///
+/// # Fields
+/// * `value` [`f64`] raw value interpolated at the cursor
+/// * `index` [`usize`] index of the nearest track point
+/// * `fractional_index` [`f64`] point index with the fraction along the segment
+/// * `distance_along` [`f64`] meters from the start of the track
+/// * `timestamp` [`Option<f64>`] time at the cursor in seconds since the Unix epoch, if the track has timestamps
+/// * `color` [`Color`] color the hotline draws at the cursor
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct HotlineHover {
+    /// raw value interpolated at the cursor
+    pub value: f64,
+    /// index of the nearest track point
+    pub index: usize,
+    /// point index with the fraction along the segment
+    pub fractional_index: f64,
+    /// meters from the start of the track
+    pub distance_along: f64,
+    /// time at the cursor in seconds since the Unix epoch
+    pub timestamp: Option<f64>,
+    /// color the hotline draws at the cursor
+    pub color: Color,
+}
+
+/// tooltip binding held while the hotline layer exists
+type TooltipBinding = (Hotline, Closure<dyn Fn(L::MouseEvent)>);
+
+///
+/// Creates a tooltip that follows the cursor along the enclosing [`crate::HotPolyline`],
+/// showing a formatted label and a swatch of the hotline color under the cursor
+///
+/// Must be a child of [`crate::HotPolyline`].  Leaflet binds one tooltip per layer,
+/// so do not combine it with a leptos-leaflet `Tooltip` child.
+///
+/// # Arguments
+///
+/// * `format` - formats the label for the [`HotlineHover`] point under the cursor
+/// * `direction` - leaflet tooltip direction, `"top"` by default
+/// * `swatch` - whether to show the color swatch, `true` by default
+///
+/// # Returns
+///
+/// `impl` [`leptos::IntoView`]
+///
+/// # Examples
+///
+/// ```ignore
+/// view! {
+///     <HotPolyline positions=positions palette=palette>
+///         <HotlineTooltip format=|hover: HotlineHover| format!("{:.0} bpm", hover.value)/>
+///     </HotPolyline>
+/// }
+/// ```
+///
+#[component]
+pub fn HotlineTooltip(
+    #[prop(into)] format: Callback<(HotlineHover,), String>,
+    #[prop(optional, into, default = "top".into())] direction: Signal<String>,
+    #[prop(optional, default = true)] swatch: bool,
+) -> impl IntoView {
+    let overlay_context = use_context::<LeafletOverlayContainerContext>();
+    let hotline_context = use_context::<HotlineContext>();
+    if hotline_context.is_none() {
+        log!("HotlineTooltip must be a child of HotPolyline.");
+    }
+
+    let hover = RwSignal::new(None::<HotlineHover>);
+    let content = NodeRef::<Div>::new();
+    let binding = StoredValue::new_with_storage(None::<TooltipBinding>);
+
+    Effect::new(move |_| {
+        unbind_tooltip(binding);
+
+        let (Some(overlay_context), Some(hotline_context)) = (overlay_context, hotline_context)
+        else {
+            return;
+        };
+        let (Some(hotline), Some(element)) =
+            (overlay_context.container::<Hotline>(), content.get())
+        else {
+            return;
+        };
+
+        let options = L::TooltipOptions::default();
+        options.set_sticky(true);
+        options.set_direction(direction.get());
+        let tooltip = L::Tooltip::new(&options, Some(hotline.unchecked_ref()));
+        tooltip.set_content(element.unchecked_ref());
+        hotline.bind_tooltip(&tooltip);
+
+        let on_mouse_move = Closure::<dyn Fn(L::MouseEvent)>::new(move |event: L::MouseEvent| {
+            let lat_lng = event.lat_lng();
+            let position = FlatPosition::new(lat_lng.lat(), lat_lng.lng());
+            hover.set(hover_at(hotline_context, &position));
+        });
+        hotline
+            .unchecked_ref::<L::Evented>()
+            .on("mousemove", on_mouse_move.as_ref().unchecked_ref());
+        binding.set_value(Some((hotline, on_mouse_move)));
+    });
+
+    on_cleanup(move || unbind_tooltip(binding));
+
+    view! {
+        <div style="visibility:collapse">
+            <div node_ref=content class="hotline-tooltip" style="display: flex; align-items: center; gap: 4px;">
+                <Show when=move || swatch>
+                    <span
+                        class="hotline-tooltip-swatch"
+                        style=move || {
+                            let color = hover.get().map(|hover| hover.color).unwrap_or(Color::BLACK);
+                            format!(
+                                "display: inline-block; width: 10px; height: 10px; border-radius: 2px; background: {color};",
+                            )
+                        }
+                    ></span>
+                </Show>
+                <span class="hotline-tooltip-label">
+                    {move || hover.get().map(|hover| format.run((hover,))).unwrap_or_default()}
+                </span>
+            </div>
+        </div>
+    }
+}
+
+/// describes the hotline point nearest to a cursor position
+fn hover_at(context: HotlineContext, position: &FlatPosition) -> Option<HotlineHover> {
+    let (projection, value, timestamp) = context.positions.with_untracked(|track| {
+        let projection = track.project(position)?;
+        let value = track.value_at(projection.fractional_index)?;
+        Some((
+            projection,
+            value,
+            track.timestamp_at(projection.fractional_index),
+        ))
+    })?;
+
+    Some(HotlineHover {
+        value: projection.value,
+        index: projection.fractional_index.round() as usize,
+        fractional_index: projection.fractional_index,
+        distance_along: projection.distance_along,
+        timestamp,
+        color: context.color_for_value(value),
+    })
+}
+
+/// removes the tooltip and its mousemove listener from the hotline layer, if bound
+#[inline]
+fn unbind_tooltip(binding: StoredValue<Option<TooltipBinding>, LocalStorage>) {
+    if let Some((hotline, on_mouse_move)) = binding.try_update_value(Option::take).flatten() {
+        hotline
+            .unchecked_ref::<L::Evented>()
+            .off("mousemove", on_mouse_move.as_ref().unchecked_ref());
+        hotline.unbind_tooltip();
+    }
+}
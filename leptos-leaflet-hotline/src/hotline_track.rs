@@ -0,0 +1,504 @@
+//! module for hotline track data structure, keeping positions and values separate
+use std::fmt;
+
+#[cfg(feature = "wasm")]
+use js_sys::Array;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use super::hotline_error::HotlineError;
+use super::hotline_normalization::Normalization;
+use super::hotline_position::{FlatPosition, HotlinePosition, HotlinePositionVec};
+
+///
+/// struct data type for a hotline track
+///
+/// Positions and the values visualized along the path are stored side by side
+/// instead of packing the value into the altitude slot of each position,
+/// so a track can carry any metric (speed, heart rate, ...) independent of
+/// the geometry.
+///
+/// # Fields
+/// * `positions` [`Vec<FlatPosition>`] lat, lng pairs along the path
+/// * `values` [`Vec<f64>`] value for each position, same length as `positions`
+/// * `raw_values` [`Vec<f64>`] value for each position before normalization, shown in
+///   event and tooltip readouts; equal to `values` unless built from a [`HotlinePositionVec`]
+/// * `timestamps` [`Option<Vec<f64>>`] optional time of each position, in seconds since the Unix epoch
+/// * `normalization` [`Normalization`] strategy that turned `raw_values` into `values`
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotlineTrack {
+    positions: Vec<FlatPosition>,
+    values: Vec<f64>,
+    raw_values: Vec<f64>,
+    timestamps: Option<Vec<f64>>,
+    normalization: Normalization,
+}
+
+///
+/// error returned when a [`HotlineTrack`] cannot be constructed
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HotlineTrackError {
+    /// number of positions and number of values differ
+    LengthMismatch {
+        /// number of positions given
+        positions: usize,
+        /// number of values given
+        values: usize,
+    },
+    /// number of positions and number of timestamps differ
+    TimestampLengthMismatch {
+        /// number of positions in the track
+        positions: usize,
+        /// number of timestamps given
+        timestamps: usize,
+    },
+}
+
+impl fmt::Display for HotlineTrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch { positions, values } => write!(
+                f,
+                "hotline track has {positions} positions but {values} values"
+            ),
+            Self::TimestampLengthMismatch {
+                positions,
+                timestamps,
+            } => write!(
+                f,
+                "hotline track has {positions} positions but {timestamps} timestamps"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HotlineTrackError {}
+
+/// an empty track whose values are drawn as given
+impl Default for HotlineTrack {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            positions: Vec::new(),
+            values: Vec::new(),
+            raw_values: Vec::new(),
+            timestamps: None,
+            normalization: Normalization::None,
+        }
+    }
+}
+
+/// implement constructors and accessors for [`HotlineTrack`]
+impl HotlineTrack {
+    ///
+    /// construct a new [`HotlineTrack`] from positions and their values
+    ///
+    /// # Returns
+    /// [`HotlineTrack`], or [`HotlineTrackError::LengthMismatch`] if the
+    /// slices differ in length
+    ///
+    #[inline]
+    pub fn new(positions: Vec<FlatPosition>, values: Vec<f64>) -> Result<Self, HotlineTrackError> {
+        if positions.len() != values.len() {
+            return Err(HotlineTrackError::LengthMismatch {
+                positions: positions.len(),
+                values: values.len(),
+            });
+        }
+        Ok(Self {
+            positions,
+            raw_values: values.clone(),
+            values,
+            timestamps: None,
+            normalization: Normalization::None,
+        })
+    }
+
+    ///
+    /// construct a new [`HotlineTrack`] from arbitrary records, using accessors
+    /// to pick the position and the value to visualize from each record
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use leptos_leaflet_hotline::{FlatPosition, HotlineTrack};
+    ///
+    /// struct Sample { lat: f64, lng: f64, elevation: f64, heart_rate: f64 }
+    ///
+    /// let samples = [
+    ///     Sample { lat: 40.2928, lng: -105.6180, elevation: 3500.0, heart_rate: 120.0 },
+    ///     Sample { lat: 40.2928, lng: -105.6190, elevation: 3510.0, heart_rate: 135.0 },
+    /// ];
+    /// let track = HotlineTrack::from_items(
+    ///     &samples,
+    ///     |s| FlatPosition::new(s.lat, s.lng),
+    ///     |s| s.heart_rate,
+    /// );
+    /// assert_eq!(track.values(), &[120.0, 135.0]);
+    /// ```
+    ///
+    #[must_use]
+    #[inline]
+    pub fn from_items<T>(
+        items: &[T],
+        position: impl Fn(&T) -> FlatPosition,
+        value: impl Fn(&T) -> f64,
+    ) -> Self {
+        let values: Vec<f64> = items.iter().map(&value).collect();
+        Self {
+            positions: items.iter().map(&position).collect(),
+            raw_values: values.clone(),
+            values,
+            timestamps: None,
+            normalization: Normalization::None,
+        }
+    }
+
+    ///
+    /// attach a timestamp to each position, in seconds since the Unix epoch
+    ///
+    /// # Returns
+    /// [`HotlineTrack`], or [`HotlineTrackError::TimestampLengthMismatch`] if the
+    /// number of timestamps differs from the number of positions
+    ///
+    #[inline]
+    pub fn with_timestamps(mut self, timestamps: Vec<f64>) -> Result<Self, HotlineTrackError> {
+        if timestamps.len() != self.len() {
+            return Err(HotlineTrackError::TimestampLengthMismatch {
+                positions: self.len(),
+                timestamps: timestamps.len(),
+            });
+        }
+        self.timestamps = Some(timestamps);
+        Ok(self)
+    }
+
+    /// positions along the path
+    #[must_use]
+    #[inline]
+    pub fn positions(&self) -> &[FlatPosition] {
+        &self.positions
+    }
+
+    /// values visualized along the path
+    #[must_use]
+    #[inline]
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// values before normalization, same length as [`HotlineTrack::values`]
+    #[must_use]
+    #[inline]
+    pub fn raw_values(&self) -> &[f64] {
+        &self.raw_values
+    }
+
+    /// strategy that turned [`HotlineTrack::raw_values`] into [`HotlineTrack::values`]
+    #[must_use]
+    #[inline]
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    ///
+    /// raw value that the track's normalization maps to `value`, e.g. to label a
+    /// palette breakpoint in real units
+    ///
+    /// # Returns
+    /// [`f64`]
+    ///
+    #[must_use]
+    #[inline]
+    pub fn raw_value_for(&self, value: f64) -> f64 {
+        self.normalization.invert(&self.raw_values, value)
+    }
+
+    /// time of each position in seconds since the Unix epoch, if the track has timestamps
+    #[must_use]
+    #[inline]
+    pub fn timestamps(&self) -> Option<&[f64]> {
+        self.timestamps.as_deref()
+    }
+
+    /// value drawn at a fractional point index, e.g. from [`crate::PathProjection`]
+    #[must_use]
+    #[inline]
+    pub fn value_at(&self, fractional_index: f64) -> Option<f64> {
+        interpolate(&self.values, fractional_index)
+    }
+
+    /// timestamp at a fractional point index, if the track has timestamps
+    #[must_use]
+    #[inline]
+    pub fn timestamp_at(&self, fractional_index: f64) -> Option<f64> {
+        self.timestamps
+            .as_deref()
+            .and_then(|timestamps| interpolate(timestamps, fractional_index))
+    }
+
+    /// number of points in the track
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// true if the track has no points
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// iterate over (position, value) pairs
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (FlatPosition, f64)> + '_ {
+        self.positions
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+    }
+
+    ///
+    /// append a point with its raw value to the track; if the track has timestamps,
+    /// the new point's timestamp is NaN
+    ///
+    /// The value is normalized like the rest of the track.  When the normalization
+    /// depends on the values, such as [`Normalization::DivideByMax`], the whole track
+    /// is normalized again, so earlier points may change color.
+    ///
+    pub fn push(&mut self, position: FlatPosition, value: f64) {
+        self.positions.push(position);
+        self.raw_values.push(value);
+        if self.normalization.depends_on_values() {
+            self.values = self.normalization.apply(&self.raw_values);
+        } else {
+            self.values.extend(self.normalization.apply(&[value]));
+        }
+        if let Some(timestamps) = self.timestamps.as_mut() {
+            timestamps.push(f64::NAN);
+        }
+    }
+
+    ///
+    /// check that every position can be placed on a map
+    ///
+    /// # Returns
+    /// [`Result<(), HotlineError>`], [`HotlineError::InvalidPosition`] for the first
+    /// position with a NaN or infinite latitude or longitude
+    ///
+    pub fn check_positions(&self) -> Result<(), HotlineError> {
+        match self
+            .positions()
+            .iter()
+            .position(|position| !(position.lat.is_finite() && position.lng.is_finite()))
+        {
+            Some(index) => Err(HotlineError::InvalidPosition {
+                index,
+                position: self.positions()[index],
+            }),
+            None => Ok(()),
+        }
+    }
+
+    ///
+    /// index of the track point closest to `position`
+    ///
+    /// Distances use an equirectangular approximation, which is accurate at the
+    /// scale of a pointer position on a map.
+    ///
+    /// # Returns
+    /// [`Option<usize>`], `None` for an empty track
+    ///
+    #[must_use]
+    pub fn nearest_index(&self, position: &FlatPosition) -> Option<usize> {
+        let lng_scale = position.lat.to_radians().cos();
+        let distance = |pos: &FlatPosition| {
+            let d_lat = pos.lat - position.lat;
+            let d_lng = (pos.lng - position.lng) * lng_scale;
+            d_lat * d_lat + d_lng * d_lng
+        };
+        self.positions
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+            .map(|(index, _)| index)
+    }
+}
+
+/// implement conversion from [`HotlinePositionVec`], taking the value from each position's `alt`
+/// and keeping its raw values
+impl From<&HotlinePositionVec> for HotlineTrack {
+    #[inline]
+    fn from(value: &HotlinePositionVec) -> Self {
+        let mut track = Self::from_items(&value.positions, |pos| pos.latlng, |pos| pos.alt);
+        if value.raw_values.len() == track.len() {
+            track.raw_values.clone_from(&value.raw_values);
+            track.normalization = value.normalization;
+        }
+        track
+    }
+}
+
+/// implement conversion from [`HotlinePositionVec`], taking the value from each position's `alt`
+impl From<HotlinePositionVec> for HotlineTrack {
+    #[inline]
+    fn from(value: HotlinePositionVec) -> Self {
+        Self::from(&value)
+    }
+}
+
+/// implement conversion to [`HotlinePositionVec`], storing each value unchanged in the position's `alt`
+impl From<&HotlineTrack> for HotlinePositionVec {
+    #[inline]
+    fn from(value: &HotlineTrack) -> Self {
+        Self {
+            positions: value
+                .iter()
+                .map(|(pos, val)| HotlinePosition::new(pos.lat, pos.lng, val))
+                .collect(),
+            raw_values: value.raw_values.clone(),
+            normalization: value.normalization,
+        }
+    }
+}
+
+/// linearly interpolate `values` at a fractional index, clamped to the slice
+#[inline]
+fn interpolate(values: &[f64], fractional_index: f64) -> Option<f64> {
+    let last = values.len().checked_sub(1)?;
+    let index = fractional_index.clamp(0.0, last as f64);
+    let below = index.floor() as usize;
+    let above = index.ceil() as usize;
+    Some(values[below] + (values[above] - values[below]) * (index - below as f64))
+}
+
+/// Creates a JS Array of ```[lat, lng, value]``` arrays for passing to the JS
+/// leaflet-hotline code through wasm bindings.  Leaflet converts each entry with
+/// `L.latLng`, which places the value in the altitude slot leaflet-hotline reads,
+/// so no separate `L.LatLng` binding is needed on the Rust side.
+///
+/// # Args
+/// `track`: hotline track with positions and values.
+///
+/// # Returns
+/// JS Array of arrays containing hotline positions and values.
+///
+#[cfg(feature = "wasm")]
+#[must_use]
+#[inline]
+pub(crate) fn to_hotline_lat_lng_array(track: &HotlineTrack) -> Array {
+    let array = Array::new();
+    for (pos, val) in track.iter() {
+        let point = Array::of3(
+            &JsValue::from_f64(pos.lat),
+            &JsValue::from_f64(pos.lng),
+            &JsValue::from_f64(val),
+        );
+        array.push(&point);
+    }
+    array
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_values_from_position_vec() {
+        let track = HotlineTrack::from(HotlinePositionVec::new(&[
+            (40.0, -105.0, 50.0),
+            (40.1, -105.1, 100.0),
+        ]));
+        assert_eq!(track.values(), &[0.5, 1.0]);
+        assert_eq!(track.raw_values(), &[50.0, 100.0]);
+        assert_eq!(track.normalization(), Normalization::DivideByMax);
+        assert_eq!(track.raw_value_for(0.25), 25.0);
+        assert_eq!(
+            HotlinePositionVec::from(&track).normalization,
+            Normalization::DivideByMax
+        );
+    }
+
+    #[test]
+    fn test_push_normalized() {
+        let mut track = HotlineTrack::from(HotlinePositionVec::new(&[
+            (40.0, -105.0, 10.0),
+            (40.1, -105.1, 20.0),
+        ]));
+        track.push(FlatPosition::new(40.2, -105.2), 40.0);
+        assert_eq!(track.raw_values(), &[10.0, 20.0, 40.0]);
+        assert_eq!(track.values(), &[0.25, 0.5, 1.0]);
+
+        let mut fixed = HotlineTrack::from(HotlinePositionVec::with_normalization(
+            &[(40.0, -105.0, 10.0)],
+            Normalization::FixedDomain { lo: 0.0, hi: 20.0 },
+        ));
+        fixed.push(FlatPosition::new(40.1, -105.1), 5.0);
+        assert_eq!(fixed.values(), &[0.5, 0.25]);
+
+        let mut plain = HotlineTrack::default();
+        plain.push(FlatPosition::new(40.0, -105.0), 7.0);
+        assert_eq!(plain.values(), &[7.0]);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let track = HotlineTrack::from(HotlinePositionVec::new(&[
+            (40.0, -105.0, 50.0),
+            (40.1, -105.1, 100.0),
+        ]));
+        assert_eq!(
+            track.clone().with_timestamps(vec![0.0]),
+            Err(HotlineTrackError::TimestampLengthMismatch {
+                positions: 2,
+                timestamps: 1
+            })
+        );
+
+        let track = track.with_timestamps(vec![1_000.0, 1_060.0]).unwrap();
+        assert_eq!(track.timestamp_at(0.5), Some(1_030.0));
+        assert_eq!(track.timestamp_at(7.0), Some(1_060.0));
+        assert_eq!(track.value_at(0.5), Some(0.75));
+        assert_eq!(HotlineTrack::default().timestamp_at(0.0), None);
+    }
+
+    #[test]
+    fn test_check_positions() {
+        let track = HotlineTrack::new(
+            vec![
+                FlatPosition::new(40.0, -105.0),
+                FlatPosition::new(f64::NAN, -105.1),
+            ],
+            vec![0.0, 1.0],
+        )
+        .unwrap();
+        let Err(HotlineError::InvalidPosition { index, position }) = track.check_positions() else {
+            panic!("expected an invalid position");
+        };
+        assert_eq!(index, 1);
+        assert!(position.lat.is_nan());
+        assert_eq!(HotlineTrack::default().check_positions(), Ok(()));
+    }
+
+    #[test]
+    fn test_nearest_index() {
+        let track = HotlineTrack::from_items(
+            &[(40.0, -105.0), (40.0, -105.01), (40.0, -105.02)],
+            |&(lat, lng)| FlatPosition::new(lat, lng),
+            |_| 0.0,
+        );
+        assert_eq!(
+            track.nearest_index(&FlatPosition::new(40.001, -105.012)),
+            Some(1)
+        );
+        assert_eq!(
+            HotlineTrack::default().nearest_index(&FlatPosition::new(40.0, -105.0)),
+            None
+        );
+    }
+}